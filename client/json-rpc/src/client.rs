@@ -3,6 +3,7 @@
 
 use crate::{errors::JsonRpcError, views::AccountView, JsonRpcResponse};
 use anyhow::{ensure, format_err, Error, Result};
+use libra_crypto::HashValue;
 use libra_types::{account_address::AccountAddress, transaction::SignedTransaction};
 use reqwest::{Client, ClientBuilder, Url};
 use serde_json::{json, Value};
@@ -40,6 +41,25 @@ impl JsonRpcBatch {
         Ok(())
     }
 
+    pub fn add_simulate_transaction_request(
+        &mut self,
+        transaction: SignedTransaction,
+        skip_signature_check: bool,
+    ) -> Result<()> {
+        let txn_payload = hex::encode(lcs::to_bytes(&transaction)?);
+        self.add_request(
+            "simulate_transaction".to_string(),
+            vec![Value::String(txn_payload), json!(skip_signature_check)],
+        );
+        Ok(())
+    }
+
+    pub fn add_estimate_gas_request(&mut self, transaction: SignedTransaction) -> Result<()> {
+        let txn_payload = hex::encode(lcs::to_bytes(&transaction)?);
+        self.add_request("estimate_gas".to_string(), vec![Value::String(txn_payload)]);
+        Ok(())
+    }
+
     pub fn add_get_account_state_request(&mut self, address: AccountAddress) {
         self.add_request(
             "get_account_state".to_string(),
@@ -47,6 +67,20 @@ impl JsonRpcBatch {
         );
     }
 
+    /// `type_tags` are the types, e.g. "0x1::M::S", of resources to decode besides the ones the
+    /// server knows of.
+    pub fn add_get_account_resources_request(
+        &mut self,
+        address: AccountAddress,
+        version: u64,
+        type_tags: Vec<String>,
+    ) {
+        self.add_request(
+            "get_account_resources".to_string(),
+            vec![json!(address.to_string()), json!(version), json!(type_tags)],
+        );
+    }
+
     pub fn add_get_metadata_request(&mut self, version: Option<u64>) {
         self.add_request("get_metadata".to_string(), vec![json!(version)]);
     }
@@ -83,6 +117,24 @@ impl JsonRpcBatch {
         );
     }
 
+    pub fn add_get_transaction_by_hash_request(&mut self, hash: HashValue, include_events: bool) {
+        self.add_request(
+            "get_transaction_by_hash".to_string(),
+            vec![json!(hash.to_hex()), json!(include_events)],
+        );
+    }
+
+    pub fn add_get_mempool_transaction_status_request(
+        &mut self,
+        sender: AccountAddress,
+        sequence_number: u64,
+    ) {
+        self.add_request(
+            "get_mempool_transaction_status".to_string(),
+            vec![json!(sender.to_string()), json!(sequence_number)],
+        );
+    }
+
     pub fn add_get_events_request(&mut self, event_key: String, start: u64, limit: u64) {
         self.add_request(
             "get_events".to_string(),
@@ -90,6 +142,62 @@ impl JsonRpcBatch {
         );
     }
 
+    pub fn add_get_transactions_with_proofs_request(
+        &mut self,
+        start_version: u64,
+        limit: u64,
+        include_events: bool,
+    ) {
+        self.add_request(
+            "get_transactions_with_proofs".to_string(),
+            vec![json!(start_version), json!(limit), json!(include_events)],
+        );
+    }
+
+    pub fn add_get_account_transaction_with_proofs_request(
+        &mut self,
+        account: AccountAddress,
+        sequence: u64,
+        include_events: bool,
+    ) {
+        self.add_request(
+            "get_account_transaction_with_proofs".to_string(),
+            vec![
+                json!(account.to_string()),
+                json!(sequence),
+                json!(include_events),
+            ],
+        );
+    }
+
+    pub fn add_get_events_with_proofs_request(
+        &mut self,
+        event_key: String,
+        start: u64,
+        limit: u64,
+    ) {
+        self.add_request(
+            "get_events_with_proofs".to_string(),
+            vec![json!(event_key), json!(start), json!(limit)],
+        );
+    }
+
+    pub fn add_get_account_state_history_request(
+        &mut self,
+        account: AccountAddress,
+        start_version: u64,
+        limit: u64,
+    ) {
+        self.add_request(
+            "get_account_state_history".to_string(),
+            vec![
+                json!(account.to_string()),
+                json!(start_version),
+                json!(limit),
+            ],
+        );
+    }
+
     pub fn add_get_state_proof_request(&mut self, known_version: u64) {
         self.add_request("get_state_proof".to_string(), vec![json!(known_version)]);
     }
@@ -3,11 +3,15 @@
 
 mod blocking;
 mod client;
+mod pool;
 mod response;
+mod verifying;
 
 pub use blocking::JsonRpcClient;
 pub use client::{
     get_response_from_batch, process_batch_response, JsonRpcAsyncClient, JsonRpcBatch,
 };
 pub use libra_json_rpc_types::{errors, views};
+pub use pool::{JsonRpcPoolClient, JsonRpcPoolConfig};
 pub use response::{JsonRpcResponse, ResponseAsView};
+pub use verifying::VerifyingClient;
@@ -0,0 +1,239 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{process_batch_response, JsonRpcBatch, JsonRpcResponse};
+use anyhow::{ensure, format_err, Result};
+use libra_retrier::{fixed_retry_strategy, retry};
+use libra_types::transaction::Version;
+use reqwest::{
+    blocking::{Client, ClientBuilder},
+    Url,
+};
+use serde_json::Value;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Debug)]
+pub struct JsonRpcPoolConfig {
+    /// Timeout of each HTTP request.
+    pub timeout: Duration,
+    /// How long the result of a health check is trusted for.
+    pub health_check_interval: Duration,
+    /// Number of times a request is retried, on the next best endpoint, after a transport error
+    /// or a stale response.
+    pub max_retries: usize,
+    pub retry_delay_ms: u64,
+    /// Responses served at a ledger version more than this behind the latest one of the healthy
+    /// endpoints are rejected as stale.
+    pub max_version_lag: u64,
+    /// Same as `max_version_lag`, for the timestamp of the ledger.
+    pub max_timestamp_lag: Duration,
+}
+
+impl Default for JsonRpcPoolConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(5),
+            health_check_interval: Duration::from_secs(10),
+            max_retries: 2,
+            retry_delay_ms: 100,
+            max_version_lag: 1_000,
+            max_timestamp_lag: Duration::from_secs(30),
+        }
+    }
+}
+
+/// The ledger a server served a request at, as carried by its responses.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+struct Ledger {
+    version: Version,
+    timestamp_usecs: u64,
+}
+
+struct Endpoint {
+    url: Url,
+    /// The ledger of the last response, `None` until the endpoint answers.
+    ledger: Option<Ledger>,
+    healthy: bool,
+}
+
+/// A JSON-RPC client over several full nodes. Each request goes to the healthy endpoint with the
+/// highest ledger version, and is retried on the next best one after a transport error or a
+/// response which is stale compared to the latest ledger of the healthy endpoints. An endpoint
+/// which fails no longer counts, so that the others aren't rejected as stale for lagging behind
+/// it.
+///
+/// Endpoints are health-checked with `get_metadata` before the first request, then whenever the
+/// last check is older than `JsonRpcPoolConfig::health_check_interval` or no endpoint is healthy.
+pub struct JsonRpcPoolClient {
+    client: Client,
+    endpoints: Vec<Endpoint>,
+    config: JsonRpcPoolConfig,
+    last_health_check: Option<Instant>,
+}
+
+impl JsonRpcPoolClient {
+    pub fn new(urls: Vec<Url>, config: JsonRpcPoolConfig) -> Result<Self> {
+        ensure!(!urls.is_empty(), "No JSON-RPC endpoint is given.");
+        Ok(Self {
+            client: ClientBuilder::new()
+                .use_rustls_tls()
+                .timeout(config.timeout)
+                .build()?,
+            endpoints: urls
+                .into_iter()
+                .map(|url| Endpoint {
+                    url,
+                    ledger: None,
+                    healthy: false,
+                })
+                .collect(),
+            config,
+            last_health_check: None,
+        })
+    }
+
+    pub fn urls(&self) -> impl Iterator<Item = &Url> {
+        self.endpoints.iter().map(|endpoint| &endpoint.url)
+    }
+
+    /// Sends a JSON RPC batched request to the best endpoint, failing over to the others.
+    /// Returns a vector of responses s.t. response order matches the request order
+    pub fn execute(&mut self, batch: JsonRpcBatch) -> Result<Vec<Result<JsonRpcResponse>>> {
+        if batch.requests.is_empty() {
+            return Ok(vec![]);
+        }
+        let retry_strategy =
+            fixed_retry_strategy(self.config.retry_delay_ms, self.config.max_retries);
+        retry(retry_strategy, || {
+            let index = self.select_endpoint();
+            self.send(index, &batch).map_err(|e| {
+                self.endpoints[index].healthy = false;
+                format_err!("Request to {} failed: {}", self.endpoints[index].url, e)
+            })
+        })
+    }
+
+    /// Sends `get_metadata` to every endpoint, which is healthy if it answers with a ledger which
+    /// isn't stale compared to the latest ledger of the ones answering.
+    pub fn health_check(&mut self) {
+        for endpoint in &mut self.endpoints {
+            endpoint.healthy = false;
+        }
+        for index in 0..self.endpoints.len() {
+            let mut batch = JsonRpcBatch::new();
+            batch.add_get_metadata_request(None);
+            // Not checked for staleness yet, as the latest ledger is only known once all answer.
+            self.endpoints[index].healthy = self
+                .fetch(index, &batch)
+                .map_or(false, |responses| responses.iter().all(Result::is_ok));
+        }
+        let stale: Vec<_> = self
+            .endpoints
+            .iter()
+            .map(|endpoint| {
+                endpoint.healthy
+                    && endpoint
+                        .ledger
+                        .map_or(false, |ledger| self.ensure_not_stale(ledger).is_err())
+            })
+            .collect();
+        for (endpoint, stale) in self.endpoints.iter_mut().zip(stale) {
+            if stale {
+                endpoint.healthy = false;
+            }
+        }
+        self.last_health_check = Some(Instant::now());
+    }
+
+    /// Returns the index of the healthy endpoint with the highest ledger version, health-checking
+    /// the endpoints first if that's due. If none is healthy, the one with the highest version is
+    /// tried anyway.
+    fn select_endpoint(&mut self) -> usize {
+        let check_due = self.last_health_check.map_or(true, |last_check| {
+            last_check.elapsed() >= self.config.health_check_interval
+        });
+        if check_due || self.endpoints.iter().all(|endpoint| !endpoint.healthy) {
+            self.health_check();
+        }
+        (0..self.endpoints.len())
+            .max_by_key(|&index| {
+                let endpoint = &self.endpoints[index];
+                (endpoint.healthy, endpoint.ledger)
+            })
+            .expect("Endpoints can't be empty.")
+    }
+
+    /// Sends `batch` to the endpoint at `index` and records the ledger it's served at. Fails on
+    /// transport errors and stale responses.
+    fn send(&mut self, index: usize, batch: &JsonRpcBatch) -> Result<Vec<Result<JsonRpcResponse>>> {
+        let responses = self.fetch(index, batch)?;
+        if let Some(ledger) = self.endpoints[index].ledger {
+            self.ensure_not_stale(ledger)?;
+        }
+        Ok(responses)
+    }
+
+    /// Sends `batch` to the endpoint at `index` and records the ledger it's served at. Fails on
+    /// transport errors only.
+    fn fetch(
+        &mut self,
+        index: usize,
+        batch: &JsonRpcBatch,
+    ) -> Result<Vec<Result<JsonRpcResponse>>> {
+        let responses: Vec<Value> = self
+            .client
+            .post(self.endpoints[index].url.clone())
+            .json(&batch.json_request())
+            .send()?
+            .error_for_status()?
+            .json()?;
+
+        // The calls of a batch are served at the same ledger. Calls rejected before being served,
+        // e.g. for being over the limits of the server, don't carry it.
+        if let Some(ledger) = responses.iter().find_map(response_ledger) {
+            self.endpoints[index].ledger = Some(ledger);
+        }
+        let responses = process_batch_response(batch.clone(), responses)?;
+        ensure!(
+            batch.requests.len() == responses.len(),
+            "received unexpected number of responses in batch"
+        );
+        Ok(responses)
+    }
+
+    /// The latest ledger of the endpoints which are healthy.
+    fn latest_ledger(&self) -> Option<Ledger> {
+        self.endpoints
+            .iter()
+            .filter(|endpoint| endpoint.healthy)
+            .filter_map(|endpoint| endpoint.ledger)
+            .max()
+    }
+
+    fn ensure_not_stale(&self, ledger: Ledger) -> Result<()> {
+        if let Some(latest) = self.latest_ledger() {
+            let max_timestamp_lag_usecs = self.config.max_timestamp_lag.as_micros() as u64;
+            ensure!(
+                ledger.version.saturating_add(self.config.max_version_lag) >= latest.version
+                    && ledger
+                        .timestamp_usecs
+                        .saturating_add(max_timestamp_lag_usecs)
+                        >= latest.timestamp_usecs,
+                "Stale response at ledger version {} (timestamp {}), while the latest of the \
+                 healthy endpoints is {} (timestamp {}).",
+                ledger.version,
+                ledger.timestamp_usecs,
+                latest.version,
+                latest.timestamp_usecs,
+            );
+        }
+        Ok(())
+    }
+}
+
+fn response_ledger(response: &Value) -> Option<Ledger> {
+    Some(Ledger {
+        version: response.get("libra_ledger_version")?.as_u64()?,
+        timestamp_usecs: response.get("libra_ledger_timestampusec")?.as_u64()?,
+    })
+}
@@ -2,8 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::views::{
-    AccountStateWithProofView, AccountView, BlockMetadata, CurrencyInfoView, EventView,
-    StateProofView, TransactionView,
+    AccountResourceView, AccountStateWithProofView, AccountTransactionWithProofView, AccountView,
+    BlockMetadata, CurrencyInfoView, EventView, EventsWithProofView, GasEstimateView,
+    MempoolTransactionStatusView, StateProofView, TransactionByHashView,
+    TransactionListWithProofView, TransactionOutputView, TransactionView,
 };
 use anyhow::{ensure, format_err, Error, Result};
 
@@ -14,14 +16,23 @@ use std::convert::TryFrom;
 #[derive(Clone, PartialEq, Debug)]
 pub enum JsonRpcResponse {
     SubmissionResponse,
+    SimulationResponse(TransactionOutputView),
+    GasEstimateResponse(GasEstimateView),
+    MempoolTransactionStatusResponse(Option<MempoolTransactionStatusView>),
     AccountResponse(Option<AccountView>),
+    AccountResourcesResponse(Option<Vec<AccountResourceView>>),
     StateProofResponse(StateProofView),
     AccountTransactionResponse(Option<TransactionView>),
+    TransactionByHashResponse(Option<TransactionByHashView>),
     TransactionsResponse(Vec<TransactionView>),
     EventsResponse(Vec<EventView>),
     BlockMetadataResponse(BlockMetadata),
     CurrenciesResponse(Vec<CurrencyInfoView>),
     AccountStateWithProofResponse(AccountStateWithProofView),
+    AccountStateHistoryResponse(Vec<u64>),
+    TransactionsWithProofResponse(TransactionListWithProofView),
+    AccountTransactionWithProofResponse(AccountTransactionWithProofView),
+    EventsWithProofResponse(EventsWithProofView),
     NetworkStatusResponse(Number),
     UnknownResponse(Value),
 }
@@ -39,6 +50,14 @@ impl TryFrom<(String, Value)> for JsonRpcResponse {
                 );
                 Ok(JsonRpcResponse::SubmissionResponse)
             }
+            "simulate_transaction" => {
+                let output: TransactionOutputView = serde_json::from_value(value)?;
+                Ok(JsonRpcResponse::SimulationResponse(output))
+            }
+            "estimate_gas" => {
+                let estimate: GasEstimateView = serde_json::from_value(value)?;
+                Ok(JsonRpcResponse::GasEstimateResponse(estimate))
+            }
             "get_account_state" => {
                 let account = match value {
                     Value::Null => None,
@@ -67,6 +86,30 @@ impl TryFrom<(String, Value)> for JsonRpcResponse {
                     account_with_proof,
                 ))
             }
+            "get_account_resources" => {
+                let resources = match value {
+                    Value::Null => None,
+                    _ => {
+                        let resources: Vec<AccountResourceView> = serde_json::from_value(value)?;
+                        Some(resources)
+                    }
+                };
+                Ok(JsonRpcResponse::AccountResourcesResponse(resources))
+            }
+            "get_account_state_history" => {
+                let versions: Vec<u64> = serde_json::from_value(value)?;
+                Ok(JsonRpcResponse::AccountStateHistoryResponse(versions))
+            }
+            "get_mempool_transaction_status" => {
+                let status = match value {
+                    Value::Null => None,
+                    _ => {
+                        let status: MempoolTransactionStatusView = serde_json::from_value(value)?;
+                        Some(status)
+                    }
+                };
+                Ok(JsonRpcResponse::MempoolTransactionStatusResponse(status))
+            }
             "get_state_proof" => {
                 let state_proof: StateProofView = serde_json::from_value(value)?;
                 Ok(JsonRpcResponse::StateProofResponse(state_proof))
@@ -81,10 +124,32 @@ impl TryFrom<(String, Value)> for JsonRpcResponse {
                 };
                 Ok(JsonRpcResponse::AccountTransactionResponse(txn))
             }
+            "get_transaction_by_hash" => {
+                let txn = match value {
+                    Value::Null => None,
+                    _ => {
+                        let txn: TransactionByHashView = serde_json::from_value(value)?;
+                        Some(txn)
+                    }
+                };
+                Ok(JsonRpcResponse::TransactionByHashResponse(txn))
+            }
             "get_transactions" => {
                 let txns: Vec<TransactionView> = serde_json::from_value(value)?;
                 Ok(JsonRpcResponse::TransactionsResponse(txns))
             }
+            "get_transactions_with_proofs" => {
+                let txns: TransactionListWithProofView = serde_json::from_value(value)?;
+                Ok(JsonRpcResponse::TransactionsWithProofResponse(txns))
+            }
+            "get_account_transaction_with_proofs" => {
+                let txn: AccountTransactionWithProofView = serde_json::from_value(value)?;
+                Ok(JsonRpcResponse::AccountTransactionWithProofResponse(txn))
+            }
+            "get_events_with_proofs" => {
+                let events: EventsWithProofView = serde_json::from_value(value)?;
+                Ok(JsonRpcResponse::EventsWithProofResponse(events))
+            }
             "get_network_status" => {
                 let connected_peers_count: Number = serde_json::from_value(value)?;
                 Ok(JsonRpcResponse::NetworkStatusResponse(
@@ -156,6 +221,26 @@ impl ResponseAsView for CurrencyInfoView {
     }
 }
 
+impl ResponseAsView for MempoolTransactionStatusView {
+    fn optional_from_response(response: JsonRpcResponse) -> Result<Option<Self>> {
+        if let JsonRpcResponse::MempoolTransactionStatusResponse(view) = response {
+            Ok(view)
+        } else {
+            Self::unexpected_response_error::<Option<Self>>(response)
+        }
+    }
+}
+
+impl ResponseAsView for TransactionByHashView {
+    fn optional_from_response(response: JsonRpcResponse) -> Result<Option<Self>> {
+        if let JsonRpcResponse::TransactionByHashResponse(view) = response {
+            Ok(view)
+        } else {
+            Self::unexpected_response_error::<Option<Self>>(response)
+        }
+    }
+}
+
 impl ResponseAsView for TransactionView {
     fn optional_from_response(response: JsonRpcResponse) -> Result<Option<Self>> {
         if let JsonRpcResponse::AccountTransactionResponse(view) = response {
@@ -174,6 +259,26 @@ impl ResponseAsView for TransactionView {
     }
 }
 
+impl ResponseAsView for TransactionOutputView {
+    fn from_response(response: JsonRpcResponse) -> Result<Self> {
+        if let JsonRpcResponse::SimulationResponse(view) = response {
+            Ok(view)
+        } else {
+            Self::unexpected_response_error::<Self>(response)
+        }
+    }
+}
+
+impl ResponseAsView for GasEstimateView {
+    fn from_response(response: JsonRpcResponse) -> Result<Self> {
+        if let JsonRpcResponse::GasEstimateResponse(view) = response {
+            Ok(view)
+        } else {
+            Self::unexpected_response_error::<Self>(response)
+        }
+    }
+}
+
 impl ResponseAsView for StateProofView {
     fn from_response(response: JsonRpcResponse) -> Result<Self> {
         if let JsonRpcResponse::StateProofResponse(view) = response {
@@ -193,3 +298,33 @@ impl ResponseAsView for AccountStateWithProofView {
         }
     }
 }
+
+impl ResponseAsView for TransactionListWithProofView {
+    fn from_response(response: JsonRpcResponse) -> Result<Self> {
+        if let JsonRpcResponse::TransactionsWithProofResponse(view) = response {
+            Ok(view)
+        } else {
+            Self::unexpected_response_error::<Self>(response)
+        }
+    }
+}
+
+impl ResponseAsView for AccountTransactionWithProofView {
+    fn from_response(response: JsonRpcResponse) -> Result<Self> {
+        if let JsonRpcResponse::AccountTransactionWithProofResponse(view) = response {
+            Ok(view)
+        } else {
+            Self::unexpected_response_error::<Self>(response)
+        }
+    }
+}
+
+impl ResponseAsView for EventsWithProofView {
+    fn from_response(response: JsonRpcResponse) -> Result<Self> {
+        if let JsonRpcResponse::EventsWithProofResponse(view) = response {
+            Ok(view)
+        } else {
+            Self::unexpected_response_error::<Self>(response)
+        }
+    }
+}
@@ -0,0 +1,378 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    views::{
+        AccountStateWithProofView, AccountTransactionWithProofView, EventView, EventsWithProofView,
+        StateProofView, TransactionListWithProofView, TransactionView,
+    },
+    JsonRpcBatch, JsonRpcClient, JsonRpcResponse, ResponseAsView,
+};
+use anyhow::{ensure, format_err, Result};
+use libra_crypto::hash::CryptoHash;
+use libra_types::{
+    account_address::AccountAddress,
+    account_state::AccountState,
+    account_state_blob::{AccountStateBlob, AccountStateWithProof},
+    contract_event::{ContractEvent, EventWithProof},
+    epoch_change::EpochChangeProof,
+    event::EventKey,
+    ledger_info::LedgerInfoWithSignatures,
+    proof::AccumulatorConsistencyProof,
+    transaction::{
+        Transaction, TransactionInfo, TransactionListWithProof, TransactionWithProof, Version,
+    },
+    trusted_state::{TrustedState, TrustedStateChange},
+    waypoint::Waypoint,
+};
+use reqwest::Url;
+use std::{cmp::min, convert::TryFrom};
+
+/// A JSON-RPC client which doesn't trust the server. Starting from a waypoint, it ratchets its
+/// `TrustedState` across epoch changes with the state proof requested along with every call, and
+/// verifies each response against the ledger info of that state proof. Responses which don't
+/// verify, including stale ones, are rejected with an error.
+///
+/// Account states are verified with their sparse Merkle proofs, transactions and events with
+/// their accumulator proofs, as returned by the `_with_proofs` methods. The views returned are
+/// built from the verified data.
+pub struct VerifyingClient {
+    client: JsonRpcClient,
+    /// The latest verified chain state.
+    trusted_state: TrustedState,
+    /// The ledger info the latest responses were verified against. This is `None` until the first
+    /// call.
+    latest_li: Option<LedgerInfoWithSignatures>,
+}
+
+impl VerifyingClient {
+    pub fn new(url: Url, waypoint: Waypoint) -> Result<Self> {
+        Ok(Self {
+            client: JsonRpcClient::new(url)?,
+            trusted_state: TrustedState::from(waypoint),
+            latest_li: None,
+        })
+    }
+
+    pub fn trusted_state(&self) -> &TrustedState {
+        &self.trusted_state
+    }
+
+    pub fn latest_ledger_info(&self) -> Option<&LedgerInfoWithSignatures> {
+        self.latest_li.as_ref()
+    }
+
+    /// Ratchets the trusted state to the latest ledger info of the server.
+    pub fn update_trusted_state(&mut self) -> Result<&TrustedState> {
+        self.execute_with_state_proof(JsonRpcBatch::new())?;
+        Ok(&self.trusted_state)
+    }
+
+    /// Gets the verified latest state of `account`, returning `None` if the account doesn't exist,
+    /// along with the version the state is seen at.
+    pub fn get_account_state(
+        &mut self,
+        account: AccountAddress,
+    ) -> Result<(Option<AccountStateBlob>, Version)> {
+        let mut batch = JsonRpcBatch::new();
+        batch.add_get_account_state_with_proof_request(account, None, None);
+        let (li, mut responses) = self.execute_with_state_proof(batch)?;
+
+        let blob = verify_account_state(&li, account, responses.remove(0)?)?;
+        Ok((blob, li.ledger_info().version()))
+    }
+
+    /// Gets the verified transactions in range (start_version..start_version + limit - 1). Fewer
+    /// are returned only if the ledger doesn't have as many yet.
+    pub fn get_transactions(
+        &mut self,
+        start_version: Version,
+        limit: u64,
+        include_events: bool,
+    ) -> Result<Vec<TransactionView>> {
+        let mut batch = JsonRpcBatch::new();
+        batch.add_get_transactions_with_proofs_request(start_version, limit, include_events);
+        let (li, mut responses) = self.execute_with_state_proof(batch)?;
+
+        let view = TransactionListWithProofView::from_response(responses.remove(0)?)?;
+        let (txns_li, txn_list_with_proof): (LedgerInfoWithSignatures, TransactionListWithProof) =
+            TryFrom::try_from(&view)?;
+        ensure_same_ledger_info(&li, &txns_li)?;
+
+        let ledger_version = li.ledger_info().version();
+        let expected_len = if start_version > ledger_version {
+            0
+        } else {
+            min(limit, ledger_version - start_version + 1)
+        };
+        ensure!(
+            txn_list_with_proof.transactions.len() as u64 == expected_len,
+            "{} transactions returned from version {}, expected {}.",
+            txn_list_with_proof.transactions.len(),
+            start_version,
+            expected_len,
+        );
+        ensure!(
+            expected_len == 0 || txn_list_with_proof.events.is_some() == include_events,
+            "Events returned don't match the request (include_events: {}).",
+            include_events,
+        );
+        let first_version = if expected_len == 0 {
+            None
+        } else {
+            Some(start_version)
+        };
+        txn_list_with_proof.verify(li.ledger_info(), first_version)?;
+
+        let TransactionListWithProof {
+            transactions,
+            events,
+            proof,
+            ..
+        } = txn_list_with_proof;
+        let mut events = events.map(Vec::into_iter);
+        Ok((start_version..)
+            .zip(transactions)
+            .zip(proof.transaction_infos())
+            .map(|((version, txn), txn_info)| {
+                let txn_events = events.as_mut().and_then(Iterator::next);
+                transaction_view(version, txn, txn_events, txn_info)
+            })
+            .collect())
+    }
+
+    /// Gets the verified transaction sent by `account` with sequence number `sequence_number`.
+    /// That the transaction doesn't exist is verified with the sequence number of the account.
+    pub fn get_account_transaction(
+        &mut self,
+        account: AccountAddress,
+        sequence_number: u64,
+        include_events: bool,
+    ) -> Result<Option<TransactionView>> {
+        let mut batch = JsonRpcBatch::new();
+        batch.add_get_account_transaction_with_proofs_request(
+            account,
+            sequence_number,
+            include_events,
+        );
+        batch.add_get_account_state_with_proof_request(account, None, None);
+        let (li, mut responses) = self.execute_with_state_proof(batch)?;
+
+        let view = AccountTransactionWithProofView::from_response(responses.remove(0)?)?;
+        let (txn_li, txn_with_proof): (LedgerInfoWithSignatures, Option<TransactionWithProof>) =
+            TryFrom::try_from(&view)?;
+        ensure_same_ledger_info(&li, &txn_li)?;
+
+        match txn_with_proof {
+            Some(txn_with_proof) => {
+                ensure!(
+                    txn_with_proof.events.is_some() == include_events,
+                    "Events returned don't match the request (include_events: {}).",
+                    include_events,
+                );
+                txn_with_proof.verify_user_txn(
+                    li.ledger_info(),
+                    txn_with_proof.version,
+                    account,
+                    sequence_number,
+                )?;
+                Ok(Some(transaction_view(
+                    txn_with_proof.version,
+                    txn_with_proof.transaction,
+                    txn_with_proof.events,
+                    txn_with_proof.proof.transaction_info(),
+                )))
+            }
+            None => {
+                let blob = verify_account_state(&li, account, responses.remove(0)?)?;
+                let account_sequence_number = match blob {
+                    Some(blob) => AccountState::try_from(&blob)?
+                        .get_account_resource()?
+                        .map_or(0, |resource| resource.sequence_number()),
+                    None => 0,
+                };
+                ensure!(
+                    sequence_number >= account_sequence_number,
+                    "Transaction {} of account {} is missing, while the account has sent {}.",
+                    sequence_number,
+                    account,
+                    account_sequence_number,
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    /// Gets the verified events of the event stream `event_key`, with consecutive sequence numbers
+    /// starting from `start`. Fewer than `limit` events are accepted only if the count of the
+    /// stream's event handle, in the verified state of the account which created it, shows that
+    /// the stream ends there.
+    pub fn get_events(
+        &mut self,
+        event_key: &EventKey,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<EventView>> {
+        let account = event_key.get_creator_address();
+        let mut batch = JsonRpcBatch::new();
+        batch.add_get_events_with_proofs_request(hex::encode(event_key.as_bytes()), start, limit);
+        batch.add_get_account_state_with_proof_request(account, None, None);
+        let (li, mut responses) = self.execute_with_state_proof(batch)?;
+
+        let view = EventsWithProofView::from_response(responses.remove(0)?)?;
+        let (events_li, events_with_proof): (LedgerInfoWithSignatures, Vec<EventWithProof>) =
+            TryFrom::try_from(&view)?;
+        ensure_same_ledger_info(&li, &events_li)?;
+
+        let num_events = events_with_proof.len() as u64;
+        if num_events != limit {
+            let blob = verify_account_state(&li, account, responses.remove(0)?)?;
+            let count = match blob {
+                Some(blob) => Some(event_stream_count(
+                    &AccountState::try_from(&blob)?,
+                    event_key,
+                )?),
+                None => None,
+            }
+            .ok_or_else(|| {
+                format_err!(
+                    "{} events returned, while the limit is {} and the length of event stream {} \
+                     is unknown.",
+                    num_events,
+                    limit,
+                    event_key,
+                )
+            })?;
+            ensure!(
+                num_events == min(limit, count.saturating_sub(start)),
+                "{} events returned from {}, while the limit is {} and event stream {} has {}.",
+                num_events,
+                start,
+                limit,
+                event_key,
+                count,
+            );
+        }
+
+        // Each event is verified to have the sequence number following the previous one's.
+        (start..)
+            .zip(events_with_proof)
+            .map(|(sequence_number, event_with_proof)| {
+                event_with_proof.verify(
+                    li.ledger_info(),
+                    event_key,
+                    sequence_number,
+                    event_with_proof.transaction_version,
+                    event_with_proof.event_index,
+                )?;
+                Ok(EventView::from((
+                    event_with_proof.transaction_version,
+                    event_with_proof.event,
+                )))
+            })
+            .collect()
+    }
+
+    /// Executes `batch` along with a state proof request, ratchets the trusted state with the
+    /// state proof and returns the ledger info verified, with which the responses to `batch` are
+    /// to be verified.
+    fn execute_with_state_proof(
+        &mut self,
+        mut batch: JsonRpcBatch,
+    ) -> Result<(LedgerInfoWithSignatures, Vec<Result<JsonRpcResponse>>)> {
+        let state_proof_index = batch.requests.len();
+        batch.add_get_state_proof_request(self.trusted_state.latest_version());
+        let mut responses = self.client.execute(batch)?;
+
+        let state_proof = StateProofView::from_response(responses.remove(state_proof_index)?)?;
+        let li = self.verify_state_proof(&state_proof)?;
+        Ok((li, responses))
+    }
+
+    fn verify_state_proof(
+        &mut self,
+        state_proof: &StateProofView,
+    ) -> Result<LedgerInfoWithSignatures> {
+        let (li, epoch_change_proof, _): (
+            LedgerInfoWithSignatures,
+            EpochChangeProof,
+            AccumulatorConsistencyProof,
+        ) = TryFrom::try_from(state_proof)?;
+
+        // Stale ledger infos are rejected here.
+        match self
+            .trusted_state
+            .verify_and_ratchet(&li, &epoch_change_proof)?
+        {
+            TrustedStateChange::Epoch { new_state, .. }
+            | TrustedStateChange::Version { new_state } => self.trusted_state = new_state,
+            TrustedStateChange::NoChange => (),
+        }
+        self.latest_li = Some(li.clone());
+        Ok(li)
+    }
+}
+
+/// The requests of a batch are served against the same ledger info, so the one a response is
+/// anchored to must be the one verified with the state proof.
+fn ensure_same_ledger_info(
+    verified_li: &LedgerInfoWithSignatures,
+    li: &LedgerInfoWithSignatures,
+) -> Result<()> {
+    ensure!(
+        verified_li.ledger_info() == li.ledger_info(),
+        "Response is anchored to ledger info at version {}, while the verified one is at {}.",
+        li.ledger_info().version(),
+        verified_li.ledger_info().version(),
+    );
+    Ok(())
+}
+
+/// Verifies the latest state of `account` against `li`.
+fn verify_account_state(
+    li: &LedgerInfoWithSignatures,
+    account: AccountAddress,
+    response: JsonRpcResponse,
+) -> Result<Option<AccountStateBlob>> {
+    let view = AccountStateWithProofView::from_response(response)?;
+    let account_state_with_proof = AccountStateWithProof::try_from(&view)?;
+    account_state_with_proof.verify(li.ledger_info(), li.ledger_info().version(), account)?;
+    Ok(account_state_with_proof.blob)
+}
+
+/// Returns the count of the event stream `event_key`, i.e. the number of events in it, from its
+/// handle in the account resource of `account_state`. Only the streams of sent and received
+/// payments are known, so any other stream fails.
+fn event_stream_count(account_state: &AccountState, event_key: &EventKey) -> Result<u64> {
+    let account_resource = account_state
+        .get_account_resource()?
+        .ok_or_else(|| format_err!("Account resource of event stream {} not found.", event_key))?;
+    [
+        account_resource.sent_events(),
+        account_resource.received_events(),
+    ]
+    .iter()
+    .find(|handle| handle.key() == event_key)
+    .map(|handle| handle.count())
+    .ok_or_else(|| format_err!("Event stream {} is not of a known event handle.", event_key))
+}
+
+fn transaction_view(
+    version: Version,
+    txn: Transaction,
+    events: Option<Vec<ContractEvent>>,
+    txn_info: &TransactionInfo,
+) -> TransactionView {
+    TransactionView {
+        version,
+        hash: txn.hash().to_string(),
+        transaction: txn.into(),
+        events: events
+            .unwrap_or_default()
+            .into_iter()
+            .map(|event| EventView::from((version, event)))
+            .collect(),
+        vm_status: txn_info.major_status(),
+        gas_used: txn_info.gas_used(),
+    }
+}
@@ -15,6 +15,9 @@
 //! during the previous interval.
 //!
 //! If fine-grained flow control is desired, the refill interval should be kept small.
+//!
+//! `TokenBucket` is the same token-bucket without a stream, for callers which reject the elements
+//! over the limit rather than wait for new tokens, e.g. a server rate-limiting each client.
 
 use futures::{
     future::{Future, FutureExt},
@@ -23,7 +26,13 @@ use futures::{
 };
 use futures_semaphore::Semaphore;
 use pin_project::pin_project;
-use std::{mem::ManuallyDrop, pin::Pin, task, task::Poll, time::Duration};
+use std::{
+    mem::ManuallyDrop,
+    pin::Pin,
+    task,
+    task::Poll,
+    time::{Duration, Instant},
+};
 use tokio::time::{interval, Interval};
 
 /// Config parameters for a rate-limiter.
@@ -113,6 +122,52 @@ impl<T: Stream> Stream for RateLimiter<T> {
     }
 }
 
+/// A token-bucket refilled like the one of `RateLimiter`: it starts with `capacity` tokens and
+/// is refilled to `capacity` once `refill_interval` has passed since the last refill. Time is
+/// passed in by the caller, so that no task is needed for the refill.
+#[derive(Debug)]
+pub struct TokenBucket {
+    capacity: usize,
+    refill_interval: Duration,
+    tokens: usize,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(refill_interval: Duration, capacity: usize, now: Instant) -> Self {
+        Self {
+            capacity,
+            refill_interval,
+            tokens: capacity,
+            last_refill: now,
+        }
+    }
+
+    /// Takes `count` tokens if there are as many left, returning whether they were taken.
+    pub fn try_acquire(&mut self, count: usize, now: Instant) -> bool {
+        self.refill(now);
+        if count > self.tokens {
+            return false;
+        }
+        self.tokens -= count;
+        true
+    }
+
+    /// Whether no token is taken as of `now`, in which case the bucket is no different from a new
+    /// one.
+    pub fn is_full(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        self.tokens == self.capacity
+    }
+
+    fn refill(&mut self, now: Instant) {
+        if now.saturating_duration_since(self.last_refill) >= self.refill_interval {
+            self.tokens = self.capacity;
+            self.last_refill = now;
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -174,4 +229,26 @@ mod test {
             assert_eq!(count, 100);
         }
     }
+
+    #[test]
+    fn test_token_bucket() {
+        let start = Instant::now();
+        let interval = Duration::from_millis(100);
+        let mut bucket = TokenBucket::new(interval, 10, start);
+        assert!(bucket.is_full(start));
+
+        assert!(bucket.try_acquire(4, start));
+        assert!(!bucket.is_full(start));
+        // Tokens are either all taken or none.
+        assert!(!bucket.try_acquire(7, start));
+        assert!(bucket.try_acquire(6, start));
+        assert!(!bucket.try_acquire(1, start + interval / 2));
+
+        // Refilled to capacity, but not beyond.
+        let refill_time = start + interval;
+        assert!(bucket.is_full(refill_time));
+        assert!(!bucket.try_acquire(11, refill_time));
+        assert!(bucket.try_acquire(10, refill_time));
+        assert!(!bucket.try_acquire(1, refill_time + interval / 2));
+    }
 }
@@ -159,6 +159,8 @@ impl ValidatorConfig {
             let db_rw = DbReaderWriter::new(LibraDB::open(
                 &path, false, /* readonly */
                 None,  /* pruner */
+                None,  /* ledger_pruner */
+                false, /* account_state_history */
             )?);
             let waypoint = db_bootstrapper::bootstrap_db_if_empty::<LibraVM>(&db_rw, &genesis)?
                 .ok_or_else(|| format_err!("Failed to bootstrap empty DB."))?;
@@ -209,8 +209,8 @@ fn compute_genesis(
     genesis_path: &PathBuf,
     db_path: &Path,
 ) -> Result<(DbReaderWriter, Waypoint), Error> {
-    let libradb =
-        LibraDB::open(db_path, false, None).map_err(|e| Error::UnexpectedError(e.to_string()))?;
+    let libradb = LibraDB::open(db_path, false, None, None, false)
+        .map_err(|e| Error::UnexpectedError(e.to_string()))?;
     let db_rw = DbReaderWriter::new(libradb);
 
     let mut file = File::open(genesis_path)
@@ -32,8 +32,8 @@ impl CreateWaypoint {
         let genesis = genesis_helper.execute()?;
 
         let path = TempPath::new();
-        let libradb =
-            LibraDB::open(&path, false, None).map_err(|e| Error::UnexpectedError(e.to_string()))?;
+        let libradb = LibraDB::open(&path, false, None, None, false)
+            .map_err(|e| Error::UnexpectedError(e.to_string()))?;
         let db_rw = DbReaderWriter::new(libradb);
 
         let waypoint = db_bootstrapper::bootstrap_db_if_empty::<LibraVM>(&db_rw, &genesis)
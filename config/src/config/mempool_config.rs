@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default, deny_unknown_fields)]
@@ -12,12 +13,28 @@ pub struct MempoolConfig {
     pub shared_mempool_batch_size: usize,
     pub shared_mempool_max_concurrent_inbound_syncs: usize,
     pub shared_mempool_min_broadcast_recipient_count: Option<usize>,
+    // broadcast the hashes of transactions, so that peers request only the ones they miss, instead
+    // of the transactions themselves
+    pub shared_mempool_inventory_mode: bool,
     pub max_broadcasts_per_peer: usize,
     pub capacity: usize,
     // max number of transactions per user in Mempool
     pub capacity_per_user: usize,
+    // min increase of the gas unit price, in percent, for a transaction to replace the one in
+    // Mempool with the same sender and sequence number
+    pub replace_by_fee_min_bump_percent: u64,
     pub system_transaction_timeout_secs: u64,
     pub system_transaction_gc_interval_ms: u64,
+    // journal of the accepted transactions, replayed on startup so that they survive a restart.
+    // None disables it. A relative path is relative to the data directory
+    pub journal_path: Option<PathBuf>,
+    // rules that transactions must pass to be admitted into Mempool, reloaded every
+    // `admission_policy_reload_interval_ms` so that they can be changed without a restart.
+    // None admits all transactions. A relative path is relative to the data directory
+    pub admission_policy_path: Option<PathBuf>,
+    pub admission_policy_reload_interval_ms: u64,
+    #[serde(skip)]
+    data_dir: PathBuf,
 }
 
 impl Default for MempoolConfig {
@@ -29,11 +46,43 @@ impl Default for MempoolConfig {
             shared_mempool_batch_size: 100,
             shared_mempool_max_concurrent_inbound_syncs: 100,
             shared_mempool_min_broadcast_recipient_count: None,
+            shared_mempool_inventory_mode: false,
             max_broadcasts_per_peer: 25,
             capacity: 1_000_000,
             capacity_per_user: 100,
+            replace_by_fee_min_bump_percent: 10,
             system_transaction_timeout_secs: 86400,
             system_transaction_gc_interval_ms: 180_000,
+            journal_path: None,
+            admission_policy_path: None,
+            admission_policy_reload_interval_ms: 10_000,
+            data_dir: PathBuf::from("/opt/libra/data/common"),
+        }
+    }
+}
+
+impl MempoolConfig {
+    pub fn journal_path(&self) -> Option<PathBuf> {
+        self.journal_path
+            .as_ref()
+            .map(|path| self.resolve_path(path))
+    }
+
+    pub fn admission_policy_path(&self) -> Option<PathBuf> {
+        self.admission_policy_path
+            .as_ref()
+            .map(|path| self.resolve_path(path))
+    }
+
+    pub fn set_data_dir(&mut self, data_dir: PathBuf) {
+        self.data_dir = data_dir;
+    }
+
+    fn resolve_path(&self, path: &Path) -> PathBuf {
+        if path.is_relative() {
+            self.data_dir.join(path)
+        } else {
+            path.to_path_buf()
         }
     }
 }
@@ -9,6 +9,23 @@ use std::net::SocketAddr;
 #[serde(default, deny_unknown_fields)]
 pub struct RpcConfig {
     pub address: SocketAddr,
+    // max number of calls in a batch request
+    pub batch_size_limit: usize,
+    // max size of a request body in bytes
+    pub content_length_limit: u64,
+    // max `limit` of the paginated methods, e.g. `get_transactions` and `get_events`
+    pub page_size_limit: u64,
+    // rate limit of the calls from each IP, none if unset
+    pub rate_limit: Option<RpcRateLimitConfig>,
+    // origins allowed to call from browsers, "*" for any; CORS requests are refused if empty
+    pub cors_allowed_origins: Vec<String>,
+    // max number of active subscriptions over each WebSocket connection
+    pub subscriptions_per_connection_limit: usize,
+    // enables `simulate_transaction` and `estimate_gas`, which execute the transactions of callers
+    // in the VM
+    pub simulation_enabled: bool,
+    // max gas amount of the transactions executed by `simulate_transaction` and `estimate_gas`
+    pub simulation_max_gas_amount: u64,
 }
 
 pub const DEFAULT_JSON_RPC_PORT: u16 = 8080;
@@ -19,6 +36,14 @@ impl Default for RpcConfig {
             address: format!("0.0.0.0:{}", DEFAULT_JSON_RPC_PORT)
                 .parse()
                 .unwrap(),
+            batch_size_limit: 20,
+            content_length_limit: 256 * 1024,
+            page_size_limit: 1000,
+            rate_limit: None,
+            cors_allowed_origins: vec![],
+            subscriptions_per_connection_limit: 10,
+            simulation_enabled: false,
+            simulation_max_gas_amount: 1_000_000,
         }
     }
 }
@@ -28,3 +53,12 @@ impl RpcConfig {
         self.address.set_port(utils::get_available_port());
     }
 }
+
+/// Token-bucket rate limit: each IP can make `calls_per_interval` calls, and is allowed as many
+/// again `refill_interval_ms` after its bucket was last refilled.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RpcRateLimitConfig {
+    pub calls_per_interval: usize,
+    pub refill_interval_ms: u64,
+}
@@ -15,6 +15,12 @@ pub struct StorageConfig {
     /// None disables pruning. The windows is in number of versions, consider system tps
     /// (transaction per second) when calculating proper window.
     pub prune_window: Option<u64>,
+    /// Same as `prune_window`, but for the ledger history: transactions, transaction infos and
+    /// events. None disables pruning them.
+    pub ledger_prune_window: Option<u64>,
+    /// Maintains an index of the versions at which each account's state changed, which serves
+    /// `get_account_state_history` queries. The index is complete only since it's enabled.
+    pub account_state_history: bool,
     #[serde(skip)]
     data_dir: PathBuf,
 }
@@ -27,6 +33,8 @@ impl Default for StorageConfig {
             dir: PathBuf::from("libradb/db"),
             grpc_max_receive_len: Some(100_000_000),
             prune_window: None,
+            ledger_prune_window: None,
+            account_state_history: false,
             data_dir: PathBuf::from("/opt/libra/data/common"),
         }
     }
@@ -39,6 +39,8 @@ fn main() -> Result<()> {
             &opt.db_dir,
             false, /* readonly */
             None,  /* pruner */
+            None,  /* ledger_pruner */
+            false, /* account_state_history */
         )
         .with_context(|| format_err!("Failed to open DB."))?,
     );
@@ -268,6 +268,8 @@ fn create_storage_service_and_executor(
             &config.storage.dir(),
             false, /* readonly */
             None,  /* pruner */
+            None,  /* ledger_pruner */
+            false, /* account_state_history */
         )
         .expect("DB should open."),
     );
@@ -0,0 +1,52 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+use libra_config::config::NodeConfig;
+use libra_json_rpc::bootstrap_secondary;
+use std::{net::SocketAddr, path::PathBuf, time::Duration};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    about = "Serves JSON RPC from the DB of a running node, without going through the node"
+)]
+struct Args {
+    #[structopt(short = "f", long, parse(from_os_str))]
+    /// Path to the NodeConfig of the node, whose DB and JSON RPC settings are used
+    config: PathBuf,
+    #[structopt(long, parse(from_os_str))]
+    /// Dir to hold the RocksDB info logs of the secondary DB instance
+    secondary_dir: PathBuf,
+    #[structopt(long, default_value = "1000")]
+    /// Milliseconds between catching up with what the node has written
+    catch_up_interval_ms: u64,
+    #[structopt(long)]
+    /// Address to listen on, instead of the one in the NodeConfig, which the node listens on
+    address: Option<SocketAddr>,
+}
+
+fn main() {
+    let args = Args::from_args();
+
+    let config = NodeConfig::load(args.config).expect("Failed to load node config");
+    libra_logger::Logger::new().init();
+
+    let mut rpc_config = config.rpc.clone();
+    if let Some(address) = args.address {
+        rpc_config.address = address;
+    }
+    let _runtime = bootstrap_secondary(
+        &rpc_config,
+        &config.storage.dir(),
+        &args.secondary_dir,
+        Duration::from_millis(args.catch_up_interval_ms),
+        config.base.role,
+    )
+    .expect("Failed to start JSON RPC");
+
+    loop {
+        std::thread::park();
+    }
+}
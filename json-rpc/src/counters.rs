@@ -23,7 +23,8 @@ pub static INVALID_REQUESTS: Lazy<IntGaugeVec> = Lazy::new(|| {
         "libra_client_service_invalid_requests_count",
         "Cumulative number of invalid requests that JSON RPC client service receives",
         &[
-            "type", // categories of invalid requests: "invalid_format", "invalid_params", "invalid_method", "method_not_found"
+            "type", // categories of invalid requests: "invalid_format", "invalid_params", "invalid_method", "method_not_found",
+                    // and requests over the limits: "content_too_large", "batch_size_too_large", "rate_limited"
         ]
     )
     .unwrap()
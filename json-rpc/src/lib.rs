@@ -10,7 +10,9 @@
 //!
 //! Module organization:
 //! ├── methods.rs        # contains all available JSON RPC method handlers
+//! ├── rate_limit.rs     # per-IP rate limiting of JSON RPC calls
 //! ├── runtime.rs        # implementation of JSON RPC protocol over HTTP
+//! ├── subscriptions.rs  # subscriptions to events and transactions over WebSocket
 //! ├── tests.rs          # tests
 
 #[macro_use]
@@ -18,11 +20,13 @@ mod util;
 
 mod counters;
 mod methods;
+mod rate_limit;
 mod runtime;
+mod subscriptions;
 
 pub use libra_json_rpc_types::{errors, views};
 
-pub use runtime::{bootstrap, bootstrap_from_config};
+pub use runtime::{bootstrap, bootstrap_from_config, bootstrap_secondary};
 
 #[cfg(any(feature = "fuzzing", test))]
 /// Fuzzer for JSON RPC service
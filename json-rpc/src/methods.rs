@@ -5,8 +5,11 @@
 use crate::{
     errors::JsonRpcError,
     views::{
-        AccountStateWithProofView, AccountView, BlockMetadata, CurrencyInfoView, EventView,
-        StateProofView, TransactionView,
+        AccountResourceView, AccountStateWithProofView, AccountTransactionWithProofView,
+        AccountView, BlockMetadata, BytesView, CurrencyInfoView, EventView, EventsWithProofView,
+        GasEstimateView, MempoolTransactionStatusView, MoveFieldView, MoveStructView,
+        MoveValueView, StateProofView, TransactionByHashView, TransactionListWithProofView,
+        TransactionOutputView, TransactionView,
     },
 };
 use anyhow::{ensure, format_err, Error, Result};
@@ -14,9 +17,14 @@ use core::future::Future;
 use debug_interface::prelude::*;
 use futures::{channel::oneshot, SinkExt};
 use libra_config::config::RoleType;
-use libra_crypto::hash::CryptoHash;
-use libra_mempool::MempoolClientSender;
+use libra_crypto::{hash::CryptoHash, HashValue};
+use libra_mempool::{
+    MempoolClientRequest, MempoolClientSender, MempoolPressure, MempoolTransactionStatus,
+    RemovalReason,
+};
+use libra_state_view::StateView;
 use libra_types::{
+    access_path::AccessPath,
     account_address::AccountAddress,
     account_config::{from_currency_code_string, CurrencyInfoResource},
     account_state::AccountState,
@@ -25,32 +33,144 @@ use libra_types::{
     mempool_status::MempoolStatusCode,
     move_resource::MoveStorage,
     on_chain_config::{OnChainConfig, RegisteredCurrencies},
-    transaction::SignedTransaction,
+    transaction::{
+        RawTransaction, SignedTransaction, Transaction, TransactionInfo, TransactionWithProof,
+        Version,
+    },
+};
+use libra_vm::LibraVM;
+use move_core_types::{
+    gas_schedule::GasAlgebra, language_storage::TypeTag, parser::parse_type_tags,
 };
 use network::counters;
+use resource_viewer::{AnnotatedMoveStruct, AnnotatedMoveValue, MoveValueAnnotator};
+use scratchpad::SparseMerkleTree;
 use serde_json::Value;
-use std::{collections::HashMap, convert::TryFrom, ops::Deref, pin::Pin, str::FromStr, sync::Arc};
-use storage_interface::DbReader;
+use std::{
+    cmp::{max, min},
+    collections::HashMap,
+    convert::TryFrom,
+    ops::Deref,
+    pin::Pin,
+    str::FromStr,
+    sync::Arc,
+};
+use storage_interface::{state_view::VerifiedStateView, DbReader};
+
+/// Number of the latest transactions whose gas unit prices `estimate_gas` bases its suggestion on.
+const GAS_PRICE_SAMPLE_SIZE: u64 = 1000;
+/// Margin added by `estimate_gas` to the simulated gas usage, since the state can change before the
+/// transaction is executed for real.
+const GAS_ESTIMATE_MARGIN_PERCENT: u64 = 20;
 
 #[derive(Clone)]
 pub(crate) struct JsonRpcService {
     db: Arc<dyn DbReader>,
     mempool_sender: MempoolClientSender,
     role: RoleType,
+    page_size_limit: u64,
+    simulation_max_gas_amount: u64,
 }
 
 impl JsonRpcService {
-    pub fn new(db: Arc<dyn DbReader>, mempool_sender: MempoolClientSender, role: RoleType) -> Self {
+    pub fn new(
+        db: Arc<dyn DbReader>,
+        mempool_sender: MempoolClientSender,
+        role: RoleType,
+        page_size_limit: u64,
+        simulation_max_gas_amount: u64,
+    ) -> Self {
         Self {
             db,
             mempool_sender,
             role,
+            page_size_limit,
+            simulation_max_gas_amount,
         }
     }
 
     pub fn get_latest_ledger_info(&self) -> Result<LedgerInfoWithSignatures> {
         self.db.get_latest_ledger_info()
     }
+
+    /// Returns the root hash of the state after the transaction at `version`.
+    fn get_state_root(&self, version: u64) -> Result<HashValue> {
+        self.ensure_state_not_pruned(version)?;
+        let txns = self.db.get_transactions(version, 1, version, false)?;
+        txns.proof
+            .transaction_infos()
+            .first()
+            .map(TransactionInfo::state_root_hash)
+            .ok_or_else(|| format_err!("no transaction at version {}", version))
+    }
+
+    /// Returns the gas unit prices paid in `currency_code` by the user transactions among the
+    /// latest `GAS_PRICE_SAMPLE_SIZE` transactions readable.
+    fn get_recent_gas_unit_prices(
+        &self,
+        currency_code: &str,
+        ledger_version: u64,
+    ) -> Result<Vec<u64>> {
+        let start_version = max(
+            (ledger_version + 1).saturating_sub(GAS_PRICE_SAMPLE_SIZE),
+            self.db.get_least_readable_ledger_version()?,
+        );
+        if start_version > ledger_version {
+            return Ok(vec![]);
+        }
+        let txns = self.db.get_transactions(
+            start_version,
+            ledger_version - start_version + 1,
+            ledger_version,
+            false, /* fetch_events */
+        )?;
+        Ok(txns
+            .transactions
+            .iter()
+            .filter_map(|txn| match txn {
+                Transaction::UserTransaction(txn) if txn.gas_currency_code() == currency_code => {
+                    Some(txn.gas_unit_price())
+                }
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Fails with a `VersionPruned` error if the transactions and events at `version` have been
+    /// pruned from the DB.
+    pub fn ensure_ledger_not_pruned(&self, version: u64) -> Result<()> {
+        ensure_not_pruned(version, self.db.get_least_readable_ledger_version()?)
+    }
+
+    /// Fails with a `VersionPruned` error if the state at `version` has been pruned from the DB.
+    pub fn ensure_state_not_pruned(&self, version: u64) -> Result<()> {
+        ensure_not_pruned(version, self.db.get_least_readable_state_version()?)
+    }
+
+    pub fn page_size_limit(&self) -> u64 {
+        self.page_size_limit
+    }
+
+    /// Fails with a `PageSizeTooLarge` error if `limit` is more than the page size limit.
+    pub fn ensure_page_size(&self, limit: u64) -> Result<()> {
+        if limit > self.page_size_limit {
+            return Err(Error::new(JsonRpcError::page_size_too_large(
+                limit,
+                self.page_size_limit,
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn ensure_not_pruned(version: u64, least_readable_version: u64) -> Result<()> {
+    if version < least_readable_version {
+        return Err(Error::new(JsonRpcError::version_pruned(
+            version,
+            least_readable_version,
+        )));
+    }
+    Ok(())
 }
 
 type RpcHandler =
@@ -84,7 +204,10 @@ async fn submit(mut service: JsonRpcService, request: JsonRpcRequest) -> Result<
     let (req_sender, callback) = oneshot::channel();
     service
         .mempool_sender
-        .send((transaction, req_sender))
+        .send(MempoolClientRequest::SubmitTransaction(
+            transaction,
+            req_sender,
+        ))
         .await?;
     let (mempool_status, vm_status) = callback.await??;
 
@@ -97,6 +220,192 @@ async fn submit(mut service: JsonRpcService, request: JsonRpcRequest) -> Result<
     }
 }
 
+/// Executes a transaction against the state at the ledger version of the request without
+/// submitting it, returning what it would do. The signature check is skipped if requested, so that
+/// a transaction can be simulated before being signed.
+async fn simulate_transaction(
+    service: JsonRpcService,
+    request: JsonRpcRequest,
+) -> Result<TransactionOutputView> {
+    let txn_payload: String = serde_json::from_value(request.get_param(0))?;
+    let transaction: SignedTransaction = lcs::from_bytes(&hex::decode(txn_payload)?)?;
+    let skip_signature_check: bool = serde_json::from_value(request.get_param(1))?;
+    ensure!(
+        transaction.max_gas_amount() <= service.simulation_max_gas_amount,
+        "max gas amount {} exceeds the simulation limit {}",
+        transaction.max_gas_amount(),
+        service.simulation_max_gas_amount
+    );
+
+    let version = request.version();
+    let state_root = service.get_state_root(version)?;
+    let db = Arc::clone(&service.db);
+    // the VM runs synchronously, so it's kept off the threads serving requests
+    let output = tokio::task::spawn_blocking(move || {
+        let smt = SparseMerkleTree::new(state_root);
+        let state_view = VerifiedStateView::new(db, Some(version), state_root, &smt);
+        LibraVM::new().simulate_transaction(transaction, skip_signature_check, &state_view)
+    })
+    .await?;
+
+    Ok(TransactionOutputView::from((version + 1, output)))
+}
+
+/// Estimates the gas amount and suggests the gas unit price for a transaction, which doesn't need to
+/// be signed. The gas amount is the gas used by simulating the transaction plus a safety margin.
+/// The price is based on what the latest transactions in the same currency paid, and on how busy
+/// mempool is.
+async fn estimate_gas(
+    mut service: JsonRpcService,
+    request: JsonRpcRequest,
+) -> Result<GasEstimateView> {
+    let txn_payload: String = serde_json::from_value(request.get_param(0))?;
+    let transaction: SignedTransaction = lcs::from_bytes(&hex::decode(txn_payload)?)?;
+
+    let version = request.version();
+    let state_root = service.get_state_root(version)?;
+    let db = Arc::clone(&service.db);
+    let simulation_max_gas_amount = service.simulation_max_gas_amount;
+    let simulated_txn = transaction.clone();
+    // the VM runs synchronously, so it's kept off the threads serving requests
+    let (output, max_gas_units, min_gas_unit_price) =
+        tokio::task::spawn_blocking(move || -> Result<_> {
+            let smt = SparseMerkleTree::new(state_root);
+            let state_view = VerifiedStateView::new(db, Some(version), state_root, &smt);
+            let mut vm = LibraVM::new();
+            vm.load_configs(&state_view);
+            let gas_constants = &vm.get_gas_schedule()?.gas_constants;
+            let max_gas_units = min(
+                gas_constants.maximum_number_of_gas_units.get(),
+                simulation_max_gas_amount,
+            );
+            let min_gas_unit_price = gas_constants.min_price_per_gas_unit.get();
+
+            // Simulate with the most gas allowed at the lowest price, so that neither the guess of
+            // the caller nor the balance of the sender limits the execution.
+            let raw_txn = RawTransaction::new(
+                simulated_txn.sender(),
+                simulated_txn.sequence_number(),
+                simulated_txn.payload().clone(),
+                max_gas_units,
+                min_gas_unit_price,
+                simulated_txn.gas_currency_code().to_string(),
+                simulated_txn.expiration_time(),
+            );
+            let output = vm.simulate_transaction(
+                SignedTransaction::new_with_authenticator(raw_txn, simulated_txn.authenticator()),
+                true, /* skip_signature_check */
+                &state_view,
+            );
+            Ok((output, max_gas_units, min_gas_unit_price))
+        })
+        .await??;
+    let gas_used = output.gas_used();
+    let max_gas_amount = min(
+        gas_used + gas_used * GAS_ESTIMATE_MARGIN_PERCENT / 100,
+        max_gas_units,
+    );
+
+    let (req_sender, callback) = oneshot::channel();
+    service
+        .mempool_sender
+        .send(MempoolClientRequest::GetPressure(req_sender))
+        .await?;
+    let pressure = callback.await?;
+    let recent_gas_unit_prices =
+        service.get_recent_gas_unit_prices(transaction.gas_currency_code(), version)?;
+    let gas_unit_price = max(
+        suggest_gas_unit_price(recent_gas_unit_prices, &pressure),
+        min_gas_unit_price,
+    );
+
+    Ok(GasEstimateView {
+        vm_status: output.status().vm_status().major_status,
+        gas_used,
+        max_gas_amount,
+        gas_unit_price,
+        gas_currency_code: transaction.gas_currency_code().to_string(),
+    })
+}
+
+/// Returns where the transaction of given sender and sequence number is in mempool, or why it was
+/// removed or not accepted recently
+async fn get_mempool_transaction_status(
+    mut service: JsonRpcService,
+    request: JsonRpcRequest,
+) -> Result<Option<MempoolTransactionStatusView>> {
+    let p_sender: String = serde_json::from_value(request.get_param(0))?;
+    let sequence_number: u64 = serde_json::from_value(request.get_param(1))?;
+    let sender = AccountAddress::try_from(p_sender)?;
+
+    let (req_sender, callback) = oneshot::channel();
+    service
+        .mempool_sender
+        .send(MempoolClientRequest::GetTransactionStatus(
+            sender,
+            sequence_number,
+            req_sender,
+        ))
+        .await?;
+    Ok(callback.await?.map(|status| match status {
+        MempoolTransactionStatus::Ready { expiration_time } => {
+            MempoolTransactionStatusView::Ready {
+                expiration_time: expiration_time.as_secs(),
+            }
+        }
+        MempoolTransactionStatus::Parked { expiration_time } => {
+            MempoolTransactionStatusView::Parked {
+                expiration_time: expiration_time.as_secs(),
+            }
+        }
+        MempoolTransactionStatus::Removed(reason) => MempoolTransactionStatusView::Removed {
+            reason: match reason {
+                RemovalReason::Committed => "committed",
+                RemovalReason::Rejected => "rejected",
+                RemovalReason::Expired => "expired",
+                RemovalReason::TimedOut => "timed_out",
+                RemovalReason::Evicted => "evicted",
+                RemovalReason::Replaced => "replaced",
+            }
+            .to_string(),
+        },
+        MempoolTransactionStatus::NotAccepted { code, vm_status } => {
+            MempoolTransactionStatusView::NotAccepted {
+                reason: match code {
+                    MempoolStatusCode::InvalidSeqNumber => "invalid_seq_number",
+                    MempoolStatusCode::MempoolIsFull => "mempool_is_full",
+                    MempoolStatusCode::TooManyTransactions => "too_many_transactions",
+                    MempoolStatusCode::InvalidUpdate => "invalid_update",
+                    MempoolStatusCode::VmError => "vm_error",
+                    MempoolStatusCode::RejectedByPolicy => "rejected_by_policy",
+                    MempoolStatusCode::Accepted | MempoolStatusCode::UnknownStatus => "unknown",
+                }
+                .to_string(),
+                vm_status,
+            }
+        }
+    }))
+}
+
+/// Suggests a gas unit price given the prices paid by recent transactions: the median, or the 90th
+/// percentile if mempool is more than half full, so that the transaction gets ahead of most of the
+/// queue. Zero if there's no recent transaction.
+pub(crate) fn suggest_gas_unit_price(
+    mut recent_prices: Vec<u64>,
+    pressure: &MempoolPressure,
+) -> u64 {
+    if recent_prices.is_empty() {
+        return 0;
+    }
+    recent_prices.sort_unstable();
+    let percentile = if pressure.num_transactions * 2 > pressure.capacity {
+        90
+    } else {
+        50
+    };
+    recent_prices[(recent_prices.len() - 1) * percentile / 100]
+}
+
 /// Returns account state (AccountView) by given address
 async fn get_account_state(
     service: JsonRpcService,
@@ -125,6 +434,133 @@ async fn get_account_state(
     Ok(None)
 }
 
+/// Returns all the resources under an account at given version, decoded with the types of their
+/// Move structs where known
+async fn get_account_resources(
+    service: JsonRpcService,
+    request: JsonRpcRequest,
+) -> Result<Option<Vec<AccountResourceView>>> {
+    let address: String = serde_json::from_value(request.get_param(0))?;
+    let version: u64 = serde_json::from_value(request.get_param(1))?;
+    let type_tags: Vec<String> = serde_json::from_value(request.get_param(2))?;
+    let account_address = AccountAddress::from_str(&address)?;
+    ensure!(
+        version <= request.version(),
+        "Version {} is newer than the latest version {}.",
+        version,
+        request.version(),
+    );
+    service.ensure_state_not_pruned(version)?;
+    let struct_tags = type_tags
+        .iter()
+        .map(|type_tag| match parse_type_tags(type_tag)?.as_slice() {
+            [TypeTag::Struct(struct_tag)] => Ok(struct_tag.clone()),
+            _ => Err(format_err!("{} is not a struct type.", type_tag)),
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let blob = match service
+        .db
+        .get_account_state_with_proof_by_version(account_address, version)?
+        .0
+    {
+        Some(blob) => blob,
+        None => return Ok(None),
+    };
+    let account_state = AccountState::try_from(&blob)?;
+    let state_view = DbStateView {
+        db: service.db.deref(),
+        version,
+    };
+    let annotator = MoveValueAnnotator::new(&state_view);
+    Ok(Some(
+        annotator
+            .view_account_resources(&account_state, &struct_tags)
+            .into_iter()
+            .map(|(path, annotated)| {
+                // Resources are viewed by the paths in the account state, so the data is there.
+                let data = account_state.get(&path).cloned().unwrap_or_default();
+                AccountResourceView {
+                    path: BytesView::from(&path),
+                    data: BytesView::from(&data),
+                    value: annotated.ok().map(|s| move_struct_view(&s)),
+                }
+            })
+            .collect(),
+    ))
+}
+
+/// The state at a version, read from the DB without verification, for the resource viewer to load
+/// modules with
+struct DbStateView<'a> {
+    db: &'a dyn DbReader,
+    version: Version,
+}
+
+impl<'a> StateView for DbStateView<'a> {
+    fn get(&self, access_path: &AccessPath) -> Result<Option<Vec<u8>>> {
+        match self
+            .db
+            .get_account_state_with_proof_by_version(access_path.address, self.version)?
+            .0
+        {
+            Some(blob) => Ok(AccountState::try_from(&blob)?
+                .get(&access_path.path)
+                .cloned()),
+            None => Ok(None),
+        }
+    }
+
+    fn multi_get(&self, access_paths: &[AccessPath]) -> Result<Vec<Option<Vec<u8>>>> {
+        access_paths
+            .iter()
+            .map(|access_path| self.get(access_path))
+            .collect()
+    }
+
+    fn is_genesis(&self) -> bool {
+        false
+    }
+}
+
+fn move_struct_view(annotated: &AnnotatedMoveStruct) -> MoveStructView {
+    MoveStructView {
+        address: annotated.type_.address.to_string(),
+        module: annotated.type_.module.to_string(),
+        name: annotated.type_.name.to_string(),
+        type_params: annotated
+            .type_
+            .type_params
+            .iter()
+            .map(ToString::to_string)
+            .collect(),
+        is_resource: annotated.is_resource,
+        fields: annotated
+            .value
+            .iter()
+            .map(|(name, value)| MoveFieldView {
+                name: name.to_string(),
+                value: move_value_view(value),
+            })
+            .collect(),
+    }
+}
+
+fn move_value_view(annotated: &AnnotatedMoveValue) -> MoveValueView {
+    match annotated {
+        AnnotatedMoveValue::U8(v) => MoveValueView::U8(*v),
+        AnnotatedMoveValue::U64(v) => MoveValueView::U64(*v),
+        AnnotatedMoveValue::U128(v) => MoveValueView::U128(v.to_string()),
+        AnnotatedMoveValue::Bool(v) => MoveValueView::Bool(*v),
+        AnnotatedMoveValue::Address(v) => MoveValueView::Address(v.to_string()),
+        AnnotatedMoveValue::Vector(v) => {
+            MoveValueView::Vector(v.iter().map(move_value_view).collect())
+        }
+        AnnotatedMoveValue::Bytes(v) => MoveValueView::Bytes(BytesView::from(v)),
+        AnnotatedMoveValue::Struct(v) => MoveValueView::Struct(move_struct_view(v)),
+    }
+}
+
 /// Returns the blockchain metadata for a specified version. If no version is specified, default to
 /// returning the current blockchain metadata
 /// Can be used to verify that target Full Node is up-to-date
@@ -152,10 +588,9 @@ async fn get_transactions(
     let limit: u64 = serde_json::from_value(request.get_param(1))?;
     let include_events: bool = serde_json::from_value(request.get_param(2))?;
 
-    ensure!(
-        limit > 0 && limit <= 1000,
-        "limit must be smaller than 1000"
-    );
+    ensure!(limit > 0, "limit must be positive");
+    service.ensure_page_size(limit)?;
+    service.ensure_ledger_not_pruned(start_version)?;
 
     let txs =
         service
@@ -212,37 +647,65 @@ async fn get_account_transaction(
 
     let account = AccountAddress::try_from(p_account)?;
 
-    let tx = service
+    service
         .db
-        .get_txn_by_account(account, sequence, request.version(), include_events)?;
+        .get_txn_by_account(account, sequence, request.version(), include_events)?
+        .map(|tx| transaction_view(tx, include_events))
+        .transpose()
+}
 
-    if let Some(tx) = tx {
-        if include_events {
-            ensure!(
-                tx.events.is_some(),
-                "Storage layer didn't return events when requested!"
-            );
-        }
-        let tx_version = tx.version;
+/// Returns the transaction with the given hash, whether it's committed or still pending in mempool
+async fn get_transaction_by_hash(
+    mut service: JsonRpcService,
+    request: JsonRpcRequest,
+) -> Result<Option<TransactionByHashView>> {
+    let p_hash: String = serde_json::from_value(request.get_param(0))?;
+    let include_events: bool = serde_json::from_value(request.get_param(1))?;
 
-        let events = tx
-            .events
-            .unwrap_or_default()
-            .into_iter()
-            .map(|x| ((tx_version, x).into()))
-            .collect();
+    let hash = HashValue::from_hex(&p_hash)?;
+    if let Some(tx) = service
+        .db
+        .get_txn_by_hash(hash, request.version(), include_events)?
+    {
+        return Ok(Some(TransactionByHashView::Committed {
+            transaction: transaction_view(tx, include_events)?,
+        }));
+    }
 
-        Ok(Some(TransactionView {
-            version: tx_version,
-            hash: tx.transaction.hash().to_string(),
-            transaction: tx.transaction.into(),
-            events,
-            vm_status: tx.proof.transaction_info().major_status(),
-            gas_used: tx.proof.transaction_info().gas_used(),
-        }))
-    } else {
-        Ok(None)
+    let (req_sender, callback) = oneshot::channel();
+    service
+        .mempool_sender
+        .send(MempoolClientRequest::GetTransactionByHash(hash, req_sender))
+        .await?;
+    Ok(callback.await?.map(|txn| TransactionByHashView::Pending {
+        transaction: Transaction::UserTransaction(txn).into(),
+    }))
+}
+
+fn transaction_view(tx: TransactionWithProof, include_events: bool) -> Result<TransactionView> {
+    if include_events {
+        ensure!(
+            tx.events.is_some(),
+            "Storage layer didn't return events when requested!"
+        );
     }
+    let tx_version = tx.version;
+
+    let events = tx
+        .events
+        .unwrap_or_default()
+        .into_iter()
+        .map(|x| ((tx_version, x).into()))
+        .collect();
+
+    Ok(TransactionView {
+        version: tx_version,
+        hash: tx.transaction.hash().to_string(),
+        transaction: tx.transaction.into(),
+        events,
+        vm_status: tx.proof.transaction_info().major_status(),
+        gas_used: tx.proof.transaction_info().gas_used(),
+    })
 }
 
 /// Returns events by given access path
@@ -251,6 +714,7 @@ async fn get_events(service: JsonRpcService, request: JsonRpcRequest) -> Result<
     let start: u64 = serde_json::from_value(request.get_param(1))?;
     let limit: u64 = serde_json::from_value(request.get_param(2))?;
 
+    service.ensure_page_size(limit)?;
     let event_key = EventKey::try_from(&hex::decode(raw_event_key)?[..])?;
     let events_with_proof = service.db.get_events(&event_key, start, true, limit)?;
 
@@ -263,6 +727,64 @@ async fn get_events(service: JsonRpcService, request: JsonRpcRequest) -> Result<
     Ok(events)
 }
 
+/// Returns transactions with the proof against the ledger info of the request, which is returned
+/// along with them.
+async fn get_transactions_with_proofs(
+    service: JsonRpcService,
+    request: JsonRpcRequest,
+) -> Result<TransactionListWithProofView> {
+    let start_version: u64 = serde_json::from_value(request.get_param(0))?;
+    let limit: u64 = serde_json::from_value(request.get_param(1))?;
+    let include_events: bool = serde_json::from_value(request.get_param(2))?;
+
+    ensure!(limit > 0, "limit must be positive");
+    service.ensure_page_size(limit)?;
+    service.ensure_ledger_not_pruned(start_version)?;
+
+    let txs =
+        service
+            .db
+            .get_transactions(start_version, limit, request.version(), include_events)?;
+    TransactionListWithProofView::try_from((request.ledger_info, txs))
+}
+
+/// Returns account transaction by account and sequence_number with the proof against the ledger
+/// info of the request, which is returned along with it.
+async fn get_account_transaction_with_proofs(
+    service: JsonRpcService,
+    request: JsonRpcRequest,
+) -> Result<AccountTransactionWithProofView> {
+    let p_account: String = serde_json::from_value(request.get_param(0))?;
+    let sequence: u64 = serde_json::from_value(request.get_param(1))?;
+    let include_events: bool = serde_json::from_value(request.get_param(2))?;
+
+    let account = AccountAddress::try_from(p_account)?;
+
+    let tx = service
+        .db
+        .get_txn_by_account(account, sequence, request.version(), include_events)?;
+    AccountTransactionWithProofView::try_from((request.ledger_info, tx))
+}
+
+/// Returns events by given access path, each with the proof against the ledger info of the
+/// request, which is returned along with them.
+async fn get_events_with_proofs(
+    service: JsonRpcService,
+    request: JsonRpcRequest,
+) -> Result<EventsWithProofView> {
+    let raw_event_key: String = serde_json::from_value(request.get_param(0))?;
+    let start: u64 = serde_json::from_value(request.get_param(1))?;
+    let limit: u64 = serde_json::from_value(request.get_param(2))?;
+
+    service.ensure_page_size(limit)?;
+    let event_key = EventKey::try_from(&hex::decode(raw_event_key)?[..])?;
+    let events_with_proof =
+        service
+            .db
+            .get_events_with_proofs(&event_key, start, true, limit, request.version())?;
+    EventsWithProofView::try_from((request.ledger_info, events_with_proof))
+}
+
 /// Returns meta information about supported currencies
 async fn currencies_info(
     service: JsonRpcService,
@@ -319,6 +841,7 @@ async fn get_account_state_with_proof(
         serde_json::from_value::<u64>(request.get_param(1)).unwrap_or_else(|_| request.version());
     let ledger_version =
         serde_json::from_value::<u64>(request.get_param(2)).unwrap_or_else(|_| request.version());
+    service.ensure_state_not_pruned(version)?;
 
     let account_state_with_proof =
         service
@@ -329,6 +852,26 @@ async fn get_account_state_with_proof(
     )?)
 }
 
+/// Returns the versions at which the state of the account changed, in ascending order, starting
+/// from `start_version`. Pages through the history with the last version returned plus one as the
+/// next `start_version`.
+async fn get_account_state_history(
+    service: JsonRpcService,
+    request: JsonRpcRequest,
+) -> Result<Vec<u64>> {
+    let address: String = serde_json::from_value(request.get_param(0))?;
+    let start_version: u64 = serde_json::from_value(request.get_param(1))?;
+    let limit: u64 = serde_json::from_value(request.get_param(2))?;
+
+    ensure!(limit > 0, "limit must be positive");
+    service.ensure_page_size(limit)?;
+    let account_address = AccountAddress::from_str(&address)?;
+
+    service
+        .db
+        .get_account_state_history(account_address, start_version, limit, request.version())
+}
+
 /// Returns the number of peers this node is connected to
 async fn get_network_status(service: JsonRpcService, _request: JsonRpcRequest) -> Result<u64> {
     let blah = counters::LIBRA_NETWORK_PEERS
@@ -342,8 +885,17 @@ async fn get_network_status(service: JsonRpcService, _request: JsonRpcRequest) -
 pub(crate) fn build_registry() -> RpcRegistry {
     let mut registry = RpcRegistry::new();
     register_rpc_method!(registry, "submit", submit, 1);
+    register_rpc_method!(registry, "simulate_transaction", simulate_transaction, 2);
+    register_rpc_method!(registry, "estimate_gas", estimate_gas, 1);
+    register_rpc_method!(
+        registry,
+        "get_mempool_transaction_status",
+        get_mempool_transaction_status,
+        2
+    );
     register_rpc_method!(registry, "get_metadata", get_metadata, 1);
     register_rpc_method!(registry, "get_account_state", get_account_state, 1);
+    register_rpc_method!(registry, "get_account_resources", get_account_resources, 3);
     register_rpc_method!(registry, "get_transactions", get_transactions, 3);
     register_rpc_method!(
         registry,
@@ -351,6 +903,12 @@ pub(crate) fn build_registry() -> RpcRegistry {
         get_account_transaction,
         3
     );
+    register_rpc_method!(
+        registry,
+        "get_transaction_by_hash",
+        get_transaction_by_hash,
+        2
+    );
     register_rpc_method!(registry, "get_events", get_events, 3);
     register_rpc_method!(registry, "get_currencies", currencies_info, 0);
 
@@ -361,6 +919,30 @@ pub(crate) fn build_registry() -> RpcRegistry {
         get_account_state_with_proof,
         3
     );
+    register_rpc_method!(
+        registry,
+        "get_transactions_with_proofs",
+        get_transactions_with_proofs,
+        3
+    );
+    register_rpc_method!(
+        registry,
+        "get_account_transaction_with_proofs",
+        get_account_transaction_with_proofs,
+        3
+    );
+    register_rpc_method!(
+        registry,
+        "get_events_with_proofs",
+        get_events_with_proofs,
+        3
+    );
+    register_rpc_method!(
+        registry,
+        "get_account_state_history",
+        get_account_state_history,
+        3
+    );
     register_rpc_method!(registry, "get_network_status", get_network_status, 0);
 
     registry
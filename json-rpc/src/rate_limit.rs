@@ -0,0 +1,58 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use libra_config::config::RpcRateLimitConfig;
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use stream_ratelimiter::TokenBucket;
+
+/// Rate-limits the JSON-RPC calls of each client IP with a token bucket, one token per call.
+pub(crate) struct IpRateLimiter {
+    refill_interval: Duration,
+    capacity: usize,
+    state: Mutex<State>,
+}
+
+struct State {
+    buckets: HashMap<IpAddr, TokenBucket>,
+    last_gc: Instant,
+}
+
+impl IpRateLimiter {
+    pub fn new(config: &RpcRateLimitConfig) -> Self {
+        Self {
+            refill_interval: Duration::from_millis(config.refill_interval_ms),
+            capacity: config.calls_per_interval,
+            state: Mutex::new(State {
+                buckets: HashMap::new(),
+                last_gc: Instant::now(),
+            }),
+        }
+    }
+
+    /// Takes a token for each of `num_calls` calls from `ip`, returning whether the calls are
+    /// allowed. The calls of a batch are either all allowed or none.
+    pub fn try_acquire(&self, ip: IpAddr, num_calls: usize) -> bool {
+        let now = Instant::now();
+        let mut state = self
+            .state
+            .lock()
+            .expect("[json-rpc] rate limiter lock poisoned");
+        // Full buckets are no different from new ones, so drop them once in a while to only keep
+        // track of the IPs which made calls lately.
+        if now.saturating_duration_since(state.last_gc) >= self.refill_interval {
+            state.buckets.retain(|_, bucket| !bucket.is_full(now));
+            state.last_gc = now;
+        }
+        let (refill_interval, capacity) = (self.refill_interval, self.capacity);
+        state
+            .buckets
+            .entry(ip)
+            .or_insert_with(|| TokenBucket::new(refill_interval, capacity, now))
+            .try_acquire(num_calls, now)
+    }
+}
@@ -5,24 +5,29 @@ use crate::{
     counters,
     errors::JsonRpcError,
     methods::{build_registry, JsonRpcRequest, JsonRpcService, RpcRegistry},
+    rate_limit::IpRateLimiter,
+    subscriptions,
 };
-use futures::future::join_all;
-use libra_config::config::{NodeConfig, RoleType};
+use anyhow::Result;
+use futures::{channel::mpsc::channel, future::join_all};
+use libra_config::config::{NodeConfig, RoleType, RpcConfig};
 use libra_mempool::MempoolClientSender;
 use libra_types::ledger_info::LedgerInfoWithSignatures;
-use serde_json::{map::Map, Value};
-use std::{net::SocketAddr, sync::Arc};
+use libradb::LibraDB;
+use serde_json::{json, map::Map, Value};
+use std::{net::SocketAddr, path::Path, sync::Arc, time::Duration};
 use storage_interface::DbReader;
 use tokio::runtime::{Builder, Runtime};
 use warp::{
     reject::{self, Reject},
-    Filter,
+    Filter, Rejection, Reply,
 };
 
-/// Creates HTTP server (warp-based) that serves JSON RPC requests
+/// Creates HTTP server (warp-based) that serves JSON RPC requests, and subscriptions over WebSocket
+/// at `/ws`, within the limits of `config`
 /// Returns handle to corresponding Tokio runtime
 pub fn bootstrap(
-    address: SocketAddr,
+    config: &RpcConfig,
     libra_db: Arc<dyn DbReader>,
     mp_sender: MempoolClientSender,
     role: RoleType,
@@ -34,17 +39,79 @@ pub fn bootstrap(
         .build()
         .expect("[rpc] failed to create runtime");
 
-    let registry = Arc::new(build_registry());
-    let service = JsonRpcService::new(libra_db, mp_sender, role);
+    let mut registry = build_registry();
+    if !config.simulation_enabled {
+        // they let any caller run code in the VM
+        registry.remove("simulate_transaction");
+        registry.remove("estimate_gas");
+    }
+    let registry = Arc::new(registry);
+    let service = JsonRpcService::new(
+        libra_db,
+        mp_sender,
+        role,
+        config.page_size_limit,
+        config.simulation_max_gas_amount,
+    );
+    let limits = Arc::new(CallLimits {
+        batch_size_limit: config.batch_size_limit,
+        rate_limiter: config.rate_limit.as_ref().map(IpRateLimiter::new),
+    });
 
+    let ws_service = service.clone();
+    let ws_registry = Arc::clone(&registry);
+    let ws_limits = Arc::clone(&limits);
+    let subscription_limit = config.subscriptions_per_connection_limit;
+    let ws_handler = warp::path("ws")
+        .and(warp::path::end())
+        .and(warp::ws())
+        .and(warp::addr::remote())
+        .and(warp::any().map(move || ws_service.clone()))
+        .and(warp::any().map(move || Arc::clone(&ws_registry)))
+        .and(warp::any().map(move || Arc::clone(&ws_limits)))
+        .map(
+            move |ws: warp::ws::Ws, remote: Option<SocketAddr>, service, registry, limits| {
+                ws.on_upgrade(move |socket| {
+                    subscriptions::handle_connection(
+                        socket,
+                        remote,
+                        service,
+                        registry,
+                        limits,
+                        subscription_limit,
+                    )
+                })
+            },
+        );
+
+    // Requests from browsers of other origins are refused unless the origin is allowed.
+    let mut cors = warp::cors()
+        .allow_methods(vec!["POST"])
+        .allow_headers(vec!["content-type"]);
+    if config
+        .cors_allowed_origins
+        .iter()
+        .any(|origin| origin == "*")
+    {
+        cors = cors.allow_any_origin();
+    } else {
+        cors = cors.allow_origins(config.cors_allowed_origins.iter().map(String::as_str));
+    }
+
+    let content_length_limit = config.content_length_limit;
     let handler = warp::any()
         .and(warp::path::end())
         .and(warp::post())
         .and(warp::header::exact("content-type", "application/json"))
+        .and(warp::body::content_length_limit(content_length_limit))
         .and(warp::body::json())
+        .and(warp::addr::remote())
         .and(warp::any().map(move || service.clone()))
         .and(warp::any().map(move || Arc::clone(&registry)))
-        .and_then(rpc_endpoint);
+        .and(warp::any().map(move || Arc::clone(&limits)))
+        .and_then(rpc_endpoint)
+        .recover(move |rejection| handle_rejection(rejection, content_length_limit))
+        .with(cors);
 
     // Ensure that we actually bind to the socket first before spawning the
     // server tasks. This helps in tests to prevent races where a client attempts
@@ -53,7 +120,8 @@ pub fn bootstrap(
     //
     // Note: we need to enter the runtime context first to actually bind, since
     //       tokio TcpListener can only be bound inside a tokio context.
-    let server = runtime.enter(move || warp::serve(handler).bind(address));
+    let address = config.address;
+    let server = runtime.enter(move || warp::serve(ws_handler.or(handler)).bind(address));
     runtime.handle().spawn(server);
     runtime
 }
@@ -64,7 +132,65 @@ pub fn bootstrap_from_config(
     libra_db: Arc<dyn DbReader>,
     mp_sender: MempoolClientSender,
 ) -> Runtime {
-    bootstrap(config.rpc.address, libra_db, mp_sender, config.base.role)
+    bootstrap(&config.rpc, libra_db, mp_sender, config.base.role)
+}
+
+/// Creates JSON RPC endpoint, within the limits of `config`, over the DB of a running node under
+/// `db_root_path`, opened as a secondary instance which catches up with the node every
+/// `catch_up_interval`. This takes reads off the node, but there's no mempool behind it, so the
+/// calls which need one fail
+pub fn bootstrap_secondary(
+    config: &RpcConfig,
+    db_root_path: &Path,
+    secondary_path: &Path,
+    catch_up_interval: Duration,
+    role: RoleType,
+) -> Result<Runtime> {
+    let libra_db =
+        LibraDB::open_as_secondary(db_root_path, secondary_path, Some(catch_up_interval))?;
+    // nothing receives the requests to mempool, so sending them fails right away
+    let (mp_sender, _) = channel(1);
+    Ok(bootstrap(config, Arc::new(libra_db), mp_sender, role))
+}
+
+/// Limits on the calls of a request, which are checked before any of them is served. They apply
+/// to each message over WebSocket connections the same way
+pub(crate) struct CallLimits {
+    batch_size_limit: usize,
+    rate_limiter: Option<IpRateLimiter>,
+}
+
+impl CallLimits {
+    /// Checks the calls of request `data` from `remote`, returning the error to reject all of them
+    /// with if they are over a limit.
+    pub(crate) fn check(
+        &self,
+        remote: Option<SocketAddr>,
+        data: &Value,
+    ) -> Result<(), JsonRpcError> {
+        let num_calls = match data {
+            Value::Array(requests) => requests.len(),
+            _ => 1,
+        };
+        if num_calls > self.batch_size_limit {
+            counters::INVALID_REQUESTS
+                .with_label_values(&["batch_size_too_large"])
+                .inc();
+            return Err(JsonRpcError::batch_size_too_large(
+                num_calls,
+                self.batch_size_limit,
+            ));
+        }
+        if let (Some(rate_limiter), Some(remote)) = (&self.rate_limiter, remote) {
+            if !rate_limiter.try_acquire(remote.ip(), num_calls) {
+                counters::INVALID_REQUESTS
+                    .with_label_values(&["rate_limited"])
+                    .inc();
+                return Err(JsonRpcError::rate_limited());
+            }
+        }
+        Ok(())
+    }
 }
 
 /// JSON RPC entry point
@@ -72,9 +198,15 @@ pub fn bootstrap_from_config(
 /// Performs routing based on methods defined in `registry`
 async fn rpc_endpoint(
     data: Value,
+    remote: Option<SocketAddr>,
     service: JsonRpcService,
     registry: Arc<RpcRegistry>,
+    limits: Arc<CallLimits>,
 ) -> Result<Box<dyn warp::Reply>, warp::Rejection> {
+    if let Err(err) = limits.check(remote, &data) {
+        return Ok(Box::new(warp::reply::json(&reject_calls(&data, err))));
+    }
+
     // take snapshot of latest version of DB to be used across all requests, especially for batched requests
     let ledger_info = service
         .get_latest_ledger_info()
@@ -112,6 +244,15 @@ async fn rpc_request_handler(
     // set defaults: protocol version to 2.0, request id to null
     response.insert("jsonrpc".to_string(), Value::String("2.0".to_string()));
     response.insert("id".to_string(), Value::Null);
+    // the ledger the request is served at, for clients to tell stale servers
+    response.insert(
+        "libra_ledger_version".to_string(),
+        json!(ledger_info.ledger_info().version()),
+    );
+    response.insert(
+        "libra_ledger_timestampusec".to_string(),
+        json!(ledger_info.ledger_info().timestamp_usecs()),
+    );
 
     match req {
         Value::Object(data) => {
@@ -221,7 +362,42 @@ async fn rpc_request_handler(
     Value::Object(response)
 }
 
-fn parse_request_id(request: &Map<String, Value>) -> Result<Value, JsonRpcError> {
+/// Responds to every call of a request with `err`, keeping the ids of the calls.
+pub(crate) fn reject_calls(data: &Value, err: JsonRpcError) -> Value {
+    let error_response_to = |request: &Value| {
+        let id = match request {
+            Value::Object(request) => parse_request_id(request).unwrap_or(Value::Null),
+            _ => Value::Null,
+        };
+        error_response(id, err.clone())
+    };
+    match data {
+        Value::Array(requests) => Value::Array(requests.iter().map(error_response_to).collect()),
+        request => error_response_to(request),
+    }
+}
+
+/// Turns the rejection of a request body over the content length limit into a JSON RPC error.
+/// Other rejections are left to warp.
+async fn handle_rejection(
+    rejection: Rejection,
+    content_length_limit: u64,
+) -> Result<impl Reply, Rejection> {
+    if rejection.find::<reject::PayloadTooLarge>().is_some() {
+        counters::INVALID_REQUESTS
+            .with_label_values(&["content_too_large"])
+            .inc();
+        let err = JsonRpcError::content_too_large(content_length_limit);
+        return Ok(warp::reply::json(&error_response(Value::Null, err)));
+    }
+    Err(rejection)
+}
+
+pub(crate) fn error_response(id: Value, err: JsonRpcError) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "error": err.serialize()})
+}
+
+pub(crate) fn parse_request_id(request: &Map<String, Value>) -> Result<Value, JsonRpcError> {
     match request.get("id") {
         Some(req_id) => {
             if req_id.is_string() || req_id.is_number() || req_id.is_null() {
@@ -234,7 +410,7 @@ fn parse_request_id(request: &Map<String, Value>) -> Result<Value, JsonRpcError>
     }
 }
 
-fn verify_protocol(request: &Map<String, Value>) -> Result<(), JsonRpcError> {
+pub(crate) fn verify_protocol(request: &Map<String, Value>) -> Result<(), JsonRpcError> {
     if let Some(Value::String(protocol)) = request.get("jsonrpc") {
         if protocol == "2.0" {
             return Ok(());
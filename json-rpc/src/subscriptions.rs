@@ -0,0 +1,290 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Module contains the WebSocket endpoint of JSON-RPC, over which clients subscribe to the events of
+//! an event stream or to transactions, instead of polling for them.
+//!
+//! A subscription is a JSON-RPC request sent over the WebSocket connection, e.g.
+//! `{"jsonrpc": "2.0", "id": 1, "method": "subscribe_to_events", "params": [<key>, <start_seq>]}`.
+//! Items are pushed in order as they are committed, each in a response carrying the id of the
+//! subscription request. Several subscriptions can share a connection.
+//!
+//! A subscription is resumed after reconnecting by subscribing again, starting from the sequence
+//! number (or version) right after the last one received.
+//!
+//! Each subscription polls the DB on its own, so the number of active subscriptions over a
+//! connection is limited, and requests over the limit are rejected.
+
+use crate::{
+    counters,
+    errors::JsonRpcError,
+    methods::{JsonRpcRequest, JsonRpcService, RpcRegistry},
+    runtime::{error_response, parse_request_id, reject_calls, verify_protocol, CallLimits},
+    views::{EventView, TransactionView},
+};
+use anyhow::{format_err, Error, Result};
+use futures::{
+    channel::mpsc,
+    future::{AbortHandle, Abortable},
+    SinkExt, StreamExt,
+};
+use serde_json::{json, map::Map, Value};
+use std::{cmp::min, net::SocketAddr, sync::Arc, time::Duration};
+use warp::ws::{Message, WebSocket};
+
+/// Max number of items a subscription fetches at once, if the page size limit allows.
+const PAGE_SIZE: u64 = 100;
+/// How often a subscription which has caught up checks for new items.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Max number of responses queued for a connection. Subscriptions stop fetching while the queue is
+/// full, so that a slow client holds back only itself.
+const OUTBOUND_QUEUE_SIZE: usize = 100;
+
+enum Subscription {
+    Events { event_key: String, next_seq: u64 },
+    Transactions { next_version: u64 },
+}
+
+impl Subscription {
+    fn method(&self) -> &'static str {
+        match self {
+            Subscription::Events { .. } => "subscribe_to_events",
+            Subscription::Transactions { .. } => "subscribe_to_transactions",
+        }
+    }
+
+    /// Keeps pushing new items to `sender` until the connection is closed or an error occurs, in
+    /// which case the error is pushed as the last response.
+    async fn run(
+        mut self,
+        id: Value,
+        service: JsonRpcService,
+        registry: Arc<RpcRegistry>,
+        mut sender: mpsc::Sender<Value>,
+    ) {
+        loop {
+            let items = match self.fetch_next(&service, &registry).await {
+                Ok(items) => items,
+                Err(err) => {
+                    let _ = sender
+                        .send(error_response(id, to_json_rpc_error(err)))
+                        .await;
+                    return;
+                }
+            };
+            if items.is_empty() {
+                tokio::time::delay_for(POLL_INTERVAL).await;
+                continue;
+            }
+            for item in items {
+                if sender
+                    .send(result_response(id.clone(), item))
+                    .await
+                    .is_err()
+                {
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Fetches the next page of items through the corresponding RPC method, and advances the
+    /// subscription past them.
+    async fn fetch_next(
+        &mut self,
+        service: &JsonRpcService,
+        registry: &RpcRegistry,
+    ) -> Result<Vec<Value>> {
+        let page_size = min(PAGE_SIZE, service.page_size_limit());
+        let (method, params) = match self {
+            Subscription::Events {
+                event_key,
+                next_seq,
+            } => (
+                "get_events",
+                vec![json!(event_key), json!(next_seq), json!(page_size)],
+            ),
+            Subscription::Transactions { next_version } => (
+                "get_transactions",
+                vec![json!(next_version), json!(page_size), json!(true)],
+            ),
+        };
+        let handler = registry
+            .get(method)
+            .ok_or_else(|| format_err!("Method {} is not registered.", method))?;
+        let request = JsonRpcRequest {
+            params,
+            ledger_info: service.get_latest_ledger_info()?,
+        };
+        let result = handler(service.clone(), request).await?;
+
+        match self {
+            Subscription::Events { next_seq, .. } => {
+                let events: Vec<EventView> = serde_json::from_value(result.clone())?;
+                if let Some(event) = events.last() {
+                    *next_seq = event.sequence_number + 1;
+                }
+            }
+            Subscription::Transactions { next_version } => {
+                let txns: Vec<TransactionView> = serde_json::from_value(result.clone())?;
+                if let Some(txn) = txns.last() {
+                    *next_version = txn.version + 1;
+                }
+            }
+        }
+        match result {
+            Value::Array(items) => Ok(items),
+            _ => Err(format_err!("{} didn't return an array.", method)),
+        }
+    }
+}
+
+/// Serves a WebSocket connection from `remote` until the client closes it, with at most
+/// `subscription_limit` subscriptions active at a time. Each message is checked against `limits`
+/// like an HTTP request.
+pub(crate) async fn handle_connection(
+    ws: WebSocket,
+    remote: Option<SocketAddr>,
+    service: JsonRpcService,
+    registry: Arc<RpcRegistry>,
+    limits: Arc<CallLimits>,
+    subscription_limit: usize,
+) {
+    let (mut ws_sender, mut ws_receiver) = ws.split();
+    let (mut sender, mut receiver) = mpsc::channel::<Value>(OUTBOUND_QUEUE_SIZE);
+    tokio::spawn(async move {
+        while let Some(response) = receiver.next().await {
+            if ws_sender
+                .send(Message::text(response.to_string()))
+                .await
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+
+    let mut subscriptions = vec![];
+    // Held by every running subscription, which releases it when it ends, e.g. on errors.
+    let active_subscriptions = Arc::new(());
+    while let Some(Ok(message)) = ws_receiver.next().await {
+        if message.is_close() {
+            break;
+        }
+        // Ignore pings, pongs and binary messages.
+        let request = match message.to_str() {
+            Ok(text) => serde_json::from_str(text).unwrap_or(Value::Null),
+            Err(_) => continue,
+        };
+        if let Err(err) = limits.check(remote, &request) {
+            if sender.send(reject_calls(&request, err)).await.is_err() {
+                break;
+            }
+            continue;
+        }
+        match parse_subscription(request) {
+            Ok((id, _)) if Arc::strong_count(&active_subscriptions) > subscription_limit => {
+                counters::INVALID_REQUESTS
+                    .with_label_values(&["too_many_subscriptions"])
+                    .inc();
+                let err = JsonRpcError::too_many_subscriptions(subscription_limit);
+                if sender.send(error_response(id, err)).await.is_err() {
+                    break;
+                }
+            }
+            Ok((id, subscription)) => {
+                counters::REQUESTS
+                    .with_label_values(&[subscription.method(), "success"])
+                    .inc();
+                let (abort_handle, abort_registration) = AbortHandle::new_pair();
+                let active = Arc::clone(&active_subscriptions);
+                let run =
+                    subscription.run(id, service.clone(), Arc::clone(&registry), sender.clone());
+                tokio::spawn(Abortable::new(
+                    async move {
+                        run.await;
+                        drop(active);
+                    },
+                    abort_registration,
+                ));
+                subscriptions.push(abort_handle);
+            }
+            Err((id, err)) => {
+                if sender.send(error_response(id, err)).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    for subscription in subscriptions {
+        subscription.abort();
+    }
+}
+
+/// Parses a subscription request, returning the request id along with the error if it's invalid.
+fn parse_subscription(request: Value) -> Result<(Value, Subscription), (Value, JsonRpcError)> {
+    let request: Map<String, Value> = match request {
+        Value::Object(request) => request,
+        _ => {
+            counters::INVALID_REQUESTS
+                .with_label_values(&["invalid_format"])
+                .inc();
+            return Err((Value::Null, JsonRpcError::invalid_request()));
+        }
+    };
+    let id = parse_request_id(&request).map_err(|err| (Value::Null, err))?;
+    verify_protocol(&request).map_err(|err| (id.clone(), err))?;
+
+    let params = match request.get("params") {
+        Some(Value::Array(params)) => params,
+        _ => {
+            counters::INVALID_REQUESTS
+                .with_label_values(&["invalid_params"])
+                .inc();
+            return Err((id, JsonRpcError::invalid_params()));
+        }
+    };
+    let subscription = match request.get("method").and_then(Value::as_str) {
+        Some("subscribe_to_events") => match (
+            params.get(0).and_then(Value::as_str),
+            params.get(1).and_then(Value::as_u64),
+        ) {
+            (Some(event_key), Some(start_seq)) if params.len() == 2 => Subscription::Events {
+                event_key: event_key.to_string(),
+                next_seq: start_seq,
+            },
+            _ => return Err((id, JsonRpcError::invalid_params())),
+        },
+        Some("subscribe_to_transactions") => match params.get(0).and_then(Value::as_u64) {
+            Some(start_version) if params.len() == 1 => Subscription::Transactions {
+                next_version: start_version,
+            },
+            _ => return Err((id, JsonRpcError::invalid_params())),
+        },
+        Some(_) => {
+            counters::INVALID_REQUESTS
+                .with_label_values(&["method_not_found"])
+                .inc();
+            return Err((id, JsonRpcError::method_not_found()));
+        }
+        None => {
+            counters::INVALID_REQUESTS
+                .with_label_values(&["invalid_method"])
+                .inc();
+            return Err((id, JsonRpcError::invalid_request()));
+        }
+    };
+    Ok((id, subscription))
+}
+
+fn to_json_rpc_error(err: Error) -> JsonRpcError {
+    match err.downcast::<JsonRpcError>() {
+        Ok(err) => err,
+        Err(err) => JsonRpcError::internal_error(err.to_string()),
+    }
+}
+
+fn result_response(id: Value, result: Value) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "result": result})
+}
@@ -3,44 +3,71 @@
 
 use crate::{
     errors::{JsonRpcError, ServerCode},
+    methods::suggest_gas_unit_price,
     tests::utils::{test_bootstrap, MockLibraDB},
 };
-use futures::{channel::mpsc::channel, StreamExt};
-use libra_config::utils;
+use executor::{db_bootstrapper::bootstrap_db_if_empty, Executor};
+use executor_test_helpers::{
+    extract_signer, gen_block_id, gen_ledger_info_with_sigs, get_test_signed_transaction,
+};
+use executor_types::BlockExecutor;
+use futures::{channel::mpsc::channel, SinkExt, StreamExt};
+use libra_config::{
+    config::{RoleType, RpcConfig, RpcRateLimitConfig},
+    utils::{self, get_genesis_txn},
+};
 use libra_crypto::{ed25519::Ed25519PrivateKey, hash::CryptoHash, HashValue, PrivateKey, Uniform};
 use libra_json_rpc_client::{
     views::{
-        AccountStateWithProofView, BlockMetadata, BytesView, EventView, StateProofView,
-        TransactionDataView, TransactionView,
+        AccountStateWithProofView, AccountTransactionWithProofView, BlockMetadata, BytesView,
+        EventDataView, EventView, EventsWithProofView, MempoolTransactionStatusView,
+        StateProofView, TransactionByHashView, TransactionDataView, TransactionListWithProofView,
+        TransactionOutputView, TransactionView,
     },
-    JsonRpcAsyncClient, JsonRpcBatch, JsonRpcResponse, ResponseAsView,
+    JsonRpcAsyncClient, JsonRpcBatch, JsonRpcPoolClient, JsonRpcPoolConfig, JsonRpcResponse,
+    ResponseAsView, VerifyingClient,
+};
+use libra_mempool::{
+    MempoolClientRequest, MempoolPressure, MempoolTransactionStatus, RemovalReason,
 };
 use libra_proptest_helpers::ValueGenerator;
+use libra_temppath::TempPath;
 use libra_types::{
     account_address::AccountAddress,
-    account_config::AccountResource,
+    account_config::{association_address, AccountResource},
+    account_state::AccountState,
     account_state_blob::{AccountStateBlob, AccountStateWithProof},
-    contract_event::ContractEvent,
+    block_info::BlockInfo,
+    contract_event::{ContractEvent, EventWithProof},
     event::EventKey,
-    ledger_info::LedgerInfoWithSignatures,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
     mempool_status::{MempoolStatus, MempoolStatusCode},
     proof::{SparseMerkleProof, TransactionAccumulatorProof, TransactionInfoWithProof},
     test_helpers::transaction_test_helpers::get_test_signed_txn,
-    transaction::{Transaction, TransactionInfo, TransactionPayload},
+    transaction::{
+        authenticator::AuthenticationKey, RawTransaction, SignedTransaction, Transaction,
+        TransactionInfo, TransactionListWithProof, TransactionPayload, TransactionWithProof,
+    },
     vm_error::{StatusCode, VMStatus},
+    waypoint::Waypoint,
 };
-use libradb::test_helper::arb_blocks_to_commit;
-use move_core_types::language_storage::TypeTag;
+use libra_vm::LibraVM;
+use libradb::{test_helper::arb_blocks_to_commit, LibraDB};
+use move_core_types::language_storage::{TypeTag, RESOURCE_TAG};
 use proptest::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+use serde_json::json;
 use std::{
     collections::{BTreeMap, HashMap},
     convert::TryFrom,
     str::FromStr,
     sync::Arc,
-    time::{SystemTime, UNIX_EPOCH},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
-use storage_interface::DbReader;
+use storage_interface::{DbReader, DbReaderWriter};
 use tokio::runtime::Runtime;
+use tokio_tungstenite::tungstenite::Message;
+use transaction_builder::encode_mint_lbr_to_address_script;
 use vm_validator::{
     mocks::mock_vm_validator::MockVMValidator, vm_validator::TransactionValidation,
 };
@@ -57,6 +84,7 @@ fn mock_db() -> MockLibraDB {
     let mut all_accounts = BTreeMap::new();
     let mut all_txns = vec![];
     let mut events = vec![];
+    let mut account_state_history: BTreeMap<AccountAddress, Vec<u64>> = BTreeMap::new();
     let mut timestamp = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap()
@@ -74,6 +102,12 @@ fn mock_db() -> MockLibraDB {
                     .iter()
                     .map(|e| ((idx + version) as u64, e.clone())),
             );
+            for address in txn.account_states().keys() {
+                account_state_history
+                    .entry(*address)
+                    .or_default()
+                    .push((idx + version) as u64);
+            }
         }
         version += txns_to_commit.len();
         let mut account_states = HashMap::new();
@@ -120,6 +154,9 @@ fn mock_db() -> MockLibraDB {
         all_txns,
         events,
         account_state_with_proof,
+        least_readable_ledger_version: 0,
+        least_readable_state_version: 0,
+        account_state_history,
     }
 }
 
@@ -175,6 +212,102 @@ fn test_json_rpc_protocol() {
     assert_eq!(fetch_error(resp), -32000);
 }
 
+#[test]
+fn test_request_limits() {
+    let address = format!("0.0.0.0:{}", utils::get_available_port());
+    let config = RpcConfig {
+        address: address.parse().unwrap(),
+        batch_size_limit: 2,
+        content_length_limit: 1024,
+        page_size_limit: 10,
+        rate_limit: Some(RpcRateLimitConfig {
+            calls_per_interval: 3,
+            refill_interval_ms: 3_600_000,
+        }),
+        cors_allowed_origins: vec!["https://wallet.libra.org".to_string()],
+        subscriptions_per_connection_limit: 10,
+    };
+    let _runtime = crate::bootstrap(
+        &config,
+        Arc::new(mock_db()),
+        channel(1024).0,
+        RoleType::Validator,
+    );
+    let client = reqwest::blocking::Client::new();
+    let url = format!("http://{}", address);
+    let call = |id: u64, method: &str, params: serde_json::Value| json!({"jsonrpc": "2.0", "method": method, "params": params, "id": id});
+
+    // only allowed origins can call from browsers
+    let preflight = |origin: &str| {
+        client
+            .request(reqwest::Method::OPTIONS, &url)
+            .header("origin", origin)
+            .header("access-control-request-method", "POST")
+            .send()
+            .unwrap()
+    };
+    let resp = preflight("https://wallet.libra.org");
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers()["access-control-allow-origin"],
+        "https://wallet.libra.org"
+    );
+    assert_eq!(preflight("https://evil.example").status(), 403);
+
+    // body larger than the content length limit
+    let request = call(1, "get_metadata", json!(["0".repeat(1024)]));
+    let resp = client.post(&url).json(&request).send().unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(fetch_error(resp), ServerCode::ContentTooLarge as i16);
+
+    // batch larger than the batch size limit, each call is rejected
+    let request = json!([
+        call(1, "get_metadata", json!([])),
+        call(2, "get_metadata", json!([])),
+        call(3, "get_metadata", json!([])),
+    ]);
+    let responses: Vec<serde_json::Value> = client
+        .post(&url)
+        .json(&request)
+        .send()
+        .unwrap()
+        .json()
+        .unwrap();
+    assert_eq!(responses.len(), 3);
+    for (id, response) in (1..).zip(responses) {
+        assert_eq!(response["id"], json!(id));
+        assert_eq!(
+            response["error"]["code"],
+            json!(ServerCode::BatchSizeTooLarge as i16)
+        );
+    }
+
+    // page larger than the page size limit
+    let request = call(1, "get_transactions", json!([0, 11, false]));
+    let resp = client.post(&url).json(&request).send().unwrap();
+    assert_eq!(fetch_error(resp), ServerCode::PageSizeTooLarge as i16);
+
+    // the body and the batch too large were rejected before the rate limit is checked, so only
+    // the last call took a token, and two more calls are allowed before the limit is hit
+    let request = json!([
+        call(1, "get_metadata", json!([])),
+        call(2, "get_metadata", json!([])),
+    ]);
+    let responses: Vec<serde_json::Value> = client
+        .post(&url)
+        .json(&request)
+        .send()
+        .unwrap()
+        .json()
+        .unwrap();
+    assert!(responses
+        .iter()
+        .all(|response| response.get("result").is_some()));
+    let request = call(1, "get_metadata", json!([]));
+    let resp = client.post(&url).json(&request).send().unwrap();
+    assert_eq!(fetch_error(resp), ServerCode::RateLimited as i16);
+}
+
 #[test]
 fn test_transaction_submission() {
     let (mp_sender, mut mp_events) = channel(1);
@@ -190,7 +323,11 @@ fn test_transaction_submission() {
     // future that mocks shared mempool execution
     runtime.spawn(async move {
         let validator = MockVMValidator;
-        while let Some((txn, cb)) = mp_events.next().await {
+        while let Some(request) = mp_events.next().await {
+            let (txn, cb) = match request {
+                MempoolClientRequest::SubmitTransaction(txn, cb) => (txn, cb),
+                _ => panic!("unexpected mempool client request"),
+            };
             let vm_status = validator.validate_transaction(txn).unwrap().status();
             let result = if vm_status.is_some() {
                 (MempoolStatus::new(MempoolStatusCode::VmError), vm_status)
@@ -235,6 +372,172 @@ fn test_transaction_submission() {
     }
 }
 
+#[test]
+fn test_simulate_transaction() {
+    let (_mock_db, client, mut runtime) = create_database_client_and_runtime(1);
+
+    let sender = AccountAddress::new([9; AccountAddress::LENGTH]);
+    let privkey = Ed25519PrivateKey::generate_for_testing();
+    let other_pubkey = Ed25519PrivateKey::generate_for_testing().public_key();
+    // Signed by a key other than the one carried by the transaction.
+    let txn = get_test_signed_txn(sender, 0, &privkey, other_pubkey, None);
+
+    let mut simulate = move |skip_signature_check| {
+        let mut batch = JsonRpcBatch::default();
+        batch
+            .add_simulate_transaction_request(txn.clone(), skip_signature_check)
+            .unwrap();
+        let result = execute_batch_and_get_first_response(&client, &mut runtime, batch);
+        TransactionOutputView::from_response(result).unwrap()
+    };
+
+    let output = simulate(false);
+    assert_eq!(output.vm_status, StatusCode::INVALID_SIGNATURE);
+    assert!(output.discarded);
+    assert!(output.write_set.is_empty());
+
+    // The mock DB doesn't have the on-chain configs, so execution doesn't get far, but it
+    // shouldn't fail on the signature.
+    let output = simulate(true);
+    assert_ne!(output.vm_status, StatusCode::INVALID_SIGNATURE);
+}
+
+#[test]
+fn test_simulation_disabled_by_default() {
+    let port = utils::get_available_port();
+    let config = RpcConfig {
+        address: format!("0.0.0.0:{}", port).parse().unwrap(),
+        ..RpcConfig::default()
+    };
+    let mut runtime = crate::bootstrap(
+        &config,
+        Arc::new(mock_db()),
+        channel(1).0,
+        RoleType::Validator,
+    );
+    let client = JsonRpcAsyncClient::new(
+        reqwest::Url::from_str(format!("http://127.0.0.1:{}", port).as_str()).expect("invalid url"),
+    );
+
+    let sender = AccountAddress::new([9; AccountAddress::LENGTH]);
+    let privkey = Ed25519PrivateKey::generate_for_testing();
+    let txn = get_test_signed_txn(sender, 0, &privkey, privkey.public_key(), None);
+    let mut batch = JsonRpcBatch::default();
+    batch
+        .add_simulate_transaction_request(txn.clone(), true)
+        .unwrap();
+    batch.add_estimate_gas_request(txn).unwrap();
+    for response in runtime.block_on(client.execute(batch)).unwrap() {
+        let error = response.unwrap_err();
+        let error = error.downcast_ref::<JsonRpcError>().unwrap();
+        assert_eq!(error.code, JsonRpcError::method_not_found().code);
+    }
+}
+
+#[test]
+fn test_simulate_transaction_over_gas_limit() {
+    let (_mock_db, client, mut runtime) = create_database_client_and_runtime(1);
+
+    let sender = AccountAddress::new([9; AccountAddress::LENGTH]);
+    let privkey = Ed25519PrivateKey::generate_for_testing();
+    let txn = get_test_signed_txn(sender, 0, &privkey, privkey.public_key(), None);
+    let raw_txn = RawTransaction::new(
+        txn.sender(),
+        txn.sequence_number(),
+        txn.payload().clone(),
+        RpcConfig::default().simulation_max_gas_amount + 1,
+        txn.gas_unit_price(),
+        txn.gas_currency_code().to_string(),
+        txn.expiration_time(),
+    );
+    let txn = SignedTransaction::new_with_authenticator(raw_txn, txn.authenticator());
+
+    let mut batch = JsonRpcBatch::default();
+    batch.add_simulate_transaction_request(txn, true).unwrap();
+    let response = runtime.block_on(client.execute(batch)).unwrap().remove(0);
+    assert!(response.is_err());
+}
+
+#[test]
+fn test_suggest_gas_unit_price() {
+    let light = MempoolPressure {
+        num_transactions: 10,
+        capacity: 100,
+    };
+    let busy = MempoolPressure {
+        num_transactions: 60,
+        capacity: 100,
+    };
+
+    assert_eq!(suggest_gas_unit_price(vec![], &busy), 0);
+    let prices: Vec<u64> = (1..=11).rev().collect();
+    assert_eq!(suggest_gas_unit_price(prices.clone(), &light), 6);
+    assert_eq!(suggest_gas_unit_price(prices, &busy), 10);
+}
+
+#[test]
+fn test_get_mempool_transaction_status() {
+    let (mp_sender, mut mp_events) = channel(1);
+    let port = utils::get_available_port();
+    let address = format!("0.0.0.0:{}", port);
+    let mut runtime = test_bootstrap(address.parse().unwrap(), Arc::new(mock_db()), mp_sender);
+    let client = JsonRpcAsyncClient::new(
+        reqwest::Url::from_str(format!("http://{}:{}", "127.0.0.1", port).as_str())
+            .expect("invalid url"),
+    );
+
+    // future that mocks shared mempool, where transaction 0 is ready, 1 was rejected and 2 failed VM
+    // validation
+    runtime.spawn(async move {
+        while let Some(request) = mp_events.next().await {
+            match request {
+                MempoolClientRequest::GetTransactionStatus(_, sequence_number, cb) => {
+                    let status = match sequence_number {
+                        0 => Some(MempoolTransactionStatus::Ready {
+                            expiration_time: Duration::from_secs(100),
+                        }),
+                        1 => Some(MempoolTransactionStatus::Removed(RemovalReason::Rejected)),
+                        2 => Some(MempoolTransactionStatus::NotAccepted {
+                            code: MempoolStatusCode::VmError,
+                            vm_status: Some(StatusCode::INVALID_SIGNATURE),
+                        }),
+                        _ => None,
+                    };
+                    cb.send(status).unwrap();
+                }
+                _ => panic!("unexpected mempool client request"),
+            }
+        }
+    });
+    let mut get_status = |sequence_number| {
+        let mut batch = JsonRpcBatch::default();
+        batch.add_get_mempool_transaction_status_request(AccountAddress::random(), sequence_number);
+        let result = execute_batch_and_get_first_response(&client, &mut runtime, batch);
+        MempoolTransactionStatusView::optional_from_response(result).unwrap()
+    };
+
+    assert_eq!(
+        get_status(0),
+        Some(MempoolTransactionStatusView::Ready {
+            expiration_time: 100
+        })
+    );
+    assert_eq!(
+        get_status(1),
+        Some(MempoolTransactionStatusView::Removed {
+            reason: "rejected".to_string()
+        })
+    );
+    assert_eq!(
+        get_status(2),
+        Some(MempoolTransactionStatusView::NotAccepted {
+            reason: "vm_error".to_string(),
+            vm_status: Some(StatusCode::INVALID_SIGNATURE),
+        })
+    );
+    assert_eq!(get_status(3), None);
+}
+
 // TODO: Once account configs are published in the mock DB this test can be turned back on
 //#[test]
 //fn test_get_account_state() {
@@ -305,6 +608,59 @@ fn test_transaction_submission() {
 //    }
 //}
 
+#[test]
+fn test_bootstrap_secondary() {
+    let mut gen = ValueGenerator::new();
+    let blocks = loop {
+        let blocks = gen.generate(arb_blocks_to_commit());
+        if blocks.len() > 1 {
+            break blocks;
+        }
+    };
+    let tmp_dir = TempPath::new();
+    let secondary_tmp_dir = TempPath::new();
+    let db = LibraDB::new_for_test(&tmp_dir);
+    let mut cur_ver = 0;
+    let (txns_to_commit, ledger_info_with_sigs) = &blocks[0];
+    db.save_transactions(txns_to_commit, cur_ver, Some(ledger_info_with_sigs))
+        .unwrap();
+    cur_ver += txns_to_commit.len() as u64;
+
+    let port = utils::get_available_port();
+    let config = RpcConfig {
+        address: format!("0.0.0.0:{}", port).parse().unwrap(),
+        ..RpcConfig::default()
+    };
+    let mut runtime = crate::bootstrap_secondary(
+        &config,
+        tmp_dir.path(),
+        secondary_tmp_dir.path(),
+        Duration::from_millis(10),
+        RoleType::FullNode,
+    )
+    .unwrap();
+    let client = JsonRpcAsyncClient::new(
+        reqwest::Url::from_str(format!("http://127.0.0.1:{}", port).as_str()).expect("invalid url"),
+    );
+    let mut get_version = || {
+        let mut batch = JsonRpcBatch::default();
+        batch.add_get_metadata_request(None);
+        let result = execute_batch_and_get_first_response(&client, &mut runtime, batch);
+        BlockMetadata::from_response(result).unwrap().version
+    };
+    assert_eq!(get_version(), cur_ver - 1);
+
+    // what the node commits later is served once the secondary catches up
+    for (txns_to_commit, ledger_info_with_sigs) in &blocks[1..] {
+        db.save_transactions(txns_to_commit, cur_ver, Some(ledger_info_with_sigs))
+            .unwrap();
+        cur_ver += txns_to_commit.len() as u64;
+    }
+    while get_version() != cur_ver - 1 {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
 #[test]
 fn test_get_metadata() {
     let (mock_db, client, mut runtime) = create_database_client_and_runtime(1);
@@ -350,6 +706,242 @@ fn test_get_events() {
     );
 }
 
+#[test]
+fn test_get_account_state_history() {
+    let (mock_db, client, mut runtime) = create_database_client_and_runtime(1);
+
+    let (address, versions) = mock_db.account_state_history.iter().next().unwrap();
+
+    let mut batch = JsonRpcBatch::default();
+    batch.add_get_account_state_history_request(*address, 0, 1000);
+    let result = execute_batch_and_get_first_response(&client, &mut runtime, batch);
+    assert_eq!(
+        result,
+        JsonRpcResponse::AccountStateHistoryResponse(versions.clone())
+    );
+
+    // Page from right after the first version.
+    let mut batch = JsonRpcBatch::default();
+    batch.add_get_account_state_history_request(*address, versions[0] + 1, 1);
+    let result = execute_batch_and_get_first_response(&client, &mut runtime, batch);
+    assert_eq!(
+        result,
+        JsonRpcResponse::AccountStateHistoryResponse(
+            versions.iter().skip(1).take(1).cloned().collect()
+        )
+    );
+}
+
+#[test]
+fn test_get_account_resources() {
+    let (mock_db, client, mut runtime) = create_database_client_and_runtime(1);
+
+    let (address, blob) = mock_db.all_accounts.iter().next().unwrap();
+    let mut batch = JsonRpcBatch::default();
+    batch.add_get_account_resources_request(*address, 0, vec![]);
+    let result = execute_batch_and_get_first_response(&client, &mut runtime, batch);
+    let resources = match result {
+        JsonRpcResponse::AccountResourcesResponse(Some(resources)) => resources,
+        _ => panic!("unexpected response: {:?}", result),
+    };
+    let expected: Vec<_> = AccountState::try_from(blob)
+        .unwrap()
+        .iter()
+        .filter(|(path, _)| path.first() == Some(&RESOURCE_TAG))
+        .map(|(path, data)| (BytesView::from(path), BytesView::from(data)))
+        .collect();
+    let actual: Vec<_> = resources
+        .into_iter()
+        .map(|resource| (resource.path, resource.data))
+        .collect();
+    assert_eq!(actual, expected);
+
+    // Account that doesn't exist
+    let mut batch = JsonRpcBatch::default();
+    batch.add_get_account_resources_request(
+        AccountAddress::random(),
+        0,
+        vec!["0x1::M::S<u64>".to_string()],
+    );
+    let result = execute_batch_and_get_first_response(&client, &mut runtime, batch);
+    assert_eq!(result, JsonRpcResponse::AccountResourcesResponse(None));
+
+    // Types which aren't structs
+    let mut batch = JsonRpcBatch::default();
+    batch.add_get_account_resources_request(*address, 0, vec!["u64".to_string()]);
+    let response = runtime.block_on(client.execute(batch)).unwrap().remove(0);
+    assert!(response.is_err());
+}
+
+#[test]
+fn test_subscriptions() {
+    let mock_db = mock_db();
+    let port = utils::get_available_port();
+    let address = format!("0.0.0.0:{}", port);
+    let mut runtime = test_bootstrap(
+        address.parse().unwrap(),
+        Arc::new(mock_db.clone()),
+        channel(1).0,
+    );
+
+    runtime.block_on(async {
+        let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://127.0.0.1:{}/ws", port))
+            .await
+            .unwrap();
+
+        // Transactions are pushed in order, from the start version.
+        let start_version = 1;
+        ws.send(Message::Text(
+            json!({"jsonrpc": "2.0", "id": 1, "method": "subscribe_to_transactions", "params": [start_version]})
+                .to_string(),
+        ))
+        .await
+        .unwrap();
+        for version in start_version..start_version + 3 {
+            let response = receive_json(&mut ws).await;
+            assert_eq!(response["id"], 1);
+            let txn: TransactionView =
+                serde_json::from_value(response["result"].clone()).unwrap();
+            assert_eq!(txn.version, version);
+            assert_eq!(
+                txn.hash,
+                mock_db.all_txns[version as usize].0.hash().to_string()
+            );
+        }
+
+        // Unknown methods are rejected.
+        ws.send(Message::Text(
+            json!({"jsonrpc": "2.0", "id": 2, "method": "subscribe_to_blocks", "params": []})
+                .to_string(),
+        ))
+        .await
+        .unwrap();
+        loop {
+            let response = receive_json(&mut ws).await;
+            if response["id"] == 2 {
+                assert_eq!(
+                    response["error"]["code"],
+                    JsonRpcError::method_not_found().code
+                );
+                break;
+            }
+        }
+    });
+}
+
+#[test]
+fn test_subscription_limit() {
+    let port = utils::get_available_port();
+    let config = RpcConfig {
+        address: format!("0.0.0.0:{}", port).parse().unwrap(),
+        subscriptions_per_connection_limit: 1,
+        ..RpcConfig::default()
+    };
+    let mut runtime = crate::bootstrap(
+        &config,
+        Arc::new(mock_db()),
+        channel(1).0,
+        RoleType::Validator,
+    );
+
+    runtime.block_on(async {
+        let subscribe = |id: u64| {
+            Message::Text(
+                json!({"jsonrpc": "2.0", "id": id, "method": "subscribe_to_transactions", "params": [0]})
+                    .to_string(),
+            )
+        };
+        let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://127.0.0.1:{}/ws", port))
+            .await
+            .unwrap();
+        ws.send(subscribe(1)).await.unwrap();
+        ws.send(subscribe(2)).await.unwrap();
+        loop {
+            let response = receive_json(&mut ws).await;
+            if response["id"] == 2 {
+                assert_eq!(
+                    response["error"]["code"],
+                    json!(ServerCode::TooManySubscriptions as i16)
+                );
+                break;
+            }
+            assert_eq!(response["id"], 1);
+        }
+
+        // The limit is per connection.
+        let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://127.0.0.1:{}/ws", port))
+            .await
+            .unwrap();
+        ws.send(subscribe(3)).await.unwrap();
+        let response = receive_json(&mut ws).await;
+        assert_eq!(response["id"], 3);
+        assert!(response.get("error").is_none());
+    });
+}
+
+#[test]
+fn test_subscription_call_limits() {
+    let port = utils::get_available_port();
+    let config = RpcConfig {
+        address: format!("0.0.0.0:{}", port).parse().unwrap(),
+        batch_size_limit: 2,
+        rate_limit: Some(RpcRateLimitConfig {
+            calls_per_interval: 1,
+            refill_interval_ms: 3_600_000,
+        }),
+        ..RpcConfig::default()
+    };
+    let mut runtime = crate::bootstrap(
+        &config,
+        Arc::new(mock_db()),
+        channel(1).0,
+        RoleType::Validator,
+    );
+
+    runtime.block_on(async {
+        let subscribe = |id: u64| json!({"jsonrpc": "2.0", "id": id, "method": "subscribe_to_transactions", "params": [0]});
+        let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://127.0.0.1:{}/ws", port))
+            .await
+            .unwrap();
+
+        // batch larger than the batch size limit, each call is rejected
+        ws.send(Message::Text(
+            json!([subscribe(1), subscribe(2), subscribe(3)]).to_string(),
+        ))
+        .await
+        .unwrap();
+        let responses = receive_json(&mut ws).await;
+        assert_eq!(responses.as_array().unwrap().len(), 3);
+        for (id, response) in (1..).zip(responses.as_array().unwrap()) {
+            assert_eq!(response["id"], json!(id));
+            assert_eq!(
+                response["error"]["code"],
+                json!(ServerCode::BatchSizeTooLarge as i16)
+            );
+        }
+
+        // the only call allowed by the rate limit subscribes, the next one is rejected
+        ws.send(Message::Text(subscribe(4).to_string()))
+            .await
+            .unwrap();
+        ws.send(Message::Text(subscribe(5).to_string()))
+            .await
+            .unwrap();
+        loop {
+            let response = receive_json(&mut ws).await;
+            if response["id"] == 5 {
+                assert_eq!(
+                    response["error"]["code"],
+                    json!(ServerCode::RateLimited as i16)
+                );
+                break;
+            }
+            assert_eq!(response["id"], 4);
+            assert!(response.get("error").is_none());
+        }
+    });
+}
+
 #[test]
 fn test_get_transactions() {
     let (mock_db, client, mut runtime) = create_database_client_and_runtime(1);
@@ -426,6 +1018,46 @@ fn test_get_transactions() {
     }
 }
 
+#[test]
+fn test_get_transactions_pruned() {
+    let mut mock_db = mock_db();
+    mock_db.least_readable_ledger_version = 2;
+    // Pruning the state further doesn't affect the transactions.
+    mock_db.least_readable_state_version = 3;
+
+    let port = utils::get_available_port();
+    let address = format!("0.0.0.0:{}", port);
+    let mut runtime = test_bootstrap(address.parse().unwrap(), Arc::new(mock_db), channel(1).0);
+    let client = JsonRpcAsyncClient::new(
+        reqwest::Url::from_str(format!("http://127.0.0.1:{}", port).as_str()).expect("invalid url"),
+    );
+
+    let mut batch = JsonRpcBatch::default();
+    batch.add_get_transactions_request(1, 1, false);
+    let response = runtime.block_on(client.execute(batch)).unwrap().remove(0);
+    let error = response.expect_err("expected error");
+    let error = error
+        .downcast_ref::<JsonRpcError>()
+        .expect("unexpected error format");
+    assert_eq!(error.code, ServerCode::VersionPruned as i16);
+
+    let mut batch = JsonRpcBatch::default();
+    batch.add_get_transactions_request(2, 1, false);
+    let result = execute_batch_and_get_first_response(&client, &mut runtime, batch);
+    let txns = TransactionView::vec_from_response(result).unwrap();
+    assert_eq!(txns[0].version, 2);
+
+    // while the state at version 2 is pruned
+    let mut batch = JsonRpcBatch::default();
+    batch.add_get_account_resources_request(AccountAddress::random(), 2, vec![]);
+    let response = runtime.block_on(client.execute(batch)).unwrap().remove(0);
+    let error = response.expect_err("expected error");
+    let error = error
+        .downcast_ref::<JsonRpcError>()
+        .expect("unexpected error format");
+    assert_eq!(error.code, ServerCode::VersionPruned as i16);
+}
+
 #[test]
 fn test_get_account_transaction() {
     let (mock_db, client, mut runtime) = create_database_client_and_runtime(1);
@@ -502,6 +1134,171 @@ fn test_get_account_transaction() {
     }
 }
 
+#[test]
+fn test_get_transaction_by_hash() {
+    let (mp_sender, mut mp_events) = channel(1);
+    let mock_db = mock_db();
+    let port = utils::get_available_port();
+    let address = format!("0.0.0.0:{}", port);
+    let mut runtime = test_bootstrap(
+        address.parse().unwrap(),
+        Arc::new(mock_db.clone()),
+        mp_sender,
+    );
+    let client = JsonRpcAsyncClient::new(
+        reqwest::Url::from_str(format!("http://{}:{}", "127.0.0.1", port).as_str())
+            .expect("invalid url"),
+    );
+
+    let privkey = Ed25519PrivateKey::generate_for_testing();
+    let pending_txn = get_test_signed_txn(
+        AccountAddress::random(),
+        0,
+        &privkey,
+        privkey.public_key(),
+        None,
+    );
+    let pending_hash = Transaction::UserTransaction(pending_txn.clone()).hash();
+    // future that mocks shared mempool holding `pending_txn`
+    let mempool_txn = pending_txn.clone();
+    runtime.spawn(async move {
+        while let Some(request) = mp_events.next().await {
+            match request {
+                MempoolClientRequest::GetTransactionByHash(hash, cb) => {
+                    let txn = if hash == pending_hash {
+                        Some(mempool_txn.clone())
+                    } else {
+                        None
+                    };
+                    cb.send(txn).unwrap();
+                }
+                _ => panic!("unexpected mempool client request"),
+            }
+        }
+    });
+    let mut get_transaction_by_hash = |hash| {
+        let mut batch = JsonRpcBatch::default();
+        batch.add_get_transaction_by_hash_request(hash, true);
+        let result = execute_batch_and_get_first_response(&client, &mut runtime, batch);
+        TransactionByHashView::optional_from_response(result).unwrap()
+    };
+
+    let (version, (committed_txn, _)) = mock_db
+        .all_txns
+        .iter()
+        .enumerate()
+        .find(|(_, (t, _))| t.as_signed_user_txn().is_ok())
+        .expect("no user transaction");
+    match get_transaction_by_hash(committed_txn.hash()) {
+        Some(TransactionByHashView::Committed { transaction }) => {
+            assert_eq!(transaction.version, version as u64);
+            assert_eq!(transaction.hash, committed_txn.hash().to_string());
+        }
+        view => panic!("unexpected view: {:?}", view),
+    }
+
+    match get_transaction_by_hash(pending_hash) {
+        Some(TransactionByHashView::Pending { transaction }) => {
+            assert_eq!(
+                transaction,
+                TransactionDataView::from(Transaction::UserTransaction(pending_txn))
+            );
+        }
+        view => panic!("unexpected view: {:?}", view),
+    }
+
+    assert_eq!(get_transaction_by_hash(HashValue::zero()), None);
+}
+
+#[test]
+fn test_get_transactions_with_proofs() {
+    let (mock_db, client, mut runtime) = create_database_client_and_runtime(1);
+
+    let start_version = 1;
+    let mut batch = JsonRpcBatch::default();
+    batch.add_get_transactions_with_proofs_request(start_version, 10, true);
+    let result = execute_batch_and_get_first_response(&client, &mut runtime, batch);
+    let view = TransactionListWithProofView::from_response(result).unwrap();
+    let (li, txn_list_with_proof): (LedgerInfoWithSignatures, TransactionListWithProof) =
+        TryFrom::try_from(&view).unwrap();
+
+    assert_eq!(li.ledger_info().version(), mock_db.version);
+    assert_eq!(
+        txn_list_with_proof.first_transaction_version,
+        Some(start_version)
+    );
+    let expected_txns: Vec<_> = mock_db
+        .all_txns
+        .iter()
+        .skip(start_version as usize)
+        .take(10)
+        .map(|(txn, _)| txn.clone())
+        .collect();
+    assert_eq!(txn_list_with_proof.transactions, expected_txns);
+    assert_eq!(
+        txn_list_with_proof.events.unwrap().len(),
+        expected_txns.len()
+    );
+}
+
+#[test]
+fn test_get_account_transaction_with_proofs() {
+    let (mock_db, client, mut runtime) = create_database_client_and_runtime(1);
+
+    let (version, txn) = mock_db
+        .all_txns
+        .iter()
+        .enumerate()
+        .find_map(|(version, (txn, _))| {
+            txn.as_signed_user_txn()
+                .ok()
+                .map(|signed_txn| (version as u64, signed_txn.clone()))
+        })
+        .expect("Mock DB should have user transactions.");
+
+    let mut batch = JsonRpcBatch::default();
+    batch.add_get_account_transaction_with_proofs_request(
+        txn.sender(),
+        txn.sequence_number(),
+        false,
+    );
+    let result = execute_batch_and_get_first_response(&client, &mut runtime, batch);
+    let view = AccountTransactionWithProofView::from_response(result).unwrap();
+    let (li, txn_with_proof): (LedgerInfoWithSignatures, Option<TransactionWithProof>) =
+        TryFrom::try_from(&view).unwrap();
+
+    assert_eq!(li.ledger_info().version(), mock_db.version);
+    let txn_with_proof = txn_with_proof.expect("Transaction should exist.");
+    assert_eq!(txn_with_proof.version, version);
+    assert_eq!(
+        txn_with_proof.transaction,
+        Transaction::UserTransaction(txn)
+    );
+    assert!(txn_with_proof.events.is_none());
+}
+
+#[test]
+fn test_get_events_with_proofs() {
+    let (mock_db, client, mut runtime) = create_database_client_and_runtime(1);
+
+    let (first_event_version, first_event) = mock_db.events[0].clone();
+    let event_key = hex::encode(first_event.key().as_bytes());
+
+    let mut batch = JsonRpcBatch::default();
+    batch.add_get_events_with_proofs_request(event_key, first_event.sequence_number(), 10);
+    let result = execute_batch_and_get_first_response(&client, &mut runtime, batch);
+    let view = EventsWithProofView::from_response(result).unwrap();
+    let (li, events_with_proof): (LedgerInfoWithSignatures, Vec<EventWithProof>) =
+        TryFrom::try_from(&view).unwrap();
+
+    assert_eq!(li.ledger_info().version(), mock_db.version);
+    assert_eq!(
+        events_with_proof[0].transaction_version,
+        first_event_version
+    );
+    assert_eq!(events_with_proof[0].event, first_event);
+}
+
 #[test]
 // Check that if version and ledger_version parameters are None, then the server returns the latest
 // known state.
@@ -583,6 +1380,151 @@ fn test_get_state_proof() {
     assert_eq!(li.ledger_info().version(), version);
 }
 
+#[test]
+fn test_verifying_client_rejects_unverifiable_state_proof() {
+    let mock_db = mock_db();
+    let port = utils::get_available_port();
+    let address = format!("0.0.0.0:{}", port);
+    let _runtime = test_bootstrap(
+        address.parse().unwrap(),
+        Arc::new(mock_db.clone()),
+        channel(1).0,
+    );
+    let url =
+        reqwest::Url::from_str(format!("http://127.0.0.1:{}", port).as_str()).expect("invalid url");
+
+    // The mock DB serves no epoch change proof, so its ledger infos can't be verified starting
+    // from the waypoint.
+    let waypoint = Waypoint::new_any(&LedgerInfo::new(BlockInfo::empty(), HashValue::zero()));
+    let mut client = VerifyingClient::new(url, waypoint).unwrap();
+
+    let account = get_first_account_from_mock_db(&mock_db);
+    assert!(client.get_account_state(account).is_err());
+    assert!(client.get_transactions(0, 10, false).is_err());
+    assert!(client.latest_ledger_info().is_none());
+    assert_eq!(client.trusted_state().latest_version(), waypoint.version());
+}
+
+#[test]
+fn test_verifying_client() {
+    let (_db_dir, db, waypoint) = tmp_db_with_executed_transactions();
+    let port = utils::get_available_port();
+    let address = format!("0.0.0.0:{}", port);
+    let _runtime = test_bootstrap(address.parse().unwrap(), db.clone(), channel(1).0);
+    let url =
+        reqwest::Url::from_str(format!("http://127.0.0.1:{}", port).as_str()).expect("invalid url");
+    let mut client = VerifyingClient::new(url, waypoint).unwrap();
+
+    let version = db.get_latest_version().unwrap();
+    let (blob, state_version) = client.get_account_state(association_address()).unwrap();
+    assert_eq!(state_version, version);
+    assert_eq!(
+        blob.unwrap(),
+        db.get_latest_account_state(association_address())
+            .unwrap()
+            .unwrap()
+    );
+    assert_eq!(client.trusted_state().latest_version(), version);
+
+    // genesis and the transactions of the block
+    let txns = client.get_transactions(0, 100, true).unwrap();
+    assert_eq!(txns.len() as u64, version + 1);
+    let txn = client
+        .get_account_transaction(association_address(), 1, true)
+        .unwrap()
+        .unwrap();
+    assert_eq!(txn, txns[1]);
+    assert!(client
+        .get_account_transaction(association_address(), 100, false)
+        .unwrap()
+        .is_none());
+
+    // the account minted to twice has received two payments
+    let event = txns[1]
+        .events
+        .iter()
+        .find(|event| matches!(event.data, EventDataView::ReceivedPayment { .. }))
+        .unwrap();
+    let event_key = EventKey::try_from(event.key.clone().into_bytes().unwrap().as_slice()).unwrap();
+    let account_state = db
+        .get_latest_account_state(event_key.get_creator_address())
+        .unwrap()
+        .unwrap();
+    let account_resource = AccountState::try_from(&account_state)
+        .unwrap()
+        .get_account_resource()
+        .unwrap()
+        .unwrap();
+    let received_events = account_resource.received_events();
+    assert_eq!(*received_events.key(), event_key);
+    assert_eq!(received_events.count(), 2);
+    let events = client.get_events(received_events.key(), 0, 10).unwrap();
+    assert_eq!(
+        events
+            .iter()
+            .map(|event| event.sequence_number)
+            .collect::<Vec<_>>(),
+        vec![0, 1]
+    );
+    let events = client.get_events(received_events.key(), 1, 1).unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].sequence_number, 1);
+    assert!(client
+        .get_events(received_events.key(), 2, 10)
+        .unwrap()
+        .is_empty());
+    // the length of a stream which isn't one of the account resource can't be verified
+    let unknown_key =
+        EventKey::new_from_address(&event_key.get_creator_address(), u64::max_value());
+    assert!(client.get_events(&unknown_key, 0, 10).is_err());
+}
+
+#[test]
+fn test_pool_client() {
+    let start_server = |mock_db: MockLibraDB| {
+        let port = utils::get_available_port();
+        let address = format!("0.0.0.0:{}", port);
+        let runtime = test_bootstrap(address.parse().unwrap(), Arc::new(mock_db), channel(1).0);
+        let url = reqwest::Url::from_str(&format!("http://127.0.0.1:{}", port)).unwrap();
+        (runtime, url)
+    };
+    let get_metadata = |client: &mut JsonRpcPoolClient| {
+        let mut batch = JsonRpcBatch::default();
+        batch.add_get_metadata_request(None);
+        let response = client.execute(batch).unwrap().remove(0).unwrap();
+        BlockMetadata::from_response(response).unwrap()
+    };
+    let config = JsonRpcPoolConfig {
+        max_version_lag: 10,
+        retry_delay_ms: 0,
+        ..JsonRpcPoolConfig::default()
+    };
+
+    let mock_db = mock_db();
+    let mut leading_db = mock_db.clone();
+    leading_db.version += 11;
+    let (_runtime, url) = start_server(mock_db.clone());
+    let (leading_runtime, leading_url) = start_server(leading_db.clone());
+    let down_url =
+        reqwest::Url::from_str(&format!("http://127.0.0.1:{}", utils::get_available_port()))
+            .unwrap();
+
+    // requests fail over from the endpoint which is down
+    let mut client = JsonRpcPoolClient::new(vec![down_url, url.clone()], config.clone()).unwrap();
+    assert_eq!(get_metadata(&mut client).version, mock_db.version);
+
+    // requests go to the endpoint with the highest version
+    let mut client = JsonRpcPoolClient::new(vec![url, leading_url], config).unwrap();
+    assert_eq!(get_metadata(&mut client).version, leading_db.version);
+
+    // once the leading endpoint is down, it no longer counts, so the other one isn't stale even
+    // though it's more than 10 versions behind the leading one
+    drop(leading_runtime);
+    assert_eq!(get_metadata(&mut client).version, mock_db.version);
+    client.health_check();
+    assert_eq!(get_metadata(&mut client).version, mock_db.version);
+}
+
 #[test]
 fn test_get_network_status() {
     let (_mock_db, client, mut runtime) = create_database_client_and_runtime(1);
@@ -624,6 +1566,59 @@ fn create_database_client_and_runtime(
     (mock_db, client, runtime)
 }
 
+/// Returns a LibraDB with genesis and a block minting twice to a new account committed, so that
+/// its content can be verified, along with the waypoint of genesis.
+fn tmp_db_with_executed_transactions() -> (TempPath, Arc<LibraDB>, Waypoint) {
+    let (mut config, genesis_key) = config_builder::test_config();
+    let signer = extract_signer(&mut config);
+    let tmpdir = TempPath::new();
+    let (db, db_rw) = DbReaderWriter::wrap(LibraDB::new_for_test(&tmpdir));
+    let waypoint = bootstrap_db_if_empty::<LibraVM>(&db_rw, get_genesis_txn(&config).unwrap())
+        .unwrap()
+        .unwrap();
+    let mut executor = Executor::<LibraVM>::new(db_rw);
+
+    let auth_key = AuthenticationKey::ed25519(
+        &Ed25519PrivateKey::generate(&mut StdRng::from_seed([1u8; 32])).public_key(),
+    );
+    let block: Vec<_> = (1..=2)
+        .map(|sequence_number| {
+            get_test_signed_transaction(
+                association_address(),
+                sequence_number,
+                genesis_key.clone(),
+                genesis_key.public_key(),
+                Some(encode_mint_lbr_to_address_script(
+                    &auth_key.derived_address(),
+                    auth_key.prefix().to_vec(),
+                    1_000_000,
+                )),
+            )
+        })
+        .collect();
+    let block_id = gen_block_id(1);
+    let output = executor
+        .execute_block((block_id, block), executor.committed_block_id())
+        .unwrap();
+    executor
+        .commit_blocks(
+            vec![block_id],
+            gen_ledger_info_with_sigs(1, output, block_id, vec![&signer]),
+        )
+        .unwrap();
+
+    (tmpdir, db, waypoint)
+}
+
+/// Receives the next message from the WebSocket and parses it as JSON.
+async fn receive_json<S>(ws: &mut S) -> serde_json::Value
+where
+    S: futures::Stream<Item = Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin,
+{
+    let text = ws.next().await.unwrap().unwrap().into_text().unwrap();
+    serde_json::from_str(&text).unwrap()
+}
+
 /// Returns the first account address stored in the given mock database.
 fn get_first_account_from_mock_db(mock_db: &MockLibraDB) -> AccountAddress {
     *mock_db
@@ -2,20 +2,24 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::{Error, Result};
-use libra_config::config::RoleType;
-use libra_crypto::HashValue;
+use libra_config::config::{RoleType, RpcConfig};
+use libra_crypto::{
+    hash::{CryptoHash, SPARSE_MERKLE_PLACEHOLDER_HASH},
+    HashValue,
+};
 use libra_mempool::MempoolClientSender;
 use libra_types::{
     account_address::AccountAddress,
     account_state_blob::{AccountStateBlob, AccountStateWithProof},
     block_info::BlockInfo,
-    contract_event::ContractEvent,
+    contract_event::{ContractEvent, EventWithProof},
     epoch_change::EpochChangeProof,
     event::EventKey,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
     proof::{
-        AccumulatorConsistencyProof, AccumulatorRangeProof, SparseMerkleProof,
-        TransactionAccumulatorProof, TransactionInfoWithProof, TransactionListProof,
+        AccumulatorConsistencyProof, AccumulatorRangeProof, EventAccumulatorProof, EventProof,
+        SparseMerkleProof, TransactionAccumulatorProof, TransactionInfoWithProof,
+        TransactionListProof,
     },
     transaction::{
         Transaction, TransactionInfo, TransactionListWithProof, TransactionWithProof, Version,
@@ -33,7 +37,12 @@ pub fn test_bootstrap(
     libra_db: Arc<dyn DbReader>,
     mp_sender: MempoolClientSender,
 ) -> Runtime {
-    crate::bootstrap(address, libra_db, mp_sender, RoleType::Validator)
+    let config = RpcConfig {
+        address,
+        simulation_enabled: true,
+        ..RpcConfig::default()
+    };
+    crate::bootstrap(&config, libra_db, mp_sender, RoleType::Validator)
 }
 
 /// Lightweight mock of LibraDB
@@ -45,6 +54,9 @@ pub(crate) struct MockLibraDB {
     pub all_txns: Vec<(Transaction, StatusCode)>,
     pub events: Vec<(u64, ContractEvent)>,
     pub account_state_with_proof: Vec<AccountStateWithProof>,
+    pub least_readable_ledger_version: u64,
+    pub least_readable_state_version: u64,
+    pub account_state_history: BTreeMap<AccountAddress, Vec<u64>>,
 }
 
 impl DbReader for MockLibraDB {
@@ -77,6 +89,32 @@ impl DbReader for MockLibraDB {
         ))
     }
 
+    fn get_least_readable_ledger_version(&self) -> Result<Version> {
+        Ok(self.least_readable_ledger_version)
+    }
+
+    fn get_least_readable_state_version(&self) -> Result<Version> {
+        Ok(self.least_readable_state_version)
+    }
+
+    fn get_account_state_history(
+        &self,
+        address: AccountAddress,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<Version>> {
+        Ok(self
+            .account_state_history
+            .get(&address)
+            .into_iter()
+            .flatten()
+            .cloned()
+            .filter(|v| start_version <= *v && *v <= ledger_version)
+            .take(limit as usize)
+            .collect())
+    }
+
     fn get_txn_by_account(
         &self,
         address: AccountAddress,
@@ -123,6 +161,24 @@ impl DbReader for MockLibraDB {
             }))
     }
 
+    fn get_txn_by_hash(
+        &self,
+        hash: HashValue,
+        ledger_version: u64,
+        fetch_events: bool,
+    ) -> Result<Option<TransactionWithProof>, Error> {
+        // Only user transactions are looked up, which is all the tests need.
+        match self.all_txns.iter().find(|(x, _)| x.hash() == hash) {
+            Some((Transaction::UserTransaction(t), _)) => self.get_txn_by_account(
+                t.sender(),
+                t.sequence_number(),
+                ledger_version,
+                fetch_events,
+            ),
+            _ => Ok(None),
+        }
+    }
+
     fn get_transactions(
         &self,
         start_version: u64,
@@ -140,7 +196,8 @@ impl DbReader for MockLibraDB {
                 transactions.push(t.clone());
                 txn_infos.push(TransactionInfo::new(
                     Default::default(),
-                    Default::default(),
+                    // matches the state root of `get_latest_state_root`
+                    *SPARSE_MERKLE_PLACEHOLDER_HASH,
                     Default::default(),
                     0,
                     *status,
@@ -192,6 +249,41 @@ impl DbReader for MockLibraDB {
         Ok(events)
     }
 
+    fn get_events_with_proofs(
+        &self,
+        key: &EventKey,
+        start: u64,
+        ascending: bool,
+        limit: u64,
+        _ledger_version: Version,
+    ) -> Result<Vec<EventWithProof>> {
+        let events = self
+            .get_events(key, start, ascending, limit)?
+            .into_iter()
+            .map(|(version, event)| {
+                EventWithProof::new(
+                    version,
+                    0,
+                    event,
+                    EventProof::new(
+                        TransactionInfoWithProof::new(
+                            TransactionAccumulatorProof::new(vec![]),
+                            TransactionInfo::new(
+                                Default::default(),
+                                Default::default(),
+                                Default::default(),
+                                0,
+                                StatusCode::EXECUTED,
+                            ),
+                        ),
+                        EventAccumulatorProof::new(vec![]),
+                    ),
+                )
+            })
+            .collect();
+        Ok(events)
+    }
+
     fn get_state_proof(
         &self,
         known_version: u64,
@@ -245,7 +337,9 @@ impl DbReader for MockLibraDB {
     }
 
     fn get_latest_state_root(&self) -> Result<(u64, HashValue)> {
-        unimplemented!()
+        // Consistent with the empty proofs returned by `get_account_state_with_proof_by_version`
+        // for accounts that don't exist.
+        Ok((self.version, *SPARSE_MERKLE_PLACEHOLDER_HASH))
     }
 
     fn get_latest_tree_state(&self) -> Result<TreeState> {
@@ -29,6 +29,21 @@ pub enum ServerCode {
     MempoolInvalidUpdate = -32010,
     MempoolVmError = -32011,
     MempoolUnknownError = -32012,
+
+    // Storage errors
+    VersionPruned = -32013,
+
+    // Request limit errors - see `RpcConfig` for the limits
+    ContentTooLarge = -32014,
+    BatchSizeTooLarge = -32015,
+    PageSizeTooLarge = -32016,
+    RateLimited = -32017,
+
+    // Mempool admission policy error - see `MempoolStatusCode` for specs
+    MempoolRejectedByPolicy = -32018,
+
+    // WebSocket subscription errors
+    TooManySubscriptions = -32019,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -83,6 +98,71 @@ impl JsonRpcError {
         }
     }
 
+    pub fn version_pruned(version: u64, least_readable_version: u64) -> Self {
+        Self {
+            code: ServerCode::VersionPruned as i16,
+            message: format!(
+                "Server error: version {} is pruned, the least readable version is {}",
+                version, least_readable_version
+            ),
+            data: Some(serde_json::json!({
+                "least_readable_version": least_readable_version
+            })),
+        }
+    }
+
+    pub fn content_too_large(limit: u64) -> Self {
+        Self {
+            code: ServerCode::ContentTooLarge as i16,
+            message: format!(
+                "Server error: request body is larger than the limit of {} bytes",
+                limit
+            ),
+            data: Some(serde_json::json!({ "limit": limit })),
+        }
+    }
+
+    pub fn batch_size_too_large(size: usize, limit: usize) -> Self {
+        Self {
+            code: ServerCode::BatchSizeTooLarge as i16,
+            message: format!(
+                "Server error: batch of {} calls is larger than the limit of {}",
+                size, limit
+            ),
+            data: Some(serde_json::json!({ "limit": limit })),
+        }
+    }
+
+    pub fn page_size_too_large(size: u64, limit: u64) -> Self {
+        Self {
+            code: ServerCode::PageSizeTooLarge as i16,
+            message: format!(
+                "Server error: page size {} is larger than the limit of {}",
+                size, limit
+            ),
+            data: Some(serde_json::json!({ "limit": limit })),
+        }
+    }
+
+    pub fn rate_limited() -> Self {
+        Self {
+            code: ServerCode::RateLimited as i16,
+            message: "Server error: too many calls, retry later".to_string(),
+            data: None,
+        }
+    }
+
+    pub fn too_many_subscriptions(limit: usize) -> Self {
+        Self {
+            code: ServerCode::TooManySubscriptions as i16,
+            message: format!(
+                "Server error: connection already has the max of {} subscriptions",
+                limit
+            ),
+            data: Some(serde_json::json!({ "limit": limit })),
+        }
+    }
+
     pub fn mempool_error(error: MempoolStatus) -> Result<Self> {
         let code = match error.code {
             MempoolStatusCode::InvalidSeqNumber => ServerCode::MempoolInvalidSeqNumber,
@@ -91,6 +171,7 @@ impl JsonRpcError {
             MempoolStatusCode::InvalidUpdate => ServerCode::MempoolInvalidUpdate,
             MempoolStatusCode::VmError => ServerCode::MempoolVmError,
             MempoolStatusCode::UnknownStatus => ServerCode::MempoolUnknownError,
+            MempoolStatusCode::RejectedByPolicy => ServerCode::MempoolRejectedByPolicy,
             MempoolStatusCode::Accepted => {
                 return Err(anyhow::format_err!(
                     "[JSON RPC] cannot create mempool error for mempool accepted status"
@@ -10,12 +10,16 @@ use libra_types::{
         ReceivedPaymentEvent, SentPaymentEvent, UpgradeEvent, LBR_NAME,
     },
     account_state_blob::AccountStateWithProof,
-    contract_event::ContractEvent,
+    contract_event::{ContractEvent, EventWithProof},
     epoch_change::EpochChangeProof,
     ledger_info::LedgerInfoWithSignatures,
-    proof::{AccountStateProof, AccumulatorConsistencyProof},
-    transaction::{Transaction, TransactionArgument, TransactionPayload},
+    proof::{AccountStateProof, AccumulatorConsistencyProof, TransactionInfoWithProof},
+    transaction::{
+        Transaction, TransactionArgument, TransactionListWithProof, TransactionOutput,
+        TransactionPayload, TransactionWithProof,
+    },
     vm_error::StatusCode,
+    write_set::WriteOp,
 };
 use move_core_types::{
     identifier::Identifier,
@@ -336,6 +340,18 @@ pub enum TransactionDataView {
     UnknownTransaction {},
 }
 
+/// A transaction looked up by hash, which is either committed or still pending in mempool.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(tag = "status")]
+pub enum TransactionByHashView {
+    #[serde(rename = "committed")]
+    Committed { transaction: TransactionView },
+    /// Not executed yet, hence only the transaction data.
+    #[serde(rename = "pending")]
+    Pending { transaction: TransactionDataView },
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 #[serde(tag = "type")]
 // TODO cover all script types
@@ -490,6 +506,156 @@ impl From<TransactionPayload> for ScriptView {
     }
 }
 
+/// What executing a transaction would do, as returned by simulating it.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct TransactionOutputView {
+    pub vm_status: StatusCode,
+    pub vm_sub_status: Option<u64>,
+    /// A discarded transaction would not be included in the ledger at all.
+    pub discarded: bool,
+    pub gas_used: u64,
+    pub write_set: Vec<WriteOpView>,
+    pub events: Vec<EventView>,
+}
+
+impl From<(u64, TransactionOutput)> for TransactionOutputView {
+    /// `txn_version` is the version the transaction would have been committed at, given to the
+    /// events.
+    fn from((txn_version, output): (u64, TransactionOutput)) -> TransactionOutputView {
+        let vm_status = output.status().vm_status();
+        TransactionOutputView {
+            vm_status: vm_status.major_status,
+            vm_sub_status: vm_status.sub_status,
+            discarded: output.status().is_discarded(),
+            gas_used: output.gas_used(),
+            write_set: output
+                .write_set()
+                .iter()
+                .map(|(access_path, write_op)| WriteOpView {
+                    address: BytesView::from(access_path.address.as_ref()),
+                    path: BytesView::from(&access_path.path),
+                    value: match write_op {
+                        WriteOp::Value(value) => Some(BytesView::from(value)),
+                        WriteOp::Deletion => None,
+                    },
+                })
+                .collect(),
+            events: output
+                .events()
+                .iter()
+                .map(|event| EventView::from((txn_version, event.clone())))
+                .collect(),
+        }
+    }
+}
+
+/// A resource under an account.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct AccountResourceView {
+    /// Access path of the resource under the account.
+    pub path: BytesView,
+    /// LCS bytes of the resource.
+    pub data: BytesView,
+    /// The decoded resource, `None` if the type of the resource isn't known to the server.
+    pub value: Option<MoveStructView>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct MoveStructView {
+    /// Address of the module declaring the struct.
+    pub address: String,
+    pub module: String,
+    pub name: String,
+    pub type_params: Vec<String>,
+    pub is_resource: bool,
+    /// In the order of declaration.
+    pub fields: Vec<MoveFieldView>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct MoveFieldView {
+    pub name: String,
+    pub value: MoveValueView,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(tag = "type", content = "value")]
+pub enum MoveValueView {
+    #[serde(rename = "u8")]
+    U8(u8),
+    #[serde(rename = "u64")]
+    U64(u64),
+    /// As a decimal string, since JSON numbers can't hold all u128 values.
+    #[serde(rename = "u128")]
+    U128(String),
+    #[serde(rename = "bool")]
+    Bool(bool),
+    #[serde(rename = "address")]
+    Address(String),
+    #[serde(rename = "vector")]
+    Vector(Vec<MoveValueView>),
+    /// A `vector<u8>`.
+    #[serde(rename = "bytes")]
+    Bytes(BytesView),
+    #[serde(rename = "struct")]
+    Struct(MoveStructView),
+}
+
+/// Where a transaction is in mempool, or why it was removed recently.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(tag = "status")]
+pub enum MempoolTransactionStatusView {
+    /// Ready for the next block.
+    #[serde(rename = "ready")]
+    Ready {
+        /// In seconds since the Unix epoch.
+        expiration_time: u64,
+    },
+    /// Waiting for transactions of lower sequence numbers from the same sender.
+    #[serde(rename = "parked")]
+    Parked {
+        /// In seconds since the Unix epoch.
+        expiration_time: u64,
+    },
+    /// No longer in mempool.
+    #[serde(rename = "removed")]
+    Removed {
+        /// One of "committed", "rejected", "expired", "timed_out", "evicted" and "replaced".
+        reason: String,
+    },
+    /// Not accepted into mempool when submitted.
+    #[serde(rename = "not_accepted")]
+    NotAccepted {
+        /// One of "invalid_seq_number", "mempool_is_full", "too_many_transactions",
+        /// "invalid_update", "vm_error", "rejected_by_policy" and "unknown".
+        reason: String,
+        /// The status code of the failed VM validation, for "vm_error".
+        vm_status: Option<StatusCode>,
+    },
+}
+
+/// Gas amount and unit price suggested for a transaction.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct GasEstimateView {
+    /// Status of the simulated execution the estimate is based on.
+    pub vm_status: StatusCode,
+    /// Gas used by the simulated execution.
+    pub gas_used: u64,
+    /// Suggested `max_gas_amount`, the gas used with a safety margin.
+    pub max_gas_amount: u64,
+    /// Suggested `gas_unit_price`, in `gas_currency_code`.
+    pub gas_unit_price: u64,
+    pub gas_currency_code: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct WriteOpView {
+    pub address: BytesView,
+    pub path: BytesView,
+    /// `None` if the write is a deletion.
+    pub value: Option<BytesView>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct CurrencyInfoView {
     pub code: String,
@@ -540,6 +706,180 @@ impl
     }
 }
 
+impl TryFrom<&StateProofView>
+    for (
+        LedgerInfoWithSignatures,
+        EpochChangeProof,
+        AccumulatorConsistencyProof,
+    )
+{
+    type Error = Error;
+
+    fn try_from(
+        state_proof_view: &StateProofView,
+    ) -> Result<
+        (
+            LedgerInfoWithSignatures,
+            EpochChangeProof,
+            AccumulatorConsistencyProof,
+        ),
+        Self::Error,
+    > {
+        Ok((
+            lcs::from_bytes(
+                &state_proof_view
+                    .ledger_info_with_signatures
+                    .clone()
+                    .into_bytes()?,
+            )?,
+            lcs::from_bytes(&state_proof_view.epoch_change_proof.clone().into_bytes()?)?,
+            lcs::from_bytes(
+                &state_proof_view
+                    .ledger_consistency_proof
+                    .clone()
+                    .into_bytes()?,
+            )?,
+        ))
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TransactionListWithProofView {
+    pub ledger_info_with_signatures: BytesView,
+    pub transaction_list_with_proof: BytesView,
+}
+
+impl TryFrom<(LedgerInfoWithSignatures, TransactionListWithProof)>
+    for TransactionListWithProofView
+{
+    type Error = Error;
+
+    fn try_from(
+        (ledger_info_with_signatures, transaction_list_with_proof): (
+            LedgerInfoWithSignatures,
+            TransactionListWithProof,
+        ),
+    ) -> Result<TransactionListWithProofView, Self::Error> {
+        Ok(TransactionListWithProofView {
+            ledger_info_with_signatures: BytesView::from(&lcs::to_bytes(
+                &ledger_info_with_signatures,
+            )?),
+            transaction_list_with_proof: BytesView::from(&lcs::to_bytes(
+                &transaction_list_with_proof,
+            )?),
+        })
+    }
+}
+
+impl TryFrom<&TransactionListWithProofView>
+    for (LedgerInfoWithSignatures, TransactionListWithProof)
+{
+    type Error = Error;
+
+    fn try_from(
+        view: &TransactionListWithProofView,
+    ) -> Result<(LedgerInfoWithSignatures, TransactionListWithProof), Self::Error> {
+        Ok((
+            lcs::from_bytes(&view.ledger_info_with_signatures.clone().into_bytes()?)?,
+            lcs::from_bytes(&view.transaction_list_with_proof.clone().into_bytes()?)?,
+        ))
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct AccountTransactionWithProofView {
+    pub ledger_info_with_signatures: BytesView,
+    pub transaction_with_proof: Option<BytesView>,
+}
+
+impl TryFrom<(LedgerInfoWithSignatures, Option<TransactionWithProof>)>
+    for AccountTransactionWithProofView
+{
+    type Error = Error;
+
+    fn try_from(
+        (ledger_info_with_signatures, transaction_with_proof): (
+            LedgerInfoWithSignatures,
+            Option<TransactionWithProof>,
+        ),
+    ) -> Result<AccountTransactionWithProofView, Self::Error> {
+        let transaction_with_proof = if let Some(txn) = transaction_with_proof {
+            Some(BytesView::from(&lcs::to_bytes(&txn)?))
+        } else {
+            None
+        };
+        Ok(AccountTransactionWithProofView {
+            ledger_info_with_signatures: BytesView::from(&lcs::to_bytes(
+                &ledger_info_with_signatures,
+            )?),
+            transaction_with_proof,
+        })
+    }
+}
+
+impl TryFrom<&AccountTransactionWithProofView>
+    for (LedgerInfoWithSignatures, Option<TransactionWithProof>)
+{
+    type Error = Error;
+
+    fn try_from(
+        view: &AccountTransactionWithProofView,
+    ) -> Result<(LedgerInfoWithSignatures, Option<TransactionWithProof>), Self::Error> {
+        let transaction_with_proof = if let Some(txn) = &view.transaction_with_proof {
+            Some(lcs::from_bytes(&txn.clone().into_bytes()?)?)
+        } else {
+            None
+        };
+        Ok((
+            lcs::from_bytes(&view.ledger_info_with_signatures.clone().into_bytes()?)?,
+            transaction_with_proof,
+        ))
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct EventsWithProofView {
+    pub ledger_info_with_signatures: BytesView,
+    pub events_with_proof: Vec<BytesView>,
+}
+
+impl TryFrom<(LedgerInfoWithSignatures, Vec<EventWithProof>)> for EventsWithProofView {
+    type Error = Error;
+
+    fn try_from(
+        (ledger_info_with_signatures, events_with_proof): (
+            LedgerInfoWithSignatures,
+            Vec<EventWithProof>,
+        ),
+    ) -> Result<EventsWithProofView, Self::Error> {
+        Ok(EventsWithProofView {
+            ledger_info_with_signatures: BytesView::from(&lcs::to_bytes(
+                &ledger_info_with_signatures,
+            )?),
+            events_with_proof: events_with_proof
+                .iter()
+                .map(|event| Ok(BytesView::from(&lcs::to_bytes(event)?)))
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+impl TryFrom<&EventsWithProofView> for (LedgerInfoWithSignatures, Vec<EventWithProof>) {
+    type Error = Error;
+
+    fn try_from(
+        view: &EventsWithProofView,
+    ) -> Result<(LedgerInfoWithSignatures, Vec<EventWithProof>), Self::Error> {
+        Ok((
+            lcs::from_bytes(&view.ledger_info_with_signatures.clone().into_bytes()?)?,
+            view.events_with_proof
+                .iter()
+                .map(|event| Ok(lcs::from_bytes(&event.clone().into_bytes()?)?))
+                .collect::<Result<_>>()?,
+        ))
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct AccountStateWithProofView {
     pub version: u64,
@@ -566,6 +906,25 @@ impl TryFrom<AccountStateWithProof> for AccountStateWithProofView {
     }
 }
 
+impl TryFrom<&AccountStateWithProofView> for AccountStateWithProof {
+    type Error = Error;
+
+    fn try_from(
+        account_state_with_proof_view: &AccountStateWithProofView,
+    ) -> Result<AccountStateWithProof, Error> {
+        let blob = if let Some(account_blob) = &account_state_with_proof_view.blob {
+            Some(lcs::from_bytes(&account_blob.clone().into_bytes()?)?)
+        } else {
+            None
+        };
+        Ok(AccountStateWithProof::new(
+            account_state_with_proof_view.version,
+            blob,
+            AccountStateProof::try_from(&account_state_with_proof_view.proof)?,
+        ))
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct AccountStateProofView {
     pub ledger_info_to_transaction_info_proof: BytesView,
@@ -594,3 +953,34 @@ impl TryFrom<AccountStateProof> for AccountStateProofView {
         })
     }
 }
+
+impl TryFrom<&AccountStateProofView> for AccountStateProof {
+    type Error = Error;
+
+    fn try_from(
+        account_state_proof_view: &AccountStateProofView,
+    ) -> Result<AccountStateProof, Error> {
+        Ok(AccountStateProof::new(
+            TransactionInfoWithProof::new(
+                lcs::from_bytes(
+                    &account_state_proof_view
+                        .ledger_info_to_transaction_info_proof
+                        .clone()
+                        .into_bytes()?,
+                )?,
+                lcs::from_bytes(
+                    &account_state_proof_view
+                        .transaction_info
+                        .clone()
+                        .into_bytes()?,
+                )?,
+            ),
+            lcs::from_bytes(
+                &account_state_proof_view
+                    .transaction_info_to_account_proof
+                    .clone()
+                    .into_bytes()?,
+            )?,
+        ))
+    }
+}
@@ -108,6 +108,28 @@ impl LibraVM {
         })
     }
 
+    /// Executes a user transaction against `state_view` the same way it would be executed in a
+    /// block, but only returns the output, which is never meant to be committed. The signature
+    /// check is skipped if `skip_signature_check` is set, so that unsigned transactions can be
+    /// simulated too.
+    pub fn simulate_transaction(
+        &mut self,
+        txn: SignedTransaction,
+        skip_signature_check: bool,
+        state_view: &dyn StateView,
+    ) -> TransactionOutput {
+        let mut data_cache = StateViewCache::new(state_view);
+        self.load_configs_impl(&data_cache);
+
+        if skip_signature_check {
+            return self.execute_unchecked_transaction(&mut data_cache, &txn);
+        }
+        match txn.check_signature() {
+            Ok(txn) => self.execute_user_transaction(state_view, &mut data_cache, &txn),
+            Err(_) => discard_error_output(VMStatus::new(StatusCode::INVALID_SIGNATURE)),
+        }
+    }
+
     fn check_gas(&self, txn: &SignedTransaction) -> VMResult<()> {
         let gas_constants = &self.get_gas_schedule()?.gas_constants;
         let raw_bytes_len = AbstractMemorySize::new(txn.raw_txn_bytes_len() as GasCarrier);
@@ -459,6 +481,47 @@ impl LibraVM {
         result
     }
 
+    /// Executes `txn` like `execute_user_transaction`, but without its signature being checked.
+    /// Only used by `simulate_transaction`, the outputs of which are never committed
+    fn execute_unchecked_transaction(
+        &mut self,
+        remote_cache: &mut StateViewCache<'_>,
+        txn: &SignedTransaction,
+    ) -> TransactionOutput {
+        let txn_data = TransactionMetadata::new(txn);
+        let account_currency_symbol =
+            match account_config::from_currency_code_string(txn.gas_currency_code()) {
+                Ok(account_currency_symbol) => account_currency_symbol,
+                Err(_) => {
+                    return discard_error_output(VMStatus::new(StatusCode::INVALID_GAS_SPECIFIER))
+                }
+            };
+        let verified_payload = self.check_gas(txn).and_then(|_| match txn.payload() {
+            TransactionPayload::Script(script) => self.verify_script(
+                remote_cache,
+                script,
+                TransactionMetadata::new(txn),
+                &account_currency_symbol,
+            ),
+            TransactionPayload::Module(module) => self.verify_module(
+                remote_cache,
+                module,
+                TransactionMetadata::new(txn),
+                &account_currency_symbol,
+            ),
+            TransactionPayload::WriteSet(_) => Err(VMStatus::new(StatusCode::UNREACHABLE)),
+        });
+        match verified_payload {
+            Ok(verified_payload) => self.execute_verified_payload(
+                remote_cache,
+                &txn_data,
+                verified_payload,
+                &account_currency_symbol,
+            ),
+            Err(e) => discard_error_output(e),
+        }
+    }
+
     fn read_writeset(
         &self,
         remote_cache: &StateViewCache<'_>,
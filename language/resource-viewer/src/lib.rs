@@ -10,7 +10,7 @@ use libra_types::{
 };
 use move_core_types::{
     identifier::Identifier,
-    language_storage::StructTag,
+    language_storage::{StructTag, CODE_TAG, RESOURCE_TAG},
     value::{MoveStruct, MoveValue},
 };
 use std::{
@@ -21,6 +21,7 @@ use std::{
 
 pub use cached_access_path_table::update_mapping;
 use move_vm_types::loaded_data::types::{FatStructType, FatType};
+use vm::{access::ModuleAccess, CompiledModule};
 
 mod cached_access_path_table;
 mod module_cache;
@@ -31,9 +32,9 @@ pub struct AnnotatedAccountStateBlob(BTreeMap<StructTag, AnnotatedMoveStruct>);
 
 #[derive(Debug)]
 pub struct AnnotatedMoveStruct {
-    is_resource: bool,
-    type_: StructTag,
-    value: Vec<(Identifier, AnnotatedMoveValue)>,
+    pub is_resource: bool,
+    pub type_: StructTag,
+    pub value: Vec<(Identifier, AnnotatedMoveValue)>,
 }
 
 /// AnnotatedMoveValue is a fully expanded version of on chain move data. This should only be used
@@ -71,9 +72,14 @@ impl<'a> MoveValueAnnotator<'a> {
         blob: &[u8],
     ) -> Result<AnnotatedMoveStruct> {
         let ty = resource_vec_to_type_tag(&access_path.path)?;
-        let struct_def = (&ty).try_into()?;
-        let move_struct = MoveStruct::simple_deserialize(blob, &struct_def)?;
-        self.annotate_struct(&move_struct, &ty)
+        self.view_struct(blob, &ty)
+    }
+
+    /// Views a resource of any type, loading the module declaring it from the state view if it's
+    /// not in the standard library.
+    pub fn view_resource(&self, tag: &StructTag, blob: &[u8]) -> Result<AnnotatedMoveStruct> {
+        let ty = self.cache.resolve_struct(tag)?;
+        self.view_struct(blob, &ty)
     }
 
     pub fn view_contract_event(&self, event: &ContractEvent) -> Result<AnnotatedMoveValue> {
@@ -83,17 +89,71 @@ impl<'a> MoveValueAnnotator<'a> {
         self.annotate_value(&move_value, &ty)
     }
 
+    /// Views the resources of an account one by one, so that one failing to be viewed doesn't
+    /// fail the others. Each is returned along with its path.
+    ///
+    /// The type of a resource can't be recovered from its path, which only has the hash of the
+    /// type. Known are the types in the genesis type mapping, `type_tags`, which can be of modules
+    /// published under any account, and the non-generic resources declared by modules published
+    /// under the account.
+    pub fn view_account_resources(
+        &self,
+        state: &AccountState,
+        type_tags: &[StructTag],
+    ) -> Vec<(Vec<u8>, Result<AnnotatedMoveStruct>)> {
+        let mut known_types: BTreeMap<_, _> = type_tags
+            .iter()
+            .map(|tag| (tag.access_vector(), tag.clone()))
+            .collect();
+        for (k, v) in state.iter() {
+            if k.first() != Some(&CODE_TAG) {
+                continue;
+            }
+            // A module which fails to deserialize just leaves its resources unknown.
+            if let Ok(module) = CompiledModule::deserialize(v) {
+                for struct_def in module.struct_defs() {
+                    let handle = module.struct_handle_at(struct_def.struct_handle);
+                    if handle.is_nominal_resource && handle.type_parameters.is_empty() {
+                        let tag = StructTag {
+                            address: *module.address(),
+                            module: module.name().to_owned(),
+                            name: module.identifier_at(handle.name).to_owned(),
+                            type_params: vec![],
+                        };
+                        known_types.entry(tag.access_vector()).or_insert(tag);
+                    }
+                }
+            }
+        }
+
+        state
+            .iter()
+            .filter(|(k, _)| k.first() == Some(&RESOURCE_TAG))
+            .map(|(k, v)| {
+                let annotated = match known_types.get(k) {
+                    Some(tag) => self.view_resource(tag, v),
+                    None => resource_vec_to_type_tag(k).and_then(|ty| self.view_struct(v, &ty)),
+                };
+                (k.clone(), annotated)
+            })
+            .collect()
+    }
+
     pub fn view_account_state(&self, state: &AccountState) -> Result<AnnotatedAccountStateBlob> {
         let mut output = BTreeMap::new();
         for (k, v) in state.iter() {
             let ty = resource_vec_to_type_tag(k.as_slice())?;
-            let struct_def = (&ty).try_into()?;
-            let move_struct = MoveStruct::simple_deserialize(v.as_slice(), &struct_def)?;
-            output.insert(ty.struct_tag()?, self.annotate_struct(&move_struct, &ty)?);
+            output.insert(ty.struct_tag()?, self.view_struct(v, &ty)?);
         }
         Ok(AnnotatedAccountStateBlob(output))
     }
 
+    fn view_struct(&self, blob: &[u8], ty: &FatStructType) -> Result<AnnotatedMoveStruct> {
+        let struct_def = ty.try_into()?;
+        let move_struct = MoveStruct::simple_deserialize(blob, &struct_def)?;
+        self.annotate_struct(&move_struct, ty)
+    }
+
     fn annotate_struct(
         &self,
         move_struct: &MoveStruct,
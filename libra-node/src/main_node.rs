@@ -167,6 +167,8 @@ pub fn setup_environment(node_config: &mut NodeConfig) -> LibraHandle {
             &node_config.storage.dir(),
             false, /* readonly */
             node_config.storage.prune_window,
+            node_config.storage.ledger_prune_window,
+            node_config.storage.account_state_history,
         )
         .expect("DB should open."),
     );
@@ -305,6 +307,7 @@ pub fn setup_environment(node_config: &mut NodeConfig) -> LibraHandle {
         consensus_requests,
         state_sync_requests,
         mempool_reconfig_events,
+        None,
     );
     debug!("Mempool started in {} ms", instant.elapsed().as_millis());
 
@@ -62,6 +62,11 @@ impl PriorityIndex {
         self.data.iter().rev()
     }
 
+    /// returns the lowest priority transaction in the queue
+    pub(crate) fn lowest(&self) -> Option<&OrderedQueueKey> {
+        self.data.iter().next()
+    }
+
     pub(crate) fn size(&self) -> usize {
         self.data.len()
     }
@@ -269,7 +274,7 @@ impl ParkingLotIndex {
     }
 
     /// returns random "non-ready" transaction (with highest sequence number for that account)
-    pub(crate) fn pop(&mut self) -> Option<TxnPointer> {
+    pub(crate) fn pop(&self) -> Option<TxnPointer> {
         self.data.iter().rev().next().cloned()
     }
 
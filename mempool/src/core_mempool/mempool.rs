@@ -6,7 +6,7 @@
 use crate::{
     core_mempool::{
         index::TxnPointer,
-        transaction::{MempoolTransaction, TimelineState},
+        transaction::{MempoolTransaction, MempoolTransactionStatus, TimelineState},
         transaction_store::TransactionStore,
         ttl_cache::TtlCache,
     },
@@ -14,11 +14,13 @@ use crate::{
 };
 use debug_interface::prelude::*;
 use libra_config::config::NodeConfig;
+use libra_crypto::HashValue;
 use libra_logger::prelude::*;
 use libra_types::{
     account_address::AccountAddress,
     mempool_status::{MempoolStatus, MempoolStatusCode},
     transaction::SignedTransaction,
+    vm_error::StatusCode,
 };
 use std::{
     cmp::max,
@@ -126,6 +128,12 @@ impl Mempool {
 
         // don't accept old transactions (e.g. seq is less than account's current seq_number)
         if txn.sequence_number() < sequence_number {
+            self.record_rejection(
+                txn.sender(),
+                txn.sequence_number(),
+                MempoolStatusCode::InvalidSeqNumber,
+                None,
+            );
             return MempoolStatus::new(MempoolStatusCode::InvalidSeqNumber).with_message(format!(
                 "transaction sequence number is {}, current sequence number is  {}",
                 txn.sequence_number(),
@@ -142,6 +150,7 @@ impl Mempool {
                 .insert((txn.sender(), txn.sequence_number()), SystemTime::now());
         }
 
+        let (sender, txn_sequence_number) = (txn.sender(), txn.sequence_number());
         let txn_info = MempoolTransaction::new(
             txn,
             expiration_time,
@@ -153,9 +162,25 @@ impl Mempool {
 
         let status = self.transactions.insert(txn_info, sequence_number);
         OP_COUNTERS.inc(&format!("insert.{:?}", status));
+        if status.code != MempoolStatusCode::Accepted {
+            self.record_rejection(sender, txn_sequence_number, status.code, None);
+        }
         status
     }
 
+    /// Records that a transaction wasn't accepted when submitted, e.g. since it failed VM
+    /// validation, so that clients can find out via `get_transaction_status`
+    pub(crate) fn record_rejection(
+        &mut self,
+        sender: AccountAddress,
+        sequence_number: u64,
+        code: MempoolStatusCode,
+        vm_status: Option<StatusCode>,
+    ) {
+        self.transactions
+            .record_rejection(sender, sequence_number, code, vm_status);
+    }
+
     /// Fetches next block of transactions for consensus
     /// `batch_size` - size of requested block
     /// `seen_txns` - transactions that were sent to Consensus but were not committed yet
@@ -229,6 +254,29 @@ impl Mempool {
         block
     }
 
+    /// Fetches a transaction by the hash it is committed with, i.e. that of
+    /// `Transaction::UserTransaction`
+    pub(crate) fn get_by_hash(&self, hash: &HashValue) -> Option<SignedTransaction> {
+        self.transactions.get_by_hash(hash)
+    }
+
+    /// Returns all transactions in Mempool along with their timeline state
+    pub(crate) fn get_all_transactions(&self) -> Vec<(SignedTransaction, TimelineState)> {
+        self.transactions
+            .iter_all()
+            .map(|txn| (txn.txn.clone(), txn.timeline_state))
+            .collect()
+    }
+
+    /// Reports where a transaction is in Mempool, or why it was removed or not accepted recently
+    pub(crate) fn get_transaction_status(
+        &self,
+        sender: &AccountAddress,
+        sequence_number: u64,
+    ) -> Option<MempoolTransactionStatus> {
+        self.transactions.get_status(sender, sequence_number)
+    }
+
     /// periodic core mempool garbage collection
     /// removes all expired transactions
     /// clears expired entries in metrics cache and sequence number cache
@@ -239,6 +287,11 @@ impl Mempool {
         self.sequence_number_cache.gc(now);
     }
 
+    /// number of transactions in Mempool
+    pub(crate) fn size(&self) -> usize {
+        self.transactions.size()
+    }
+
     /// Garbage collection based on client-specified expiration time
     pub(crate) fn gc_by_expiration_time(&mut self, block_time: Duration) {
         self.transactions.gc_by_expiration_time(block_time);
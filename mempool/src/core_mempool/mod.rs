@@ -9,4 +9,8 @@ mod ttl_cache;
 
 #[cfg(test)]
 pub use self::ttl_cache::TtlCache;
-pub use self::{index::TxnPointer, mempool::Mempool as CoreMempool, transaction::TimelineState};
+pub use self::{
+    index::TxnPointer,
+    mempool::Mempool as CoreMempool,
+    transaction::{MempoolTransactionStatus, RemovalReason, TimelineState},
+};
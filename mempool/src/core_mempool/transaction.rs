@@ -1,7 +1,10 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use libra_types::{account_address::AccountAddress, transaction::SignedTransaction};
+use libra_types::{
+    account_address::AccountAddress, mempool_status::MempoolStatusCode,
+    transaction::SignedTransaction, vm_error::StatusCode,
+};
 use std::time::Duration;
 
 #[derive(Clone)]
@@ -56,3 +59,45 @@ pub enum TimelineState {
     // currently we don't broadcast transactions originated on other peers
     NonQualified,
 }
+
+/// Where a transaction is in mempool, or why it left or never entered, as reported to clients
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MempoolTransactionStatus {
+    /// ready for the next block, i.e. in PriorityIndex
+    Ready {
+        /// the earlier of the client-specified and the system expiration time
+        expiration_time: Duration,
+    },
+    /// not ready for the next block, since transactions of lower sequence numbers from the same
+    /// sender are missing. Such transactions are tracked by ParkingLotIndex, from which they are
+    /// evicted first when mempool is full
+    Parked {
+        /// the earlier of the client-specified and the system expiration time
+        expiration_time: Duration,
+    },
+    /// removed from mempool recently
+    Removed(RemovalReason),
+    /// not accepted into mempool when submitted recently
+    NotAccepted {
+        code: MempoolStatusCode,
+        /// why VM validation failed, if it did
+        vm_status: Option<StatusCode>,
+    },
+}
+
+/// Why a transaction was removed from mempool
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RemovalReason {
+    /// committed, or a transaction with the same sequence number from the same sender was
+    Committed,
+    /// rejected by Consensus or execution, or a transaction before it from the same sender was
+    Rejected,
+    /// the client-specified expiration time passed
+    Expired,
+    /// it stayed in mempool for longer than the system TTL
+    TimedOut,
+    /// evicted to make room for a ready transaction with higher priority, since mempool was full
+    Evicted,
+    /// replaced by a transaction with the same sequence number and a higher gas price
+    Replaced,
+}
@@ -5,21 +5,25 @@ use crate::{
     core_mempool::{
         index::{
             AccountTransactions, ParkingLotIndex, PriorityIndex, PriorityQueueIter, TTLIndex,
-            TimelineIndex,
+            TimelineIndex, TxnPointer,
         },
-        transaction::{MempoolTransaction, TimelineState},
+        transaction::{MempoolTransaction, MempoolTransactionStatus, RemovalReason, TimelineState},
+        ttl_cache::TtlCache,
     },
     OP_COUNTERS,
 };
-use anyhow::{format_err, Result};
+use anyhow::{ensure, Result};
 use libra_config::config::MempoolConfig;
+use libra_crypto::HashValue;
 use libra_logger::prelude::*;
 use libra_types::{
     account_address::AccountAddress,
     mempool_status::{MempoolStatus, MempoolStatusCode},
     transaction::SignedTransaction,
+    vm_error::StatusCode,
 };
 use std::{
+    cmp::{max, min},
     collections::HashMap,
     ops::Bound,
     time::{Duration, SystemTime, UNIX_EPOCH},
@@ -41,10 +45,16 @@ pub struct TransactionStore {
     timeline_index: TimelineIndex,
     // keeps track of "non-ready" txns (transactions that can't be included in next block)
     parking_lot_index: ParkingLotIndex,
+    // transactions by the hash they are committed with, see `SignedTransaction::committed_hash`
+    hash_index: HashMap<HashValue, TxnPointer>,
+    // why transactions were removed or not accepted, kept for the system TTL so that clients can
+    // find out
+    removal_reasons: TtlCache<TxnPointer, MempoolTransactionStatus>,
 
     // configuration
     capacity: usize,
     capacity_per_user: usize,
+    replace_by_fee_min_bump_percent: u64,
 }
 
 impl TransactionStore {
@@ -61,10 +71,16 @@ impl TransactionStore {
             priority_index: PriorityIndex::new(),
             timeline_index: TimelineIndex::new(),
             parking_lot_index: ParkingLotIndex::new(),
+            hash_index: HashMap::new(),
+            removal_reasons: TtlCache::new(
+                config.capacity,
+                Duration::from_secs(config.system_transaction_timeout_secs),
+            ),
 
             // configuration
             capacity: config.capacity,
             capacity_per_user: config.capacity_per_user,
+            replace_by_fee_min_bump_percent: config.replace_by_fee_min_bump_percent,
         }
     }
 
@@ -84,6 +100,61 @@ impl TransactionStore {
         None
     }
 
+    /// fetch transaction by the hash it is committed with
+    pub(crate) fn get_by_hash(&self, hash: &HashValue) -> Option<SignedTransaction> {
+        self.hash_index
+            .get(hash)
+            .and_then(|(address, sequence_number)| self.get(address, *sequence_number))
+    }
+
+    /// reports where a transaction is in mempool, or why it was removed or not accepted recently
+    pub(crate) fn get_status(
+        &self,
+        address: &AccountAddress,
+        sequence_number: u64,
+    ) -> Option<MempoolTransactionStatus> {
+        if let Some(txn) = self
+            .transactions
+            .get(&address)
+            .and_then(|txns| txns.get(&sequence_number))
+        {
+            let expiration_time = min(txn.expiration_time, txn.txn.expiration_time());
+            return Some(if self.priority_index.contains(txn) {
+                MempoolTransactionStatus::Ready { expiration_time }
+            } else {
+                MempoolTransactionStatus::Parked { expiration_time }
+            });
+        }
+        self.removal_reasons
+            .get(&(*address, sequence_number))
+            .copied()
+    }
+
+    /// records that a transaction wasn't accepted when submitted, unless it's in mempool or was
+    /// removed from it recently, which is what's reported for it then
+    pub(crate) fn record_rejection(
+        &mut self,
+        address: AccountAddress,
+        sequence_number: u64,
+        code: MempoolStatusCode,
+        vm_status: Option<StatusCode>,
+    ) {
+        let in_mempool = self
+            .transactions
+            .get(&address)
+            .map_or(false, |txns| txns.contains_key(&sequence_number));
+        let removed = matches!(
+            self.removal_reasons.get(&(address, sequence_number)),
+            Some(MempoolTransactionStatus::Removed(_))
+        );
+        if !in_mempool && !removed {
+            self.removal_reasons.insert(
+                (address, sequence_number),
+                MempoolTransactionStatus::NotAccepted { code, vm_status },
+            );
+        }
+    }
+
     /// insert transaction into TransactionStore
     /// performs validation checks and updates indexes
     pub(crate) fn insert(
@@ -91,48 +162,92 @@ impl TransactionStore {
         txn: MempoolTransaction,
         current_sequence_number: u64,
     ) -> MempoolStatus {
-        if self.handle_gas_price_update(&txn).is_err() {
-            return MempoolStatus::new(MempoolStatusCode::InvalidUpdate).with_message(format!(
-                "Failed to update gas price to {}",
-                txn.get_gas_price()
-            ));
-        }
-
-        if self.check_if_full(&txn, current_sequence_number) {
-            return MempoolStatus::new(MempoolStatusCode::MempoolIsFull).with_message(format!(
-                "mempool size: {}, capacity: {}",
-                self.system_ttl_index.size(),
-                self.capacity,
-            ));
-        }
-
         let address = txn.get_sender();
         let sequence_number = txn.get_sequence_number();
 
-        self.transactions
-            .entry(address)
-            .or_insert_with(AccountTransactions::new);
+        // a transaction with the same sequence number is replaced only if the new one pays more
+        let is_replacement = match self
+            .transactions
+            .get(&address)
+            .and_then(|txns| txns.get(&sequence_number))
+        {
+            Some(current_version) => {
+                if let Err(e) = self.check_replacement(current_version, &txn) {
+                    return MempoolStatus::new(MempoolStatusCode::InvalidUpdate)
+                        .with_message(e.to_string());
+                }
+                true
+            }
+            None => false,
+        };
 
+        // all checks happen before Mempool is modified, so a rejected transaction never evicts
+        // or replaces anything
+        let num_txns = self.transactions.get(&address).map_or(0, |txns| {
+            txns.range(current_sequence_number..)
+                .filter(|(seq, _)| **seq != sequence_number)
+                .count()
+        });
+        if num_txns >= self.capacity_per_user {
+            return MempoolStatus::new(MempoolStatusCode::TooManyTransactions).with_message(
+                format!(
+                    "txns length: {} capacity per user: {}",
+                    num_txns, self.capacity_per_user,
+                ),
+            );
+        }
+
+        // a replacement doesn't take up more space
+        let eviction = if !is_replacement && self.system_ttl_index.size() >= self.capacity {
+            match self.find_eviction_candidate(&txn, current_sequence_number) {
+                Some(eviction) => Some(eviction),
+                None => {
+                    return MempoolStatus::new(MempoolStatusCode::MempoolIsFull).with_message(
+                        format!(
+                            "mempool size: {}, capacity: {}",
+                            self.system_ttl_index.size(),
+                            self.capacity,
+                        ),
+                    );
+                }
+            }
+        } else {
+            None
+        };
+
+        // nothing can fail from here on, so the old version is never removed without the new one
+        // taking its place in all indexes
         self.clean_committed_transactions(&address, current_sequence_number);
 
-        if let Some(txns) = self.transactions.get_mut(&address) {
-            // capacity check
-            if txns.len() >= self.capacity_per_user {
-                return MempoolStatus::new(MempoolStatusCode::TooManyTransactions).with_message(
-                    format!(
-                        "txns length: {} capacity per user: {}",
-                        txns.len(),
-                        self.capacity_per_user,
-                    ),
-                );
+        if let Some((evicted_address, evicted_sequence_number)) = eviction {
+            if let Some(evicted) = self
+                .transactions
+                .get_mut(&evicted_address)
+                .and_then(|txns| txns.remove(&evicted_sequence_number))
+            {
+                self.index_remove(&evicted, RemovalReason::Evicted);
             }
+        }
 
-            // insert into storage and other indexes
-            self.system_ttl_index.insert(&txn);
-            self.expiration_time_index.insert(&txn);
-            txns.insert(sequence_number, txn);
-            self.track_indices();
+        let txns = self
+            .transactions
+            .entry(address)
+            .or_insert_with(AccountTransactions::new);
+        if let Some(current_version) = txns.remove(&sequence_number) {
+            self.index_remove(&current_version, RemovalReason::Replaced);
         }
+
+        // insert into storage and other indexes
+        self.system_ttl_index.insert(&txn);
+        self.expiration_time_index.insert(&txn);
+        self.hash_index
+            .insert(txn.txn.committed_hash(), (address, sequence_number));
+        self.removal_reasons.remove(&(address, sequence_number));
+        self.transactions
+            .get_mut(&address)
+            .expect("account transactions were just created")
+            .insert(sequence_number, txn);
+        self.track_indices();
         self.process_ready_transactions(&address, current_sequence_number);
         MempoolStatus::new(MempoolStatusCode::Accepted)
     }
@@ -143,25 +258,34 @@ impl TransactionStore {
         OP_COUNTERS.set("txn.priority_index", self.priority_index.size());
     }
 
-    /// checks if Mempool is full
-    /// If it's full, tries to free some space by evicting transactions from ParkingLot
-    /// We only evict on attempt to insert a transaction that would be ready for broadcast upon insertion
-    fn check_if_full(&mut self, txn: &MempoolTransaction, curr_sequence_number: u64) -> bool {
-        if self.system_ttl_index.size() >= self.capacity
-            && self.check_txn_ready(txn, curr_sequence_number)
+    /// picks the transaction to evict when Mempool is full, without modifying it
+    /// We only evict on attempt to insert a transaction that would be ready for broadcast upon
+    /// insertion: from ParkingLot first, then the last transaction of the account holding the
+    /// lowest priority ready transaction, if it pays less than `txn`. Only the last transaction
+    /// of an account is evicted, so that the remaining ones are still sequential, and never one
+    /// of the sender of `txn`, whose readiness may depend on it
+    fn find_eviction_candidate(
+        &self,
+        txn: &MempoolTransaction,
+        curr_sequence_number: u64,
+    ) -> Option<TxnPointer> {
+        if !self.check_txn_ready(txn, curr_sequence_number) {
+            return None;
+        }
+        if let Some(parked) = self.parking_lot_index.pop() {
+            return Some(parked);
+        }
+        let lowest = self.priority_index.lowest()?;
+        if lowest.address == txn.get_sender() {
+            return None;
+        }
+        let (_, last) = self.transactions.get(&lowest.address)?.iter().next_back()?;
+        if (last.is_governance_txn, last.ranking_score) < (txn.is_governance_txn, txn.ranking_score)
         {
-            // try to free some space in Mempool from ParkingLot
-            if let Some((address, sequence_number)) = self.parking_lot_index.pop() {
-                if let Some(txn) = self
-                    .transactions
-                    .get_mut(&address)
-                    .and_then(|txns| txns.remove(&sequence_number))
-                {
-                    self.index_remove(&txn);
-                }
-            }
+            Some((lowest.address, last.get_sequence_number()))
+        } else {
+            None
         }
-        self.system_ttl_index.size() >= self.capacity
     }
 
     /// check if a transaction would be ready for broadcast in mempool upon insertion (without inserting it)
@@ -170,7 +294,7 @@ impl TransactionStore {
     /// (this handles both cases where (1) txn is first possible txn for an account
     /// and (2) previous txn is committed)
     /// 2. the txn before this is ready for broadcast but not yet committed
-    fn check_txn_ready(&mut self, txn: &MempoolTransaction, curr_sequence_number: u64) -> bool {
+    fn check_txn_ready(&self, txn: &MempoolTransaction, curr_sequence_number: u64) -> bool {
         let tx_sequence_number = txn.get_sequence_number();
         if tx_sequence_number == curr_sequence_number {
             return true;
@@ -190,27 +314,34 @@ impl TransactionStore {
         false
     }
 
-    /// check if transaction is already present in Mempool
-    /// e.g. given request is update
-    /// we allow increase in gas price to speed up process
-    fn handle_gas_price_update(&mut self, txn: &MempoolTransaction) -> Result<()> {
-        if let Some(txns) = self.transactions.get_mut(&txn.get_sender()) {
-            if let Some(current_version) = txns.get_mut(&txn.get_sequence_number()) {
-                if current_version.txn.max_gas_amount() == txn.txn.max_gas_amount()
-                    && current_version.txn.payload() == txn.txn.payload()
-                    && current_version.txn.expiration_time() == txn.txn.expiration_time()
-                    && current_version.get_gas_price() < txn.get_gas_price()
-                {
-                    if let Some(txn) = txns.remove(&txn.get_sequence_number()) {
-                        self.index_remove(&txn);
-                    }
-                } else {
-                    return Err(format_err!("Invalid gas price update. txn gas price: {}, current_version gas price: {}",
-                            txn.get_gas_price(),
-                            current_version.get_gas_price()));
-                }
-            }
-        }
+    /// checks if `txn` can replace `current_version`, the transaction in Mempool with the same
+    /// sender and sequence number: its gas unit price has to be higher by at least
+    /// `replace_by_fee_min_bump_percent`, in the same currency
+    fn check_replacement(
+        &self,
+        current_version: &MempoolTransaction,
+        txn: &MempoolTransaction,
+    ) -> Result<()> {
+        ensure!(
+            current_version.txn.gas_currency_code() == txn.txn.gas_currency_code(),
+            "Invalid replacement. txn gas currency: {}, current_version gas currency: {}",
+            txn.txn.gas_currency_code(),
+            current_version.txn.gas_currency_code()
+        );
+        let current_gas_price = current_version.get_gas_price();
+        let min_bump = max(
+            1,
+            current_gas_price.saturating_mul(self.replace_by_fee_min_bump_percent) / 100,
+        );
+        let min_gas_price = current_gas_price.saturating_add(min_bump);
+        ensure!(
+            txn.get_gas_price() >= min_gas_price,
+            "Invalid gas price update. txn gas price: {}, current_version gas price: {}, \
+             min gas price for replacement: {}",
+            txn.get_gas_price(),
+            current_gas_price,
+            min_gas_price
+        );
         Ok(())
     }
 
@@ -263,7 +394,7 @@ impl TransactionStore {
             txns.append(&mut active);
 
             for transaction in txns_for_removal.values() {
-                self.index_remove(transaction);
+                self.index_remove(transaction, RemovalReason::Committed);
             }
         }
     }
@@ -283,18 +414,23 @@ impl TransactionStore {
     pub(crate) fn reject_transaction(&mut self, account: &AccountAddress, _sequence_number: u64) {
         if let Some(txns) = self.transactions.remove(&account) {
             for transaction in txns.values() {
-                self.index_remove(&transaction);
+                self.index_remove(&transaction, RemovalReason::Rejected);
             }
         }
     }
 
-    /// removes transaction from all indexes
-    fn index_remove(&mut self, txn: &MempoolTransaction) {
+    /// removes transaction from all indexes, recording why
+    fn index_remove(&mut self, txn: &MempoolTransaction, reason: RemovalReason) {
         self.system_ttl_index.remove(&txn);
         self.expiration_time_index.remove(&txn);
         self.priority_index.remove(&txn);
         self.timeline_index.remove(&txn);
         self.parking_lot_index.remove(&txn);
+        self.hash_index.remove(&txn.txn.committed_hash());
+        self.removal_reasons.insert(
+            TxnPointer::from(txn),
+            MempoolTransactionStatus::Removed(reason),
+        );
         self.track_indices();
     }
 
@@ -356,6 +492,7 @@ impl TransactionStore {
             .expect("init timestamp failure");
 
         self.gc(now, true);
+        self.removal_reasons.gc(SystemTime::now());
     }
 
     /// GC old transactions based on client-specified expiration time
@@ -383,14 +520,28 @@ impl TransactionStore {
                     let is_active = self.priority_index.contains(&txn);
                     let status = if is_active { "active" } else { "parked" };
                     OP_COUNTERS.inc(&format!("{}.{}", index_name, status));
-                    self.index_remove(&txn);
+                    let reason = if by_system_ttl {
+                        RemovalReason::TimedOut
+                    } else {
+                        RemovalReason::Expired
+                    };
+                    self.index_remove(&txn, reason);
                 }
             }
         }
         self.track_indices();
     }
 
+    pub(crate) fn size(&self) -> usize {
+        self.system_ttl_index.size()
+    }
+
     pub(crate) fn iter_queue(&self) -> PriorityQueueIter {
         self.priority_index.iter()
     }
+
+    /// iterates over all transactions in Mempool, ready or not
+    pub(crate) fn iter_all(&self) -> impl Iterator<Item = &MempoolTransaction> {
+        self.transactions.values().flat_map(|txns| txns.values())
+    }
 }
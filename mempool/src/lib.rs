@@ -59,12 +59,13 @@
 /// This module provides mocks of shared mempool for tests.
 #[cfg(any(test, feature = "fuzzing"))]
 mod tests;
+pub use core_mempool::{MempoolTransactionStatus, RemovalReason};
 pub use shared_mempool::{
-    bootstrap, network,
+    admission_policy, bootstrap, network,
     types::{
         gen_mempool_reconfig_subscription, CommitNotification, CommitResponse,
-        CommittedTransaction, ConsensusRequest, ConsensusResponse, MempoolClientSender,
-        SubmissionStatus, TransactionExclusion,
+        CommittedTransaction, ConsensusRequest, ConsensusResponse, MempoolClientRequest,
+        MempoolClientSender, MempoolPressure, SubmissionStatus, TransactionExclusion,
     },
 };
 #[cfg(feature = "fuzzing")]
@@ -0,0 +1,144 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Operator-controlled policy deciding which transactions are admitted into mempool, e.g. so that
+//! full nodes in front of validators filter out abusive senders. It's checked before VM
+//! validation, for the transactions submitted by clients as well as for those from peers.
+//!
+//! The built-in policy is a set of `AdmissionRules` read from a YAML file, e.g.
+//! ```yaml
+//! denied_senders:
+//!   - "0b6c0dd8b5a1bc6d6e4dab1ae16d8b18"
+//! min_gas_unit_price:
+//!   LBR: 1
+//! max_transaction_size: 4096
+//! ```
+//! which is reloaded periodically, so that the rules are changed without restarting the node.
+
+use anyhow::{ensure, format_err, Result};
+use libra_crypto::HashValue;
+use libra_logger::prelude::*;
+use libra_types::{
+    account_address::AccountAddress,
+    transaction::{SignedTransaction, TransactionPayload},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+pub trait AdmissionPolicy: Send + Sync {
+    /// returns the reason why `txn` isn't admitted into mempool, if it isn't
+    fn check(&self, txn: &SignedTransaction) -> Result<()>;
+}
+
+/// The rules of the built-in admission policy. A transaction is admitted if it passes all of them,
+/// so the default rules admit all transactions
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AdmissionRules {
+    /// if not empty, only the transactions of these senders are admitted
+    pub allowed_senders: HashSet<AccountAddress>,
+    pub denied_senders: HashSet<AccountAddress>,
+    /// min gas unit price by gas currency code. There is no min for the currencies not listed
+    pub min_gas_unit_price: BTreeMap<String, u64>,
+    /// max size of the raw transaction, in bytes
+    pub max_transaction_size: Option<usize>,
+    /// if not empty, only the scripts whose code has one of these SHA3-256 hashes are admitted,
+    /// and neither modules nor write sets are
+    pub allowed_script_hashes: HashSet<HashValue>,
+}
+
+impl AdmissionPolicy for AdmissionRules {
+    fn check(&self, txn: &SignedTransaction) -> Result<()> {
+        let sender = txn.sender();
+        ensure!(
+            !self.denied_senders.contains(&sender),
+            "sender {} is denied",
+            sender
+        );
+        ensure!(
+            self.allowed_senders.is_empty() || self.allowed_senders.contains(&sender),
+            "sender {} isn't allowed",
+            sender
+        );
+        if let Some(min_gas_unit_price) = self.min_gas_unit_price.get(txn.gas_currency_code()) {
+            ensure!(
+                txn.gas_unit_price() >= *min_gas_unit_price,
+                "gas unit price {} is below the min of {} {}",
+                txn.gas_unit_price(),
+                min_gas_unit_price,
+                txn.gas_currency_code()
+            );
+        }
+        if let Some(max_transaction_size) = self.max_transaction_size {
+            ensure!(
+                txn.raw_txn_bytes_len() <= max_transaction_size,
+                "transaction size of {} bytes is above the max of {}",
+                txn.raw_txn_bytes_len(),
+                max_transaction_size
+            );
+        }
+        if !self.allowed_script_hashes.is_empty() {
+            let is_allowed = match txn.payload() {
+                TransactionPayload::Script(script) => self
+                    .allowed_script_hashes
+                    .contains(&HashValue::sha3_256_of(script.code())),
+                TransactionPayload::Module(_) | TransactionPayload::WriteSet(_) => false,
+            };
+            ensure!(is_allowed, "script isn't allowed");
+        }
+        Ok(())
+    }
+}
+
+/// `AdmissionRules` read from a file, which are replaced by the content of the file on `reload`
+pub struct FileAdmissionPolicy {
+    path: PathBuf,
+    rules: RwLock<AdmissionRules>,
+}
+
+impl FileAdmissionPolicy {
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(Self {
+            path: path.to_path_buf(),
+            rules: RwLock::new(read_rules(path)?),
+        })
+    }
+
+    /// re-reads the rules from the file. The current rules are kept if it fails
+    pub fn reload(&self) -> Result<()> {
+        let rules = read_rules(&self.path)?;
+        let mut current_rules = self
+            .rules
+            .write()
+            .expect("[shared mempool] failed to acquire admission policy lock");
+        if *current_rules != rules {
+            info!(
+                "[shared mempool] reloaded admission policy from {:?}",
+                self.path
+            );
+            *current_rules = rules;
+        }
+        Ok(())
+    }
+}
+
+impl AdmissionPolicy for FileAdmissionPolicy {
+    fn check(&self, txn: &SignedTransaction) -> Result<()> {
+        self.rules
+            .read()
+            .expect("[shared mempool] failed to acquire admission policy lock")
+            .check(txn)
+    }
+}
+
+fn read_rules(path: &Path) -> Result<AdmissionRules> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format_err!("Failed to read admission policy {:?}: {}", path, e))?;
+    serde_yaml::from_str(&contents)
+        .map_err(|e| format_err!("Failed to parse admission policy {:?}: {}", path, e))
+}
@@ -8,25 +8,27 @@ use crate::{
     counters,
     network::{MempoolNetworkEvents, MempoolSyncMsg},
     shared_mempool::{
+        admission_policy::FileAdmissionPolicy,
+        journal::Journal,
+        peer_manager::PeerManager,
         tasks,
         types::{notify_subscribers, SharedMempool, SharedMempoolNotification},
     },
-    CommitNotification, ConsensusRequest, SubmissionStatus,
+    CommitNotification, ConsensusRequest, MempoolClientRequest,
 };
 use ::network::protocols::network::Event;
-use anyhow::Result;
 use bounded_executor::BoundedExecutor;
 use channel::libra_channel;
 use debug_interface::prelude::*;
 use futures::{
-    channel::{mpsc, oneshot},
+    channel::mpsc,
     stream::{select_all, FuturesUnordered},
     StreamExt,
 };
 use libra_config::config::{NodeConfig, PeerNetworkId, UpstreamNetworkId};
 use libra_logger::prelude::*;
 use libra_security_logger::{security_log, SecurityEvent};
-use libra_types::{on_chain_config::OnChainConfigPayload, transaction::SignedTransaction};
+use libra_types::on_chain_config::OnChainConfigPayload;
 use std::{
     ops::Deref,
     sync::{Arc, Mutex},
@@ -40,10 +42,7 @@ pub(crate) async fn coordinator<V>(
     mut smp: SharedMempool<V>,
     executor: Handle,
     network_events: Vec<(UpstreamNetworkId, MempoolNetworkEvents)>,
-    mut client_events: mpsc::Receiver<(
-        SignedTransaction,
-        oneshot::Sender<Result<SubmissionStatus>>,
-    )>,
+    mut client_events: mpsc::Receiver<MempoolClientRequest>,
     mut consensus_requests: mpsc::Receiver<ConsensusRequest>,
     mut state_sync_requests: mpsc::Receiver<CommitNotification>,
     mut mempool_reconfig_events: libra_channel::Receiver<(), OnChainConfigPayload>,
@@ -69,15 +68,33 @@ pub(crate) async fn coordinator<V>(
 
     loop {
         ::futures::select! {
-            (mut msg, callback) = client_events.select_next_some() => {
-                trace_event!("mempool::client_event", {"txn", msg.sender(), msg.sequence_number()});
-                bounded_executor
-                .spawn(tasks::process_client_transaction_submission(
-                    smp.clone(),
-                    msg,
-                    callback,
-                ))
-                .await;
+            msg = client_events.select_next_some() => {
+                match msg {
+                    MempoolClientRequest::SubmitTransaction(txn, callback) => {
+                        trace_event!("mempool::client_event", {"txn", txn.sender(), txn.sequence_number()});
+                        bounded_executor
+                        .spawn(tasks::process_client_transaction_submission(
+                            smp.clone(),
+                            txn,
+                            callback,
+                        ))
+                        .await;
+                    }
+                    MempoolClientRequest::GetPressure(callback) => {
+                        tasks::process_client_get_pressure(&smp, callback);
+                    }
+                    MempoolClientRequest::GetTransactionByHash(hash, callback) => {
+                        tasks::process_client_get_transaction_by_hash(&smp, hash, callback);
+                    }
+                    MempoolClientRequest::GetTransactionStatus(sender, sequence_number, callback) => {
+                        tasks::process_client_get_transaction_status(
+                            &smp,
+                            sender,
+                            sequence_number,
+                            callback,
+                        );
+                    }
+                }
             },
             msg = consensus_requests.select_next_some() => {
                 tasks::process_consensus_request(&mempool, msg).await;
@@ -126,12 +143,7 @@ pub(crate) async fn coordinator<V>(
                                             .inc_by(transactions.len() as i64);
                                         let smp_clone = smp.clone();
                                         let peer = PeerNetworkId(network_id, peer_id);
-                                        let timeline_state = match peer_manager
-                                            .is_upstream_peer(peer)
-                                        {
-                                            true => TimelineState::NonQualified,
-                                            false => TimelineState::NotReady,
-                                        };
+                                        let timeline_state = incoming_timeline_state(&peer_manager, peer);
                                         bounded_executor
                                             .spawn(tasks::process_transaction_broadcast(
                                                 smp_clone,
@@ -147,6 +159,34 @@ pub(crate) async fn coordinator<V>(
                                         tasks::process_broadcast_ack(&mempool, peer, request_id, retry_txns, backoff, is_validator, peer_manager.clone());
                                         notify_subscribers(SharedMempoolNotification::ACK, &smp.subscribers);
                                     }
+                                    MempoolSyncMsg::BroadcastInventory{request_id, hashes} => {
+                                        counters::SHARED_MEMPOOL_TRANSACTIONS_PROCESSED
+                                            .with_label_values(&["announced".to_string().deref(), peer_id.to_string().deref()])
+                                            .inc_by(hashes.len() as i64);
+                                        let peer = PeerNetworkId(network_id, peer_id);
+                                        tasks::process_broadcast_inventory(&mut smp, peer, request_id, hashes);
+                                    }
+                                    MempoolSyncMsg::GetTransactionsRequest{request_id, indices} => {
+                                        let peer = PeerNetworkId(network_id, peer_id);
+                                        tasks::process_get_transactions_request(&mut smp, peer, request_id, indices);
+                                    }
+                                    MempoolSyncMsg::GetTransactionsResponse{request_id, transactions} => {
+                                        counters::SHARED_MEMPOOL_TRANSACTIONS_PROCESSED
+                                            .with_label_values(&["received".to_string().deref(), peer_id.to_string().deref()])
+                                            .inc_by(transactions.len() as i64);
+                                        let smp_clone = smp.clone();
+                                        let peer = PeerNetworkId(network_id, peer_id);
+                                        let timeline_state = incoming_timeline_state(&peer_manager, peer);
+                                        bounded_executor
+                                            .spawn(tasks::process_get_transactions_response(
+                                                smp_clone,
+                                                peer,
+                                                request_id,
+                                                transactions,
+                                                timeline_state
+                                            ))
+                                            .await;
+                                    }
                                 };
                             }
                             _ => {
@@ -171,15 +211,47 @@ pub(crate) async fn coordinator<V>(
     crit!("[shared mempool] inbound_network_task terminated");
 }
 
+/// transactions received from upstream peers are never broadcast, as these peers are responsible
+/// for their delivery
+fn incoming_timeline_state(peer_manager: &PeerManager, peer: PeerNetworkId) -> TimelineState {
+    if peer_manager.is_upstream_peer(peer) {
+        TimelineState::NonQualified
+    } else {
+        TimelineState::NotReady
+    }
+}
+
 /// GC all expired transactions by SystemTTL
-pub(crate) async fn gc_coordinator(mempool: Arc<Mutex<CoreMempool>>, gc_interval_ms: u64) {
+pub(crate) async fn gc_coordinator(
+    mempool: Arc<Mutex<CoreMempool>>,
+    journal: Option<Arc<Mutex<Journal>>>,
+    gc_interval_ms: u64,
+) {
     let mut interval = interval(Duration::from_millis(gc_interval_ms));
     while let Some(_interval) = interval.next().await {
         mempool
             .lock()
             .expect("[shared mempool] failed to acquire mempool lock")
             .gc();
+        if let Some(journal) = &journal {
+            tasks::compact_journal(&mempool, journal);
+        }
     }
 
     crit!("SharedMempool gc_task terminated");
 }
+
+/// Periodically reloads the admission policy from its file
+pub(crate) async fn admission_policy_coordinator(
+    admission_policy: Arc<FileAdmissionPolicy>,
+    reload_interval_ms: u64,
+) {
+    let mut interval = interval(Duration::from_millis(reload_interval_ms));
+    while let Some(_interval) = interval.next().await {
+        if let Err(e) = admission_policy.reload() {
+            error!("[shared mempool] failed to reload admission policy: {}", e);
+        }
+    }
+
+    crit!("SharedMempool admission_policy_task terminated");
+}
@@ -0,0 +1,157 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! On-disk journal of the transactions accepted by mempool, so that they survive a restart of the
+//! node.
+//!
+//! Accepted transactions are appended as length-prefixed LCS records. Removals aren't journaled:
+//! the journal is compacted instead, i.e. rewritten with the transactions still in mempool, once
+//! it holds more than twice as many records as mempool has transactions. Records are written
+//! without syncing, so the ones appended right before a crash of the machine (rather than of the
+//! process) can be lost, and a truncated last record is dropped on startup.
+
+use crate::core_mempool::TimelineState;
+use anyhow::{format_err, Result};
+use libra_logger::prelude::*;
+use libra_types::transaction::SignedTransaction;
+use serde::{Deserialize, Serialize};
+use std::{
+    convert::TryInto,
+    fs::{self, File, OpenOptions},
+    io::{ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+};
+
+const LENGTH_PREFIX_SIZE: usize = 4;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub(crate) struct JournalEntry {
+    pub transaction: SignedTransaction,
+    // whether the transaction qualifies for broadcast, i.e. it wasn't received from a peer which
+    // is responsible for its delivery
+    pub broadcast: bool,
+}
+
+impl JournalEntry {
+    pub(crate) fn new(transaction: SignedTransaction, timeline_state: TimelineState) -> Self {
+        Self {
+            transaction,
+            broadcast: timeline_state != TimelineState::NonQualified,
+        }
+    }
+
+    pub(crate) fn timeline_state(&self) -> TimelineState {
+        if self.broadcast {
+            TimelineState::NotReady
+        } else {
+            TimelineState::NonQualified
+        }
+    }
+}
+
+pub(crate) struct Journal {
+    path: PathBuf,
+    file: File,
+    // number of records in the file
+    num_entries: usize,
+}
+
+impl Journal {
+    /// opens the journal at `path`, creating it if needed, and returns it along with the entries
+    /// journaled so far
+    pub(crate) fn open(path: &Path) -> Result<(Self, Vec<JournalEntry>)> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let (entries, valid_len) = match fs::read(path) {
+            Ok(bytes) => decode_entries(&bytes),
+            Err(e) if e.kind() == ErrorKind::NotFound => (vec![], 0),
+            Err(e) => return Err(e.into()),
+        };
+        let file = open_for_append(path)?;
+        // records appended after a truncated one would never be read
+        file.set_len(valid_len as u64)?;
+        Ok((
+            Self {
+                path: path.to_path_buf(),
+                file,
+                num_entries: entries.len(),
+            },
+            entries,
+        ))
+    }
+
+    pub(crate) fn num_entries(&self) -> usize {
+        self.num_entries
+    }
+
+    pub(crate) fn append(&mut self, entries: &[JournalEntry]) -> Result<()> {
+        self.file.write_all(&encode_entries(entries)?)?;
+        self.num_entries += entries.len();
+        Ok(())
+    }
+
+    /// replaces the content of the journal with `entries`
+    pub(crate) fn compact(&mut self, entries: &[JournalEntry]) -> Result<()> {
+        let tmp_path = self.path.with_extension("tmp");
+        let mut tmp_file = File::create(&tmp_path)?;
+        tmp_file.write_all(&encode_entries(entries)?)?;
+        tmp_file.sync_all()?;
+        fs::rename(&tmp_path, &self.path)?;
+        self.file = open_for_append(&self.path)?;
+        self.num_entries = entries.len();
+        Ok(())
+    }
+}
+
+fn open_for_append(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format_err!("Failed to open mempool journal {:?}: {}", path, e))
+}
+
+fn encode_entries(entries: &[JournalEntry]) -> Result<Vec<u8>> {
+    let mut bytes = vec![];
+    for entry in entries {
+        let record = lcs::to_bytes(entry)?;
+        bytes.extend_from_slice(&(record.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&record);
+    }
+    Ok(bytes)
+}
+
+/// decodes records until the end of `bytes`, stopping at the first one which is truncated or
+/// corrupted. Returns the entries along with the length of the records decoded
+fn decode_entries(bytes: &[u8]) -> (Vec<JournalEntry>, usize) {
+    let mut entries = vec![];
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        let remaining = rest.len();
+        match decode_entry(&mut rest) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => {
+                warn!(
+                    "[shared mempool] dropping the last {} bytes of mempool journal: {}",
+                    remaining, e
+                );
+                return (entries, bytes.len() - remaining);
+            }
+        }
+    }
+    (entries, bytes.len())
+}
+
+fn decode_entry(bytes: &mut &[u8]) -> Result<JournalEntry> {
+    let mut length = [0u8; LENGTH_PREFIX_SIZE];
+    bytes.read_exact(&mut length)?;
+    let length: usize = u32::from_le_bytes(length).try_into()?;
+    if bytes.len() < length {
+        return Err(format_err!("truncated record of {} bytes", length));
+    }
+    let (record, rest) = bytes.split_at(length);
+    let entry = lcs::from_bytes(record)?;
+    *bytes = rest;
+    Ok(entry)
+}
@@ -1,6 +1,8 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod admission_policy;
+pub(crate) mod journal;
 pub mod network;
 mod runtime;
 pub(crate) mod types;
@@ -8,5 +10,5 @@ pub use runtime::bootstrap;
 #[cfg(feature = "fuzzing")]
 pub(crate) use runtime::start_shared_mempool;
 mod coordinator;
-mod peer_manager;
+pub(crate) mod peer_manager;
 mod tasks;
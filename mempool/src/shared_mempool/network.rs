@@ -5,6 +5,7 @@
 
 use crate::counters;
 use channel::message_queues::QueueStyle;
+use libra_crypto::HashValue;
 use libra_types::{transaction::SignedTransaction, PeerId};
 use network::{
     error::NetworkError,
@@ -34,6 +35,29 @@ pub enum MempoolSyncMsg {
         /// backpressure signal from recipient when it is overwhelmed (e.g. mempool is full)
         backoff: bool,
     },
+    /// announcement of the transactions in a broadcast, issued by the sender in place of
+    /// `BroadcastTransactionsRequest` in inventory mode
+    BroadcastInventory {
+        /// unique id of sync request, as for `BroadcastTransactionsRequest`
+        request_id: String,
+        /// hashes of the transactions in this batch, as they are committed with
+        hashes: Vec<HashValue>,
+    },
+    /// request for the announced transactions the receiver misses. The broadcast is ACK'ed once
+    /// they are received, or right away if there are none
+    GetTransactionsRequest {
+        /// unique id of the announcement
+        request_id: String,
+        /// indices of the requested transactions in the announcement
+        indices: Vec<u64>,
+    },
+    /// requested transactions, issued by the sender of the announcement
+    GetTransactionsResponse {
+        /// unique id of the announcement
+        request_id: String,
+        /// requested transactions still in mempool, along with their indices in the announcement
+        transactions: Vec<(u64, SignedTransaction)>,
+    },
 }
 
 /// Protocol id for mempool direct-send calls
@@ -2,11 +2,19 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use libra_config::config::{PeerNetworkId, UpstreamConfig};
+use libra_crypto::HashValue;
 use std::{
     collections::{BTreeSet, HashMap, HashSet},
     sync::Mutex,
+    time::{Duration, Instant},
 };
 
+/// how long transactions requested after an inventory announcement are waited for, before they
+/// can be requested from another peer
+const INVENTORY_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+/// max number of inventory announcements of a peer whose transactions are being requested at once
+pub(crate) const MAX_INVENTORY_REQUESTS_PER_PEER: usize = 25;
+
 /// stores only peers that receive txns from this node
 pub(crate) type PeerInfo = HashMap<PeerNetworkId, PeerSyncState>;
 
@@ -24,6 +32,48 @@ pub(crate) struct PeerManager {
     upstream_config: UpstreamConfig,
     peer_info: Mutex<PeerInfo>,
     min_broadcast_recipient_count: usize,
+    // transactions requested from peers after their inventory announcements.
+    // Unlike `peer_info`, this tracks any peer that broadcasts to this node
+    inventory_requests: Mutex<InventoryRequests>,
+}
+
+#[derive(Default)]
+struct InventoryRequests {
+    // requests by peer and announcement ID
+    by_peer: HashMap<PeerNetworkId, HashMap<String, InventoryRequest>>,
+    // peer and announcement ID of the request of each requested transaction
+    by_hash: HashMap<HashValue, (PeerNetworkId, String)>,
+}
+
+impl InventoryRequests {
+    fn remove(&mut self, peer: PeerNetworkId, request_id: &str) -> Option<InventoryRequest> {
+        let requests = self.by_peer.get_mut(&peer)?;
+        let request = requests.remove(request_id)?;
+        if requests.is_empty() {
+            self.by_peer.remove(&peer);
+        }
+        for hash in request.hashes.values() {
+            // the transaction may be requested from another peer since this request timed out
+            if self
+                .by_hash
+                .get(hash)
+                .map_or(false, |(p, id)| *p == peer && id == request_id)
+            {
+                self.by_hash.remove(hash);
+            }
+        }
+        Some(request)
+    }
+}
+
+/// transactions requested from a peer after its inventory announcement, and not received yet
+pub(crate) struct InventoryRequest {
+    // hashes of the requested transactions by their indices in the announcement
+    pub hashes: HashMap<u64, HashValue>,
+    // indices of the announced transactions which weren't requested, as they are requested from
+    // another peer already. They are ACK'ed as retriable, so that they are announced again
+    pub retry_txns: Vec<u64>,
+    pub requested_at: Instant,
 }
 
 #[derive(Clone)]
@@ -52,6 +102,7 @@ impl PeerManager {
             upstream_config,
             peer_info: Mutex::new(PeerInfo::new()),
             min_broadcast_recipient_count,
+            inventory_requests: Mutex::new(InventoryRequests::default()),
         }
     }
 
@@ -84,6 +135,17 @@ impl PeerManager {
         {
             state.is_alive = false;
         }
+        let mut inventory_requests = self
+            .inventory_requests
+            .lock()
+            .expect("failed to acquire inventory requests lock");
+        let request_ids: Vec<_> = inventory_requests
+            .by_peer
+            .get(&peer)
+            .map_or_else(Vec::new, |requests| requests.keys().cloned().collect());
+        for request_id in request_ids {
+            inventory_requests.remove(peer, &request_id);
+        }
     }
 
     pub fn update_peer_broadcast(
@@ -165,14 +227,92 @@ impl PeerManager {
         self.peer_info
             .lock()
             .expect("failed to acquire lock")
-            .get(&peer)
-            .expect("missing peer")
+            .get(&peer)?
             .broadcast_info
             .sent_batches
             .get(batch_id)
             .cloned()
     }
 
+    // returns true if a transaction with `hash` is requested from any peer and the request hasn't
+    // timed out, else false
+    pub fn is_inventory_requested(&self, hash: &HashValue) -> bool {
+        let inventory_requests = self
+            .inventory_requests
+            .lock()
+            .expect("failed to acquire inventory requests lock");
+        inventory_requests
+            .by_hash
+            .get(hash)
+            .and_then(|(peer, request_id)| inventory_requests.by_peer.get(peer)?.get(request_id))
+            .map_or(false, |request| {
+                request.requested_at.elapsed() < INVENTORY_REQUEST_TIMEOUT
+            })
+    }
+
+    // returns false if `peer` has too many outstanding requests already, in which case `request`
+    // is dropped, else true
+    pub fn add_inventory_request(
+        &self,
+        peer: PeerNetworkId,
+        // ID of the announcement
+        request_id: String,
+        request: InventoryRequest,
+    ) -> bool {
+        let mut inventory_requests = self
+            .inventory_requests
+            .lock()
+            .expect("failed to acquire inventory requests lock");
+        // clean up timed out requests of the peer, whose transactions won't be accepted anymore
+        let timed_out: Vec<_> =
+            inventory_requests
+                .by_peer
+                .get(&peer)
+                .map_or_else(Vec::new, |requests| {
+                    requests
+                        .iter()
+                        .filter(|(_, request)| {
+                            request.requested_at.elapsed() >= INVENTORY_REQUEST_TIMEOUT
+                        })
+                        .map(|(request_id, _)| request_id.clone())
+                        .collect()
+                });
+        for request_id in timed_out.iter().chain(std::iter::once(&request_id)) {
+            inventory_requests.remove(peer, request_id);
+        }
+        if inventory_requests
+            .by_peer
+            .get(&peer)
+            .map_or(0, HashMap::len)
+            >= MAX_INVENTORY_REQUESTS_PER_PEER
+        {
+            return false;
+        }
+
+        for hash in request.hashes.values() {
+            inventory_requests
+                .by_hash
+                .insert(*hash, (peer, request_id.clone()));
+        }
+        inventory_requests
+            .by_peer
+            .entry(peer)
+            .or_insert_with(HashMap::new)
+            .insert(request_id, request);
+        true
+    }
+
+    pub fn take_inventory_request(
+        &self,
+        peer: PeerNetworkId,
+        request_id: &str,
+    ) -> Option<InventoryRequest> {
+        self.inventory_requests
+            .lock()
+            .expect("failed to acquire inventory requests lock")
+            .remove(peer, request_id)
+    }
+
     pub fn is_upstream_peer(&self, peer: PeerNetworkId) -> bool {
         self.upstream_config.is_upstream_peer(peer)
     }
@@ -5,20 +5,19 @@ use crate::{
     core_mempool::CoreMempool,
     network::{MempoolNetworkEvents, MempoolNetworkSender},
     shared_mempool::{
-        coordinator::{coordinator, gc_coordinator},
+        admission_policy::{AdmissionPolicy, FileAdmissionPolicy},
+        coordinator::{admission_policy_coordinator, coordinator, gc_coordinator},
+        journal::Journal,
         peer_manager::PeerManager,
+        tasks,
         types::{SharedMempool, SharedMempoolNotification, DEFAULT_MIN_BROADCAST_RECIPIENT_COUNT},
     },
-    CommitNotification, ConsensusRequest, SubmissionStatus,
+    CommitNotification, ConsensusRequest, MempoolClientRequest,
 };
-use anyhow::Result;
 use channel::libra_channel;
-use futures::channel::{
-    mpsc::{self, Receiver, UnboundedSender},
-    oneshot,
-};
+use futures::channel::mpsc::{self, Receiver, UnboundedSender};
 use libra_config::config::NodeConfig;
-use libra_types::{on_chain_config::OnChainConfigPayload, transaction::SignedTransaction, PeerId};
+use libra_types::{on_chain_config::OnChainConfigPayload, PeerId};
 use std::{
     collections::HashMap,
     sync::{Arc, Mutex, RwLock},
@@ -32,6 +31,10 @@ use vm_validator::vm_validator::{TransactionValidation, VMValidator};
 ///   - outbound_sync_task (task that periodically broadcasts transactions to peers)
 ///   - inbound_network_task (task that handles inbound mempool messages and network events)
 ///   - gc_task (task that performs GC of all expired transactions by SystemTTL)
+///   - admission_policy_task (task that periodically reloads the admission policy, if configured)
+/// and replays the mempool journal, if enabled, before processing anything else.
+/// An `admission_policy` supplied by the caller takes precedence over the one configured by
+/// `admission_policy_path`, and isn't reloaded
 pub(crate) fn start_shared_mempool<V>(
     executor: &Handle,
     config: &NodeConfig,
@@ -39,12 +42,13 @@ pub(crate) fn start_shared_mempool<V>(
     // First element in tuple is the network ID
     // See `NodeConfig::is_upstream_peer` for the definition of network ID
     mempool_network_handles: Vec<(PeerId, MempoolNetworkSender, MempoolNetworkEvents)>,
-    client_events: mpsc::Receiver<(SignedTransaction, oneshot::Sender<Result<SubmissionStatus>>)>,
+    client_events: mpsc::Receiver<MempoolClientRequest>,
     consensus_requests: mpsc::Receiver<ConsensusRequest>,
     state_sync_requests: mpsc::Receiver<CommitNotification>,
     mempool_reconfig_events: libra_channel::Receiver<(), OnChainConfigPayload>,
     db: Arc<dyn DbReader>,
     validator: Arc<RwLock<V>>,
+    admission_policy: Option<Arc<dyn AdmissionPolicy>>,
     subscribers: Vec<UnboundedSender<SharedMempoolNotification>>,
 ) where
     V: TransactionValidation + 'static,
@@ -66,6 +70,30 @@ pub(crate) fn start_shared_mempool<V>(
         network_senders.insert(network_id, network_sender);
     }
 
+    let (journal, journal_entries) = match config.mempool.journal_path() {
+        Some(path) => {
+            let (journal, entries) =
+                Journal::open(&path).expect("[shared mempool] failed to open mempool journal");
+            (Some(Arc::new(Mutex::new(journal))), entries)
+        }
+        None => (None, vec![]),
+    };
+
+    let file_admission_policy = match admission_policy {
+        Some(_) => None,
+        None => config.mempool.admission_policy_path().map(|path| {
+            Arc::new(
+                FileAdmissionPolicy::load(&path)
+                    .expect("[shared mempool] failed to load admission policy"),
+            )
+        }),
+    };
+    let admission_policy = admission_policy.or_else(|| {
+        file_admission_policy
+            .clone()
+            .map(|admission_policy| admission_policy as Arc<dyn AdmissionPolicy>)
+    });
+
     let smp = SharedMempool {
         mempool: mempool.clone(),
         config: config.mempool.clone(),
@@ -74,36 +102,54 @@ pub(crate) fn start_shared_mempool<V>(
         validator,
         peer_manager,
         subscribers,
+        journal: journal.clone(),
+        admission_policy,
     };
 
-    executor.spawn(coordinator(
-        smp,
-        executor.clone(),
-        all_network_events,
-        client_events,
-        consensus_requests,
-        state_sync_requests,
-        mempool_reconfig_events,
-        config_clone,
-    ));
+    let coordinator_executor = executor.clone();
+    executor.spawn(async move {
+        // transactions from before a restart are back in mempool before any new one is processed
+        tasks::process_journal_replay(&smp, journal_entries).await;
+        coordinator(
+            smp,
+            coordinator_executor,
+            all_network_events,
+            client_events,
+            consensus_requests,
+            state_sync_requests,
+            mempool_reconfig_events,
+            config_clone,
+        )
+        .await
+    });
 
     executor.spawn(gc_coordinator(
         mempool,
+        journal,
         config.mempool.system_transaction_gc_interval_ms,
     ));
+
+    if let Some(admission_policy) = file_admission_policy {
+        executor.spawn(admission_policy_coordinator(
+            admission_policy,
+            config.mempool.admission_policy_reload_interval_ms,
+        ));
+    }
 }
 
 /// method used to bootstrap shared mempool for a node
+/// If `admission_policy` is None, the one configured by `admission_policy_path`, if any, is used
 pub fn bootstrap(
     config: &NodeConfig,
     db: Arc<dyn DbReader>,
     // The first element in the tuple is the ID of the network that this network is a handle to
     // See `NodeConfig::is_upstream_peer` for the definition of network ID
     mempool_network_handles: Vec<(PeerId, MempoolNetworkSender, MempoolNetworkEvents)>,
-    client_events: Receiver<(SignedTransaction, oneshot::Sender<Result<SubmissionStatus>>)>,
+    client_events: Receiver<MempoolClientRequest>,
     consensus_requests: Receiver<ConsensusRequest>,
     state_sync_requests: Receiver<CommitNotification>,
     mempool_reconfig_events: libra_channel::Receiver<(), OnChainConfigPayload>,
+    admission_policy: Option<Arc<dyn AdmissionPolicy>>,
 ) -> Runtime {
     let runtime = Builder::new()
         .thread_name("shared-mem-")
@@ -124,6 +170,7 @@ pub fn bootstrap(
         mempool_reconfig_events,
         db,
         vm_validator,
+        admission_policy,
         vec![],
     );
     runtime
@@ -4,21 +4,24 @@
 //! Tasks that are executed by coordinators (short-lived compared to coordinators)
 
 use crate::{
-    core_mempool::{CoreMempool, TimelineState, TxnPointer},
+    core_mempool::{CoreMempool, MempoolTransactionStatus, TimelineState, TxnPointer},
     counters,
     network::{MempoolNetworkSender, MempoolSyncMsg},
     shared_mempool::{
-        peer_manager::PeerManager,
+        journal::{Journal, JournalEntry},
+        peer_manager::{InventoryRequest, PeerManager},
         types::{notify_subscribers, ScheduledBroadcast, SharedMempool, SharedMempoolNotification},
     },
     CommitNotification, CommitResponse, CommittedTransaction, ConsensusRequest, ConsensusResponse,
-    SubmissionStatus,
+    MempoolPressure, SubmissionStatus,
 };
 use anyhow::{format_err, Result};
 use futures::{channel::oneshot, stream::FuturesUnordered};
 use libra_config::config::PeerNetworkId;
+use libra_crypto::HashValue;
 use libra_logger::prelude::*;
 use libra_types::{
+    account_address::AccountAddress,
     mempool_status::{MempoolStatus, MempoolStatusCode},
     on_chain_config::OnChainConfigPayload,
     transaction::SignedTransaction,
@@ -30,7 +33,7 @@ use libra_types::{
 };
 use std::{
     cmp,
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     ops::Deref,
     sync::{Arc, Mutex, RwLock},
     time::{Duration, Instant, SystemTime, UNIX_EPOCH},
@@ -154,14 +157,21 @@ where
 
     let request_id = create_request_id(timeline_id, new_timeline_id);
     let txns_ct = batch_txns.len();
-    if let Err(e) = send_mempool_sync_msg(
+    let msg = if smp.config.shared_mempool_inventory_mode {
+        MempoolSyncMsg::BroadcastInventory {
+            request_id: request_id.clone(),
+            hashes: batch_txns
+                .iter()
+                .map(SignedTransaction::committed_hash)
+                .collect(),
+        }
+    } else {
         MempoolSyncMsg::BroadcastTransactionsRequest {
             request_id: request_id.clone(),
             transactions: batch_txns,
-        },
-        peer.peer_id(),
-        &mut network_sender,
-    ) {
+        }
+    };
+    if let Err(e) = send_mempool_sync_msg(msg, peer.peer_id(), &mut network_sender) {
         error!(
             "[shared mempool] error broadcasting transactions to peer {:?}: {}",
             peer, e
@@ -227,6 +237,64 @@ pub(crate) async fn process_client_transaction_submission<V>(
     }
 }
 
+/// reports how busy mempool is to client
+pub(crate) fn process_client_get_pressure<V>(
+    smp: &SharedMempool<V>,
+    callback: oneshot::Sender<MempoolPressure>,
+) where
+    V: TransactionValidation,
+{
+    let num_transactions = smp
+        .mempool
+        .lock()
+        .expect("[shared mempool] failed to acquire mempool lock")
+        .size();
+    let pressure = MempoolPressure {
+        num_transactions,
+        capacity: smp.config.capacity,
+    };
+    if callback.send(pressure).is_err() {
+        error!("[shared mempool] failed to send back mempool pressure to AC endpoint");
+    }
+}
+
+/// looks up a transaction in mempool by hash for client
+pub(crate) fn process_client_get_transaction_by_hash<V>(
+    smp: &SharedMempool<V>,
+    hash: HashValue,
+    callback: oneshot::Sender<Option<SignedTransaction>>,
+) where
+    V: TransactionValidation,
+{
+    let txn = smp
+        .mempool
+        .lock()
+        .expect("[shared mempool] failed to acquire mempool lock")
+        .get_by_hash(&hash);
+    if callback.send(txn).is_err() {
+        error!("[shared mempool] failed to send back transaction to AC endpoint");
+    }
+}
+
+/// reports where a transaction is in mempool, or why it was removed, to client
+pub(crate) fn process_client_get_transaction_status<V>(
+    smp: &SharedMempool<V>,
+    sender: AccountAddress,
+    sequence_number: u64,
+    callback: oneshot::Sender<Option<MempoolTransactionStatus>>,
+) where
+    V: TransactionValidation,
+{
+    let status = smp
+        .mempool
+        .lock()
+        .expect("[shared mempool] failed to acquire mempool lock")
+        .get_transaction_status(&sender, sequence_number);
+    if callback.send(status).is_err() {
+        error!("[shared mempool] failed to send back transaction status to AC endpoint");
+    }
+}
+
 /// processes transactions from other nodes
 pub(crate) async fn process_transaction_broadcast<V>(
     mut smp: SharedMempool<V>,
@@ -254,6 +322,17 @@ pub(crate) async fn process_transaction_broadcast<V>(
 }
 
 fn gen_ack_response(request_id: String, results: Vec<SubmissionStatus>) -> MempoolSyncMsg {
+    let (retry_txns, backoff) = gen_retry_txns(results);
+    MempoolSyncMsg::BroadcastTransactionsResponse {
+        request_id,
+        retry_txns,
+        backoff,
+    }
+}
+
+/// returns the indices of the transactions that may succeed on resend, and whether the sender
+/// should back off
+fn gen_retry_txns(results: Vec<SubmissionStatus>) -> (Vec<u64>, bool) {
     let mut backoff = false;
     let retry_txns = results
         .into_iter()
@@ -268,12 +347,7 @@ fn gen_ack_response(request_id: String, results: Vec<SubmissionStatus>) -> Mempo
             }
         })
         .collect();
-
-    MempoolSyncMsg::BroadcastTransactionsResponse {
-        request_id,
-        retry_txns,
-        backoff,
-    }
+    (retry_txns, backoff)
 }
 
 fn is_txn_retryable(result: SubmissionStatus) -> bool {
@@ -282,6 +356,193 @@ fn is_txn_retryable(result: SubmissionStatus) -> bool {
         || mempool_status == MempoolStatusCode::MempoolIsFull
 }
 
+/// requests the transactions announced by `peer` which aren't in mempool, or ACKs the
+/// announcement right away if there are none
+pub(crate) fn process_broadcast_inventory<V>(
+    smp: &mut SharedMempool<V>,
+    peer: PeerNetworkId,
+    request_id: String,
+    hashes: Vec<HashValue>,
+) where
+    V: TransactionValidation,
+{
+    let missing_txns: Vec<_> = {
+        let mempool = smp
+            .mempool
+            .lock()
+            .expect("[shared mempool] failed to acquire mempool lock");
+        hashes
+            .into_iter()
+            .enumerate()
+            .filter(|(_idx, hash)| mempool.get_by_hash(hash).is_none())
+            .map(|(idx, hash)| (idx as u64, hash))
+            .collect()
+    };
+    // transactions requested from another peer already are announced again later instead, in
+    // case that peer doesn't deliver them
+    let (requested_txns, missing_txns): (Vec<_>, Vec<_>) = missing_txns
+        .into_iter()
+        .partition(|(_idx, hash)| smp.peer_manager.is_inventory_requested(hash));
+    let mut retry_txns: Vec<_> = requested_txns.into_iter().map(|(idx, _hash)| idx).collect();
+    let indices: Vec<_> = missing_txns.iter().map(|(idx, _hash)| *idx).collect();
+
+    let msg = if missing_txns.is_empty() {
+        MempoolSyncMsg::BroadcastTransactionsResponse {
+            request_id,
+            retry_txns,
+            backoff: false,
+        }
+    } else if smp.peer_manager.add_inventory_request(
+        peer,
+        request_id.clone(),
+        InventoryRequest {
+            hashes: missing_txns.into_iter().collect(),
+            retry_txns: retry_txns.clone(),
+            requested_at: Instant::now(),
+        },
+    ) {
+        MempoolSyncMsg::GetTransactionsRequest {
+            request_id,
+            indices,
+        }
+    } else {
+        // the peer has too many announcements being requested already, so it backs off and
+        // announces these transactions again later
+        retry_txns.extend(indices);
+        MempoolSyncMsg::BroadcastTransactionsResponse {
+            request_id,
+            retry_txns,
+            backoff: true,
+        }
+    };
+    let mut network_sender = smp
+        .network_senders
+        .get_mut(&peer.network_id())
+        .expect("[shared mempool] missing network sender");
+    if let Err(e) = send_mempool_sync_msg(msg, peer.peer_id(), &mut network_sender) {
+        error!(
+            "[shared mempool] failed to respond to inventory announcement of peer {:?}: {}",
+            peer, e
+        );
+    }
+}
+
+/// sends `peer` the transactions it requested from announcement `request_id`
+pub(crate) fn process_get_transactions_request<V>(
+    smp: &mut SharedMempool<V>,
+    peer: PeerNetworkId,
+    request_id: String,
+    indices: Vec<u64>,
+) where
+    V: TransactionValidation,
+{
+    let broadcasted_batch = match smp.peer_manager.get_broadcast_batch(peer, &request_id) {
+        Some(batch) => batch,
+        None => {
+            warn!(
+                "[shared mempool] peer {:?} requested transactions of unknown announcement {}",
+                peer, request_id
+            );
+            return;
+        }
+    };
+    let indices_by_timeline_id = indices
+        .into_iter()
+        .filter_map(|idx| {
+            broadcasted_batch
+                .get(idx as usize)
+                .map(|timeline_id| (*timeline_id, idx))
+        })
+        .collect::<HashMap<_, _>>();
+    let transactions = smp
+        .mempool
+        .lock()
+        .expect("[shared mempool] failed to acquire mempool lock")
+        .filter_read_timeline(indices_by_timeline_id.keys().cloned().collect())
+        .into_iter()
+        .filter_map(|(timeline_id, txn)| {
+            indices_by_timeline_id
+                .get(&timeline_id)
+                .map(|idx| (*idx, txn))
+        })
+        .collect();
+
+    let mut network_sender = smp
+        .network_senders
+        .get_mut(&peer.network_id())
+        .expect("[shared mempool] missing network sender");
+    if let Err(e) = send_mempool_sync_msg(
+        MempoolSyncMsg::GetTransactionsResponse {
+            request_id,
+            transactions,
+        },
+        peer.peer_id(),
+        &mut network_sender,
+    ) {
+        error!(
+            "[shared mempool] failed to send requested transactions to peer {:?}: {}",
+            peer, e
+        );
+    }
+}
+
+/// processes the transactions requested from `peer` after its inventory announcement, and ACKs the
+/// announcement
+pub(crate) async fn process_get_transactions_response<V>(
+    mut smp: SharedMempool<V>,
+    peer: PeerNetworkId,
+    request_id: String,
+    transactions: Vec<(u64, SignedTransaction)>,
+    timeline_state: TimelineState,
+) where
+    V: TransactionValidation,
+{
+    let request = match smp.peer_manager.take_inventory_request(peer, &request_id) {
+        Some(request) => request,
+        None => {
+            warn!(
+                "[shared mempool] peer {:?} sent transactions for unknown request {}",
+                peer, request_id
+            );
+            return;
+        }
+    };
+    // only the transactions requested are accepted
+    let (indices, transactions): (Vec<_>, Vec<_>) = transactions
+        .into_iter()
+        .filter(|(idx, txn)| request.hashes.get(idx) == Some(&txn.committed_hash()))
+        .unzip();
+    let results = process_incoming_transactions(&smp, transactions, timeline_state).await;
+    log_txn_process_results(&results, Some(peer.peer_id()));
+
+    // the indices of the transactions to retry are the ones in the announcement
+    let (retry_txns, backoff) = gen_retry_txns(results);
+    let retry_txns = request
+        .retry_txns
+        .into_iter()
+        .chain(
+            retry_txns
+                .into_iter()
+                .filter_map(|idx| indices.get(idx as usize).cloned()),
+        )
+        .collect();
+    let ack_response = MempoolSyncMsg::BroadcastTransactionsResponse {
+        request_id,
+        retry_txns,
+        backoff,
+    };
+    let mut network_sender = smp
+        .network_senders
+        .get_mut(&peer.network_id())
+        .expect("[shared mempool] missing network sender");
+    if let Err(e) = send_mempool_sync_msg(ack_response, peer.peer_id(), &mut network_sender) {
+        error!(
+            "[shared mempool] failed to send ACK back to peer {:?}: {}",
+            peer, e
+        );
+    }
+}
+
 /// submits a list of SignedTransaction to the local mempool
 /// and returns a vector containing AdmissionControlStatus
 async fn process_incoming_transactions<V>(
@@ -292,84 +553,166 @@ async fn process_incoming_transactions<V>(
 where
     V: TransactionValidation,
 {
-    let mut statuses = vec![];
+    // statuses by the index of their transaction in `transactions`, filled in as the transactions
+    // are rejected or added, so that they line up with `transactions` regardless of which fail
+    let mut statuses: Vec<Option<SubmissionStatus>> = vec![None; transactions.len()];
+    // transactions not accepted before reaching mempool, recorded so that clients can find out
+    let mut rejections = vec![];
 
-    let seq_numbers = transactions
-        .iter()
-        .map(|t| get_account_sequence_number(smp.db.as_ref(), t.sender()))
-        .collect::<Vec<_>>();
+    let transactions: Vec<_> = transactions
+        .into_iter()
+        .enumerate()
+        .filter(|(idx, t)| {
+            let policy_check = smp
+                .admission_policy
+                .as_ref()
+                .map_or(Ok(()), |admission_policy| admission_policy.check(t));
+            match policy_check {
+                Ok(()) => true,
+                Err(e) => {
+                    rejections.push((
+                        t.sender(),
+                        t.sequence_number(),
+                        MempoolStatusCode::RejectedByPolicy,
+                        None,
+                    ));
+                    statuses[*idx] = Some((
+                        MempoolStatus::new(MempoolStatusCode::RejectedByPolicy)
+                            .with_message(e.to_string()),
+                        None,
+                    ));
+                    false
+                }
+            }
+        })
+        .collect();
 
-    let transactions: Vec<_> =
-        transactions
-            .into_iter()
-            .enumerate()
-            .filter_map(|(idx, t)| {
-                if let Ok(sequence_number) = seq_numbers[idx] {
-                    if t.sequence_number() >= sequence_number {
-                        return Some((t, sequence_number));
-                    } else {
-                        statuses.push((
-                            MempoolStatus::new(MempoolStatusCode::VmError),
-                            Some(VMStatus::new(SEQUENCE_NUMBER_TOO_OLD)),
-                        ));
-                    }
-                } else {
+    let transactions: Vec<_> = transactions
+        .into_iter()
+        .filter_map(
+            |(idx, t)| match get_account_sequence_number(smp.db.as_ref(), t.sender()) {
+                Ok(sequence_number) if t.sequence_number() >= sequence_number => {
+                    Some((idx, t, sequence_number))
+                }
+                Ok(_) => {
+                    rejections.push((
+                        t.sender(),
+                        t.sequence_number(),
+                        MempoolStatusCode::VmError,
+                        Some(SEQUENCE_NUMBER_TOO_OLD),
+                    ));
+                    statuses[idx] = Some((
+                        MempoolStatus::new(MempoolStatusCode::VmError),
+                        Some(VMStatus::new(SEQUENCE_NUMBER_TOO_OLD)),
+                    ));
+                    None
+                }
+                Err(_) => {
                     // failed to get transaction
-                    statuses.push((
+                    rejections.push((
+                        t.sender(),
+                        t.sequence_number(),
+                        MempoolStatusCode::VmError,
+                        Some(RESOURCE_DOES_NOT_EXIST),
+                    ));
+                    statuses[idx] = Some((
                         MempoolStatus::new(MempoolStatusCode::VmError),
                         Some(VMStatus::new(RESOURCE_DOES_NOT_EXIST).with_message(
                             "[shared mempool] failed to get account state".to_string(),
                         )),
                     ));
+                    None
                 }
-                None
-            })
-            .collect();
+            },
+        )
+        .collect();
 
     let validation_results = transactions
         .iter()
-        .map(|t| {
+        .map(|(_idx, t, _sequence_number)| {
             smp.validator
                 .read()
                 .unwrap()
-                .validate_transaction(t.0.clone())
+                .validate_transaction(t.clone())
         })
         .collect::<Vec<_>>();
 
+    let mut journal_entries = vec![];
     {
         let mut mempool = smp
             .mempool
             .lock()
             .expect("[shared mempool] failed to acquire mempool lock");
-        for (idx, (transaction, sequence_number)) in transactions.into_iter().enumerate() {
-            if let Ok(validation_result) = &validation_results[idx] {
-                match validation_result.status() {
-                    None => {
-                        let gas_amount = transaction.max_gas_amount();
-                        let rankin_score = validation_result.score();
-                        let is_governance_txn = validation_result.is_governance_txn();
-                        let mempool_status = mempool.add_txn(
-                            transaction,
-                            gas_amount,
-                            rankin_score,
-                            sequence_number,
-                            timeline_state,
-                            is_governance_txn,
-                        );
-                        statuses.push((mempool_status, None));
-                    }
-                    Some(validation_status) => {
-                        statuses.push((
-                            MempoolStatus::new(MempoolStatusCode::VmError),
-                            Some(validation_status.clone()),
-                        ));
+        for (sender, sequence_number, code, vm_status) in rejections {
+            mempool.record_rejection(sender, sequence_number, code, vm_status);
+        }
+        for ((idx, transaction, sequence_number), validation_result) in
+            transactions.into_iter().zip(validation_results)
+        {
+            let validation_result = match validation_result {
+                Ok(validation_result) => validation_result,
+                Err(e) => {
+                    statuses[idx] = Some((
+                        MempoolStatus::new(MempoolStatusCode::UnknownStatus)
+                            .with_message(e.to_string()),
+                        None,
+                    ));
+                    continue;
+                }
+            };
+            match validation_result.status() {
+                None => {
+                    let gas_amount = transaction.max_gas_amount();
+                    let rankin_score = validation_result.score();
+                    let is_governance_txn = validation_result.is_governance_txn();
+                    let journal_entry = smp
+                        .journal
+                        .as_ref()
+                        .map(|_| JournalEntry::new(transaction.clone(), timeline_state));
+                    let mempool_status = mempool.add_txn(
+                        transaction,
+                        gas_amount,
+                        rankin_score,
+                        sequence_number,
+                        timeline_state,
+                        is_governance_txn,
+                    );
+                    if mempool_status.code == MempoolStatusCode::Accepted {
+                        journal_entries.extend(journal_entry);
                     }
+                    statuses[idx] = Some((mempool_status, None));
+                }
+                Some(validation_status) => {
+                    mempool.record_rejection(
+                        transaction.sender(),
+                        transaction.sequence_number(),
+                        MempoolStatusCode::VmError,
+                        Some(validation_status.major_status),
+                    );
+                    statuses[idx] = Some((
+                        MempoolStatus::new(MempoolStatusCode::VmError),
+                        Some(validation_status),
+                    ));
                 }
             }
         }
     }
+    if let Some(journal) = &smp.journal {
+        if !journal_entries.is_empty() {
+            if let Err(e) = journal
+                .lock()
+                .expect("[shared mempool] failed to acquire journal lock")
+                .append(&journal_entries)
+            {
+                error!(
+                    "[shared mempool] failed to append to mempool journal: {}",
+                    e
+                );
+            }
+        }
+    }
     notify_subscribers(SharedMempoolNotification::NewTransactions, &smp.subscribers);
-    statuses
+    statuses.into_iter().flatten().collect()
 }
 
 // TODO update counters to ID peers using PeerNetworkId
@@ -435,6 +778,80 @@ pub(crate) fn process_broadcast_ack(
     peer_manager.process_broadcast_ack(peer, request_id, retry_txns, backoff);
 }
 
+// ===================== //
+// mempool journal tasks //
+// ===================== //
+
+/// re-inserts the transactions journaled before the node restarted, then compacts the journal.
+/// The ones which have expired or been committed since are skipped, so that they aren't recorded
+/// as rejected
+pub(crate) async fn process_journal_replay<V>(smp: &SharedMempool<V>, entries: Vec<JournalEntry>)
+where
+    V: TransactionValidation,
+{
+    let journal = match &smp.journal {
+        Some(journal) => journal,
+        None => return,
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Timestamp generated is before UNIX_EPOCH");
+    // looked up once per account, as the journal tends to hold several transactions of each
+    let mut committed_sequence_numbers = HashMap::new();
+    let (broadcast_entries, non_qualified_entries): (Vec<_>, Vec<_>) = entries
+        .into_iter()
+        .filter(|entry| {
+            let txn = &entry.transaction;
+            let committed_sequence_number = *committed_sequence_numbers
+                .entry(txn.sender())
+                .or_insert_with(|| get_account_sequence_number(smp.db.as_ref(), txn.sender()).ok());
+            txn.expiration_time() > now
+                && committed_sequence_number.map_or(true, |seq| txn.sequence_number() >= seq)
+        })
+        .partition(|entry| entry.broadcast);
+    for entries in vec![broadcast_entries, non_qualified_entries] {
+        if let Some(timeline_state) = entries.first().map(JournalEntry::timeline_state) {
+            let transactions = entries.into_iter().map(|entry| entry.transaction).collect();
+            let statuses = process_incoming_transactions(smp, transactions, timeline_state).await;
+            let accepted = statuses
+                .iter()
+                .filter(|(status, _)| status.code == MempoolStatusCode::Accepted)
+                .count();
+            info!(
+                "[shared mempool] replayed {} transactions from mempool journal, {} accepted",
+                statuses.len(),
+                accepted
+            );
+        }
+    }
+    compact_journal(&smp.mempool, journal);
+}
+
+/// rewrites the journal with the transactions in mempool once it holds more than twice as many
+pub(crate) fn compact_journal(mempool: &Mutex<CoreMempool>, journal: &Mutex<Journal>) {
+    // the journal is locked first, so that transactions accepted meanwhile are appended to the
+    // compacted journal rather than lost
+    let mut journal = journal
+        .lock()
+        .expect("[shared mempool] failed to acquire journal lock");
+    let transactions = {
+        let mempool = mempool
+            .lock()
+            .expect("[shared mempool] failed to acquire mempool lock");
+        if journal.num_entries() <= 2 * mempool.size() {
+            return;
+        }
+        mempool.get_all_transactions()
+    };
+    let entries: Vec<_> = transactions
+        .into_iter()
+        .map(|(transaction, timeline_state)| JournalEntry::new(transaction, timeline_state))
+        .collect();
+    if let Err(e) = journal.compact(&entries) {
+        error!("[shared mempool] failed to compact mempool journal: {}", e);
+    }
+}
+
 // ================================= //
 // intra-node communication handlers //
 // ================================= //
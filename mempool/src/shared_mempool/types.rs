@@ -4,8 +4,11 @@
 //! Objects used by/related to shared mempool
 
 use crate::{
-    core_mempool::CoreMempool,
-    shared_mempool::{network::MempoolNetworkSender, peer_manager::PeerManager},
+    core_mempool::{CoreMempool, MempoolTransactionStatus},
+    shared_mempool::{
+        admission_policy::AdmissionPolicy, journal::Journal, network::MempoolNetworkSender,
+        peer_manager::PeerManager,
+    },
 };
 use anyhow::Result;
 use channel::libra_channel::Receiver;
@@ -15,6 +18,7 @@ use futures::{
     task::{Context, Poll},
 };
 use libra_config::config::{MempoolConfig, PeerNetworkId};
+use libra_crypto::HashValue;
 use libra_types::{
     account_address::AccountAddress,
     mempool_status::MempoolStatus,
@@ -50,6 +54,8 @@ where
     pub validator: Arc<RwLock<V>>,
     pub peer_manager: Arc<PeerManager>,
     pub subscribers: Vec<UnboundedSender<SharedMempoolNotification>>,
+    pub journal: Option<Arc<Mutex<Journal>>>,
+    pub admission_policy: Option<Arc<dyn AdmissionPolicy>>,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -190,9 +196,38 @@ pub struct TransactionExclusion {
 /// Submission Status is represented as combination of vm_validator internal status and core mempool insertion status
 pub type SubmissionStatus = (MempoolStatus, Option<VMStatus>);
 
-/// sender type: used to enqueue new transactions to shared mempool by client endpoints
-pub type MempoolClientSender =
-    mpsc::Sender<(SignedTransaction, oneshot::Sender<Result<SubmissionStatus>>)>;
+/// Request sent from client endpoints, e.g. JSON-RPC, to shared mempool
+pub enum MempoolClientRequest {
+    /// submits a transaction to mempool
+    SubmitTransaction(
+        SignedTransaction,
+        // callback to send back the submission status
+        oneshot::Sender<Result<SubmissionStatus>>,
+    ),
+    /// gets how busy mempool is
+    GetPressure(oneshot::Sender<MempoolPressure>),
+    /// gets a transaction in mempool by the hash it is committed with
+    GetTransactionByHash(HashValue, oneshot::Sender<Option<SignedTransaction>>),
+    /// gets where the transaction of given sender and sequence number is in mempool, or why it
+    /// was removed
+    GetTransactionStatus(
+        AccountAddress,
+        u64,
+        oneshot::Sender<Option<MempoolTransactionStatus>>,
+    ),
+}
+
+/// How busy mempool is, for clients to price their transactions accordingly
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MempoolPressure {
+    /// number of transactions in mempool, including those not ready for the next block
+    pub num_transactions: usize,
+    /// max number of transactions mempool holds
+    pub capacity: usize,
+}
+
+/// sender type: used to send requests to shared mempool by client endpoints
+pub type MempoolClientSender = mpsc::Sender<MempoolClientRequest>;
 
 /// On-chain configs that mempool subscribes to for reconfiguration
 const MEMPOOL_SUBSCRIBED_CONFIGS: &[ConfigID] = &[LibraVersion::CONFIG_ID, VMConfig::CONFIG_ID];
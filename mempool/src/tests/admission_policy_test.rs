@@ -0,0 +1,81 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    shared_mempool::admission_policy::{AdmissionPolicy, AdmissionRules, FileAdmissionPolicy},
+    tests::common::TestTransaction,
+};
+use libra_crypto::HashValue;
+use libra_temppath::TempPath;
+use libra_types::account_config::LBR_NAME;
+use std::fs;
+
+#[test]
+fn test_admission_rules() {
+    let txn = TestTransaction::new(0, 0, 1).make_signed_transaction();
+    let sender = TestTransaction::get_address(0);
+    let other_sender = TestTransaction::get_address(1);
+    assert!(AdmissionRules::default().check(&txn).is_ok());
+
+    // sender allow and deny lists
+    let mut rules = AdmissionRules::default();
+    rules.denied_senders.insert(other_sender);
+    assert!(rules.check(&txn).is_ok());
+    rules.denied_senders.insert(sender);
+    assert!(rules.check(&txn).is_err());
+
+    let mut rules = AdmissionRules::default();
+    rules.allowed_senders.insert(other_sender);
+    assert!(rules.check(&txn).is_err());
+    rules.allowed_senders.insert(sender);
+    assert!(rules.check(&txn).is_ok());
+
+    // min gas unit price applies to the currency the transaction pays gas in only
+    let mut rules = AdmissionRules::default();
+    rules.min_gas_unit_price.insert("Coin1".to_string(), 2);
+    assert!(rules.check(&txn).is_ok());
+    rules.min_gas_unit_price.insert(LBR_NAME.to_string(), 2);
+    assert!(rules.check(&txn).is_err());
+    rules.min_gas_unit_price.insert(LBR_NAME.to_string(), 1);
+    assert!(rules.check(&txn).is_ok());
+
+    let mut rules = AdmissionRules::default();
+    rules.max_transaction_size = Some(txn.raw_txn_bytes_len() - 1);
+    assert!(rules.check(&txn).is_err());
+    rules.max_transaction_size = Some(txn.raw_txn_bytes_len());
+    assert!(rules.check(&txn).is_ok());
+
+    // the script of test transactions is empty
+    let mut rules = AdmissionRules::default();
+    rules.allowed_script_hashes.insert(HashValue::random());
+    assert!(rules.check(&txn).is_err());
+    rules
+        .allowed_script_hashes
+        .insert(HashValue::sha3_256_of(&[]));
+    assert!(rules.check(&txn).is_ok());
+}
+
+#[test]
+fn test_admission_policy_reload() {
+    let txn = TestTransaction::new(0, 0, 1).make_signed_transaction();
+    let path = TempPath::new();
+    let mut rules = AdmissionRules::default();
+    rules.denied_senders.insert(txn.sender());
+    fs::write(path.path(), serde_yaml::to_string(&rules).unwrap()).unwrap();
+
+    let policy = FileAdmissionPolicy::load(path.path()).unwrap();
+    assert!(policy.check(&txn).is_err());
+
+    // rules which fail to parse are ignored
+    fs::write(path.path(), "denied_senders: 1").unwrap();
+    assert!(policy.reload().is_err());
+    assert!(policy.check(&txn).is_err());
+
+    fs::write(
+        path.path(),
+        serde_yaml::to_string(&AdmissionRules::default()).unwrap(),
+    )
+    .unwrap();
+    policy.reload().unwrap();
+    assert!(policy.check(&txn).is_ok());
+}
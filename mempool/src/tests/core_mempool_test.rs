@@ -2,14 +2,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    core_mempool::{CoreMempool, TimelineState, TtlCache},
+    core_mempool::{CoreMempool, MempoolTransactionStatus, RemovalReason, TimelineState, TtlCache},
     tests::common::{
         add_signed_txn, add_txn, add_txns_to_mempool, exist_in_metrics_cache, setup_mempool,
-        TestTransaction,
+        ConsensusMock, TestTransaction,
     },
 };
 use libra_config::config::NodeConfig;
-use libra_types::transaction::SignedTransaction;
+use libra_types::{
+    mempool_status::MempoolStatusCode, transaction::SignedTransaction, vm_error::StatusCode,
+};
 use std::{
     collections::HashSet,
     time::{Duration, SystemTime},
@@ -141,19 +143,65 @@ fn test_update_invalid_transaction_in_mempool() {
         vec![TestTransaction::new(0, 0, 1), TestTransaction::new(1, 0, 2)],
     );
     let updated_txn = TestTransaction::make_signed_transaction_with_max_gas_amount(
-        &TestTransaction::new(0, 0, 5),
+        &TestTransaction::new(0, 0, 1),
         200,
     );
-    let _added_tnx = add_signed_txn(&mut mempool, updated_txn);
+    assert!(add_signed_txn(&mut mempool, updated_txn).is_err());
 
-    // since both gas price and mas gas amount were updated, the ordering should not have changed.
-    // the second transaction with gas price 2 should come first
+    // since the gas price wasn't raised, the transaction isn't replaced and the ordering should
+    // not have changed. the second transaction with gas price 2 should come first
     assert_eq!(consensus.get_block(&mut mempool, 1), vec![txns[1].clone()]);
     let next_tnx = consensus.get_block(&mut mempool, 1);
     assert_eq!(next_tnx, vec![txns[0].clone()]);
     assert_eq!(next_tnx[0].gas_unit_price(), 1);
 }
 
+#[test]
+fn test_replace_by_fee() {
+    let mut config = NodeConfig::random();
+    config.mempool.capacity = 2;
+    let mut pool = CoreMempool::new(&config);
+    let mut consensus = ConsensusMock::new();
+    let txns = add_txns_to_mempool(
+        &mut pool,
+        vec![
+            TestTransaction::new(0, 0, 10),
+            TestTransaction::new(1, 0, 20),
+        ],
+    );
+    let (_, last_timeline_id) = pool.read_timeline(0, 10);
+
+    // the gas price has to be raised by at least 10%
+    let updated_txn = TestTransaction::make_signed_transaction_with_max_gas_amount(
+        &TestTransaction::new(0, 0, 10),
+        200,
+    );
+    assert!(add_signed_txn(&mut pool, updated_txn).is_err());
+
+    // a replacement goes through even though Mempool is full, and can change other fields
+    let updated_txn = TestTransaction::make_signed_transaction_with_max_gas_amount(
+        &TestTransaction::new(0, 0, 11),
+        200,
+    );
+    add_signed_txn(&mut pool, updated_txn.clone()).unwrap();
+    assert!(add_txn(&mut pool, TestTransaction::new(2, 0, 1)).is_err());
+
+    // the replacement is in the timeline again, so that it's broadcast
+    let (timeline, _) = pool.read_timeline(last_timeline_id, 10);
+    let timeline: Vec<_> = timeline.into_iter().map(|(_id, txn)| txn).collect();
+    assert_eq!(timeline, vec![updated_txn.clone()]);
+
+    let address = TestTransaction::get_address(0);
+    assert!(matches!(
+        pool.get_transaction_status(&address, 0),
+        Some(MempoolTransactionStatus::Ready { .. })
+    ));
+    assert_eq!(
+        consensus.get_block(&mut pool, 2),
+        vec![txns[1].clone(), updated_txn]
+    );
+}
+
 #[test]
 fn test_remove_transaction() {
     let (mut pool, mut consensus) = setup_mempool();
@@ -361,6 +409,75 @@ fn test_parking_lot_evict_only_for_ready_txn_insertion() {
     }
 }
 
+#[test]
+fn test_gas_price_eviction() {
+    let mut config = NodeConfig::random();
+    config.mempool.capacity = 4;
+    let mut pool = CoreMempool::new(&config);
+    add_txns_to_mempool(
+        &mut pool,
+        vec![
+            TestTransaction::new(0, 0, 1),
+            TestTransaction::new(0, 1, 1),
+            TestTransaction::new(1, 0, 5),
+            TestTransaction::new(1, 2, 20),
+        ],
+    );
+    let evicted = |pool: &CoreMempool, address: usize, sequence_number: u64| {
+        pool.get_transaction_status(&TestTransaction::get_address(address), sequence_number)
+            == Some(MempoolTransactionStatus::Removed(RemovalReason::Evicted))
+    };
+
+    // parked transactions are evicted first
+    add_txn(&mut pool, TestTransaction::new(2, 0, 10)).unwrap();
+    assert!(evicted(&pool, 1, 2));
+
+    // then the last transaction of the account paying the lowest gas price
+    add_txn(&mut pool, TestTransaction::new(2, 1, 10)).unwrap();
+    assert!(evicted(&pool, 0, 1));
+    add_txn(&mut pool, TestTransaction::new(2, 2, 2)).unwrap();
+    assert!(evicted(&pool, 0, 0));
+
+    // a transaction paying no more than the ones in Mempool is rejected
+    assert!(add_txn(&mut pool, TestTransaction::new(3, 0, 2)).is_err());
+    // transactions of the same sender are never evicted
+    assert!(add_txn(&mut pool, TestTransaction::new(2, 3, 4)).is_err());
+
+    let mut txns: Vec<_> = pool
+        .get_block(4, HashSet::new())
+        .iter()
+        .map(|txn| (txn.sender(), txn.sequence_number()))
+        .collect();
+    txns.sort();
+    let mut expected = vec![
+        (TestTransaction::get_address(1), 0),
+        (TestTransaction::get_address(2), 0),
+        (TestTransaction::get_address(2), 1),
+        (TestTransaction::get_address(2), 2),
+    ];
+    expected.sort();
+    assert_eq!(txns, expected);
+}
+
+#[test]
+fn test_rejected_transaction_does_not_evict() {
+    let mut config = NodeConfig::random();
+    config.mempool.capacity = 2;
+    config.mempool.capacity_per_user = 1;
+    let mut pool = CoreMempool::new(&config);
+    add_txns_to_mempool(
+        &mut pool,
+        vec![TestTransaction::new(0, 0, 1), TestTransaction::new(1, 0, 1)],
+    );
+
+    // the transaction would evict a cheaper one, but its sender is over its own limit
+    assert!(add_txn(&mut pool, TestTransaction::new(1, 1, 10)).is_err());
+    assert!(matches!(
+        pool.get_transaction_status(&TestTransaction::get_address(0), 0),
+        Some(MempoolTransactionStatus::Ready { .. })
+    ));
+}
+
 #[test]
 fn test_gc_ready_transaction() {
     let mut pool = setup_mempool().0;
@@ -397,6 +514,111 @@ fn test_gc_ready_transaction() {
     assert_eq!(timeline[0].sequence_number(), 0);
 }
 
+#[test]
+fn test_transaction_status() {
+    let mut pool = setup_mempool().0;
+    let address = TestTransaction::get_address(1);
+    add_txn(&mut pool, TestTransaction::new(1, 0, 1)).unwrap();
+    let txn = TestTransaction::new(1, 1, 1)
+        .make_signed_transaction_with_expiration_time(Duration::from_secs(10));
+    pool.add_txn(txn, 0, 1, 0, TimelineState::NotReady, false);
+    add_txn(&mut pool, TestTransaction::new(1, 2, 1)).unwrap();
+    // parked because of the gap at sequence number 3
+    add_txn(&mut pool, TestTransaction::new(1, 4, 1)).unwrap();
+
+    // the client-specified expiration time is earlier than the system one
+    assert_eq!(
+        pool.get_transaction_status(&address, 1),
+        Some(MempoolTransactionStatus::Ready {
+            expiration_time: Duration::from_secs(10)
+        })
+    );
+    assert!(matches!(
+        pool.get_transaction_status(&address, 4),
+        Some(MempoolTransactionStatus::Parked { .. })
+    ));
+    assert_eq!(pool.get_transaction_status(&address, 3), None);
+
+    pool.remove_transaction(&address, 0, false);
+    assert_eq!(
+        pool.get_transaction_status(&address, 0),
+        Some(MempoolTransactionStatus::Removed(RemovalReason::Committed))
+    );
+    // submitting a committed transaction again fails, while it's still reported as committed
+    assert!(add_txn(&mut pool, TestTransaction::new(1, 0, 1)).is_err());
+    assert_eq!(
+        pool.get_transaction_status(&address, 0),
+        Some(MempoolTransactionStatus::Removed(RemovalReason::Committed))
+    );
+
+    pool.gc_by_expiration_time(Duration::from_secs(11));
+    assert_eq!(
+        pool.get_transaction_status(&address, 1),
+        Some(MempoolTransactionStatus::Removed(RemovalReason::Expired))
+    );
+    assert!(matches!(
+        pool.get_transaction_status(&address, 2),
+        Some(MempoolTransactionStatus::Parked { .. })
+    ));
+
+    // following transactions of the sender are removed along with the rejected one
+    pool.remove_transaction(&address, 2, true);
+    for sequence_number in &[2, 4] {
+        assert_eq!(
+            pool.get_transaction_status(&address, *sequence_number),
+            Some(MempoolTransactionStatus::Removed(RemovalReason::Rejected))
+        );
+    }
+
+    // a transaction submitted again is reported as such
+    add_txn(&mut pool, TestTransaction::new(1, 2, 1)).unwrap();
+    assert!(matches!(
+        pool.get_transaction_status(&address, 2),
+        Some(MempoolTransactionStatus::Parked { .. })
+    ));
+}
+
+#[test]
+fn test_transaction_status_not_accepted() {
+    let mut pool = setup_mempool().0;
+    let address = TestTransaction::get_address(2);
+
+    // the sequence number is below that of the account
+    let txn = TestTransaction::new(2, 0, 1).make_signed_transaction();
+    pool.add_txn(txn, 0, 1, 1, TimelineState::NotReady, false);
+    assert_eq!(
+        pool.get_transaction_status(&address, 0),
+        Some(MempoolTransactionStatus::NotAccepted {
+            code: MempoolStatusCode::InvalidSeqNumber,
+            vm_status: None,
+        })
+    );
+
+    // rejected before reaching mempool, e.g. by VM validation
+    pool.record_rejection(
+        address,
+        1,
+        MempoolStatusCode::VmError,
+        Some(StatusCode::INVALID_SIGNATURE),
+    );
+    assert_eq!(
+        pool.get_transaction_status(&address, 1),
+        Some(MempoolTransactionStatus::NotAccepted {
+            code: MempoolStatusCode::VmError,
+            vm_status: Some(StatusCode::INVALID_SIGNATURE),
+        })
+    );
+
+    // a transaction accepted when submitted again is reported as such, even if a later
+    // submission of it isn't accepted
+    add_txn(&mut pool, TestTransaction::new(2, 1, 1)).unwrap();
+    assert!(add_txn(&mut pool, TestTransaction::new(2, 1, 1)).is_err());
+    assert!(matches!(
+        pool.get_transaction_status(&address, 1),
+        Some(MempoolTransactionStatus::Ready { .. })
+    ));
+}
+
 #[test]
 fn test_clean_stuck_transactions() {
     let mut pool = setup_mempool().0;
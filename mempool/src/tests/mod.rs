@@ -1,6 +1,8 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+#[cfg(test)]
+mod admission_policy_test;
 #[cfg(test)]
 mod common;
 #[cfg(test)]
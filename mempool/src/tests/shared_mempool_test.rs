@@ -5,9 +5,15 @@ use crate::{
     core_mempool::{CoreMempool, TimelineState},
     mocks::MockSharedMempool,
     network::{MempoolNetworkEvents, MempoolNetworkSender, MempoolSyncMsg},
-    shared_mempool::{start_shared_mempool, types::SharedMempoolNotification},
+    shared_mempool::{
+        admission_policy::{AdmissionPolicy, AdmissionRules},
+        journal::{Journal, JournalEntry},
+        peer_manager::{InventoryRequest, PeerManager, MAX_INVENTORY_REQUESTS_PER_PEER},
+        start_shared_mempool,
+        types::SharedMempoolNotification,
+    },
     tests::common::{batch_add_signed_txn, TestTransaction},
-    CommitNotification, CommittedTransaction, ConsensusRequest,
+    CommitNotification, CommittedTransaction, ConsensusRequest, MempoolClientRequest,
 };
 use channel::{self, libra_channel, message_queues::QueueStyle};
 use futures::{
@@ -24,8 +30,13 @@ use libra_config::{
     config::{NetworkConfig, NodeConfig, PeerNetworkId, RoleType, UpstreamConfig},
     network_id::NetworkId,
 };
+use libra_crypto::{hash::CryptoHash, HashValue};
 use libra_network_address::NetworkAddress;
-use libra_types::{transaction::SignedTransaction, PeerId};
+use libra_temppath::TempPath;
+use libra_types::{
+    transaction::{SignedTransaction, Transaction},
+    PeerId,
+};
 use network::{
     peer_manager::{
         conn_notifs_channel, ConnectionNotification, ConnectionRequestSender,
@@ -35,9 +46,11 @@ use network::{
 };
 use std::{
     collections::{HashMap, HashSet},
+    fs::{self, OpenOptions},
+    io::Write,
     num::NonZeroUsize,
     sync::{Arc, Mutex, RwLock},
-    time::Duration,
+    time::{Duration, Instant},
 };
 use storage_interface::mock::MockDbReader;
 use tokio::runtime::{Builder, Runtime};
@@ -59,6 +72,16 @@ struct SharedMempoolNetwork {
 // start a shared mempool for a node `peer_id` with config `config`
 // and add it to `smp` network
 fn init_single_shared_mempool(smp: &mut SharedMempoolNetwork, peer_id: PeerId, config: NodeConfig) {
+    init_shared_mempool_with_policy(smp, peer_id, config, None)
+}
+
+// same as `init_single_shared_mempool`, with `admission_policy` supplied to the shared mempool
+fn init_shared_mempool_with_policy(
+    smp: &mut SharedMempoolNetwork,
+    peer_id: PeerId,
+    config: NodeConfig,
+    admission_policy: Option<Arc<dyn AdmissionPolicy>>,
+) {
     let mempool = Arc::new(Mutex::new(CoreMempool::new(&config)));
     let (network_reqs_tx, network_reqs_rx) =
         libra_channel::new(QueueStyle::FIFO, NonZeroUsize::new(8).unwrap(), None);
@@ -97,6 +120,7 @@ fn init_single_shared_mempool(smp: &mut SharedMempoolNetwork, peer_id: PeerId, c
         reconfig_events_receiver,
         Arc::new(MockDbReader),
         Arc::new(RwLock::new(MockVMValidator)),
+        admission_policy,
         vec![sender],
     );
 
@@ -163,6 +187,7 @@ fn init_smp_multiple_networks(
         reconfig_events_receiver,
         Arc::new(MockDbReader),
         Arc::new(RwLock::new(MockVMValidator)),
+        None,
         vec![sender],
     );
 
@@ -384,6 +409,26 @@ impl SharedMempoolNetwork {
         }
     }
 
+    /// delivers next message from `peer`, whatever it is, and returns it
+    fn deliver_next_message(&mut self, peer: &PeerId) -> MempoolSyncMsg {
+        let network_reqs_rx = self.network_reqs_rxs.get_mut(peer).unwrap();
+        let network_req = block_on(network_reqs_rx.next()).unwrap();
+
+        if let PeerManagerRequest::SendMessage(peer_id, msg) = network_req {
+            let sync_msg = lcs::from_bytes(&msg.mdata).unwrap();
+            let receiver_network_notif_tx = self.network_notifs_txs.get_mut(&peer_id).unwrap();
+            receiver_network_notif_tx
+                .push(
+                    (*peer, ProtocolId::MempoolDirectSend),
+                    PeerManagerNotification::RecvMessage(*peer, msg),
+                )
+                .unwrap();
+            sync_msg
+        } else {
+            panic!("peer {:?} didn't send message", peer)
+        }
+    }
+
     fn exist_in_metrics_cache(&self, peer_id: &PeerId, txn: &TestTransaction) -> bool {
         let mempool = self.mempools.get(peer_id).unwrap().lock().unwrap();
         mempool
@@ -632,6 +677,212 @@ fn test_broadcast_updated_transaction() {
     assert_eq!(txn.get(0).unwrap().gas_unit_price(), 5);
 }
 
+#[test]
+fn test_broadcast_inventory() {
+    let mut smp = SharedMempoolNetwork::default();
+    let mut peers = vec![];
+    for _ in 0..2 {
+        let mut config = NodeConfig::random();
+        config.validator_network = Some(NetworkConfig::network_with_id(NetworkId::Validator));
+        let peer_id = config.validator_network.as_ref().unwrap().peer_id();
+        config.mempool.shared_mempool_inventory_mode = true;
+        config.upstream = UpstreamConfig::default();
+        config.upstream.primary_networks.push(peer_id);
+        init_single_shared_mempool(&mut smp, peer_id, config);
+        peers.push(peer_id);
+    }
+    let (peer_a, peer_b) = (peers[0], peers[1]);
+
+    // B already has the first of A's transactions
+    let txns = vec![TestTransaction::new(1, 0, 1), TestTransaction::new(1, 1, 1)];
+    smp.add_txns(&peer_a, txns.clone());
+    smp.add_txns(&peer_b, vec![txns[0].clone()]);
+    smp.send_connection_event(
+        &peer_a,
+        ConnectionNotification::NewPeer(peer_b, NetworkAddress::mock()),
+    );
+
+    // A announces the hashes of its transactions
+    smp.wait_for_event(&peer_a, SharedMempoolNotification::Broadcast);
+    let hashes = match smp.deliver_next_message(&peer_a) {
+        MempoolSyncMsg::BroadcastInventory { hashes, .. } => hashes,
+        msg => panic!("unexpected message {:?}", msg),
+    };
+    let signed_txns: Vec<_> = txns
+        .iter()
+        .map(|txn| txn.make_signed_transaction_with_max_gas_amount(5))
+        .collect();
+    let expected_hashes: Vec<_> = signed_txns
+        .iter()
+        .map(|txn| Transaction::UserTransaction(txn.clone()).hash())
+        .collect();
+    assert_eq!(hashes, expected_hashes);
+
+    // B requests only the one it misses
+    match smp.deliver_next_message(&peer_b) {
+        MempoolSyncMsg::GetTransactionsRequest { indices, .. } => assert_eq!(indices, vec![1]),
+        msg => panic!("unexpected message {:?}", msg),
+    }
+    match smp.deliver_next_message(&peer_a) {
+        MempoolSyncMsg::GetTransactionsResponse { transactions, .. } => {
+            assert_eq!(transactions, vec![(1, signed_txns[1].clone())])
+        }
+        msg => panic!("unexpected message {:?}", msg),
+    }
+
+    // B accepts it and ACKs the announcement
+    smp.wait_for_event(&peer_b, SharedMempoolNotification::NewTransactions);
+    let block = smp
+        .mempools
+        .get(&peer_b)
+        .unwrap()
+        .lock()
+        .unwrap()
+        .get_block(100, HashSet::new());
+    assert_eq!(block, signed_txns);
+    smp.deliver_response(&peer_b);
+}
+
+#[test]
+fn test_submission_statuses_of_mixed_batch() {
+    let mut rules = AdmissionRules::default();
+    rules.denied_senders.insert(TestTransaction::get_address(2));
+
+    let mut smp = SharedMempoolNetwork::default();
+    let mut peers = vec![];
+    for i in 0..2 {
+        let mut config = NodeConfig::random();
+        config.validator_network = Some(NetworkConfig::network_with_id(NetworkId::Validator));
+        let peer_id = config.validator_network.as_ref().unwrap().peer_id();
+        config.upstream = UpstreamConfig::default();
+        config.upstream.primary_networks.push(peer_id);
+        // only B filters transactions, and accepts one transaction per sender
+        let admission_policy = if i == 1 {
+            config.mempool.capacity_per_user = 1;
+            Some(Arc::new(rules.clone()) as Arc<dyn AdmissionPolicy>)
+        } else {
+            None
+        };
+        init_shared_mempool_with_policy(&mut smp, peer_id, config, admission_policy);
+        peers.push(peer_id);
+    }
+    let (peer_a, peer_b) = (peers[0], peers[1]);
+
+    // the transaction rejected by the policy comes after the ones B can't accept for now
+    smp.add_txns(
+        &peer_a,
+        vec![
+            TestTransaction::new(1, 0, 1),
+            TestTransaction::new(1, 1, 1),
+            TestTransaction::new(2, 0, 1),
+        ],
+    );
+    smp.send_connection_event(
+        &peer_a,
+        ConnectionNotification::NewPeer(peer_b, NetworkAddress::mock()),
+    );
+    smp.wait_for_event(&peer_a, SharedMempoolNotification::Broadcast);
+    match smp.deliver_next_message(&peer_a) {
+        MempoolSyncMsg::BroadcastTransactionsRequest { transactions, .. } => {
+            assert_eq!(transactions.len(), 3)
+        }
+        msg => panic!("unexpected message {:?}", msg),
+    }
+    smp.wait_for_event(&peer_b, SharedMempoolNotification::NewTransactions);
+
+    // only the transaction over the capacity of its sender is retried
+    match smp.deliver_next_message(&peer_b) {
+        MempoolSyncMsg::BroadcastTransactionsResponse { retry_txns, .. } => {
+            assert_eq!(retry_txns, vec![1])
+        }
+        msg => panic!("unexpected message {:?}", msg),
+    }
+}
+
+#[test]
+fn test_inventory_requests() {
+    let peer_manager = PeerManager::new(UpstreamConfig::default(), 1);
+    let peer_a = PeerNetworkId(PeerId::random(), PeerId::random());
+    let peer_b = PeerNetworkId(PeerId::random(), PeerId::random());
+    let request = |hash: HashValue| InventoryRequest {
+        hashes: vec![(0, hash)].into_iter().collect(),
+        retry_txns: vec![],
+        requested_at: Instant::now(),
+    };
+
+    let hashes: Vec<_> = (0..=MAX_INVENTORY_REQUESTS_PER_PEER)
+        .map(|_| HashValue::random())
+        .collect();
+    for (idx, hash) in hashes[..MAX_INVENTORY_REQUESTS_PER_PEER].iter().enumerate() {
+        assert!(peer_manager.add_inventory_request(peer_a, idx.to_string(), request(*hash)));
+        assert!(peer_manager.is_inventory_requested(hash));
+    }
+    // the number of outstanding requests of a peer is capped, but not the one of other peers
+    let last = hashes[MAX_INVENTORY_REQUESTS_PER_PEER];
+    assert!(!peer_manager.add_inventory_request(peer_a, "last".to_string(), request(last)));
+    assert!(!peer_manager.is_inventory_requested(&last));
+    assert!(peer_manager.add_inventory_request(peer_b, "last".to_string(), request(last)));
+    assert!(peer_manager.is_inventory_requested(&last));
+
+    // a request is forgotten once its transactions are received, or its peer is gone
+    assert!(peer_manager.take_inventory_request(peer_a, "0").is_some());
+    assert!(!peer_manager.is_inventory_requested(&hashes[0]));
+    assert!(peer_manager.add_inventory_request(peer_a, "last".to_string(), request(last)));
+    peer_manager.disable_peer(peer_b);
+    assert!(peer_manager.is_inventory_requested(&last));
+    peer_manager.disable_peer(peer_a);
+    assert!(hashes
+        .iter()
+        .all(|hash| !peer_manager.is_inventory_requested(hash)));
+}
+
+#[test]
+fn test_admission_policy() {
+    let policy_path = TempPath::new();
+    let mut rules = AdmissionRules::default();
+    rules.denied_senders.insert(TestTransaction::get_address(2));
+    fs::write(policy_path.path(), serde_yaml::to_string(&rules).unwrap()).unwrap();
+
+    let mut smp = SharedMempoolNetwork::default();
+    let mut peers = vec![];
+    for i in 0..2 {
+        let mut config = NodeConfig::random();
+        config.validator_network = Some(NetworkConfig::network_with_id(NetworkId::Validator));
+        let peer_id = config.validator_network.as_ref().unwrap().peer_id();
+        // only B filters transactions
+        if i == 1 {
+            config.mempool.admission_policy_path = Some(policy_path.path().to_path_buf());
+        }
+        config.upstream = UpstreamConfig::default();
+        config.upstream.primary_networks.push(peer_id);
+        init_single_shared_mempool(&mut smp, peer_id, config);
+        peers.push(peer_id);
+    }
+    let (peer_a, peer_b) = (peers[0], peers[1]);
+
+    smp.add_txns(
+        &peer_a,
+        vec![TestTransaction::new(1, 0, 1), TestTransaction::new(2, 0, 1)],
+    );
+    smp.send_connection_event(
+        &peer_a,
+        ConnectionNotification::NewPeer(peer_b, NetworkAddress::mock()),
+    );
+
+    // B rejects the transaction of the denied sender
+    let transactions = smp.deliver_message(&peer_a, 1, false).0;
+    assert_eq!(transactions.len(), 2);
+    let block = smp
+        .mempools
+        .get(&peer_b)
+        .unwrap()
+        .lock()
+        .unwrap()
+        .get_block(100, HashSet::new());
+    assert_eq!(block.len(), 1);
+    assert_eq!(block[0].sender(), TestTransaction::get_address(1));
+}
+
 #[test]
 fn test_consensus_events_rejected_txns() {
     let smp = MockSharedMempool::new(None);
@@ -735,6 +986,68 @@ fn test_state_sync_events_committed_txns() {
     assert_eq!(timeline.get(0).unwrap().1, kept_txn);
 }
 
+#[test]
+fn test_client_get_pressure() {
+    let smp = MockSharedMempool::new(None);
+    let txns = vec![
+        TestTransaction::new(0, 0, 1).make_signed_transaction(),
+        TestTransaction::new(0, 1, 1).make_signed_transaction(),
+    ];
+    {
+        let mut pool = smp
+            .mempool
+            .lock()
+            .expect("[mempool test] failed to acquire lock");
+        assert!(batch_add_signed_txn(&mut pool, txns).is_ok());
+    }
+
+    let (callback, callback_rcv) = oneshot::channel();
+    let mut ac_client = smp.ac_client.clone();
+    let pressure = block_on(async {
+        assert!(ac_client
+            .send(MempoolClientRequest::GetPressure(callback))
+            .await
+            .is_ok());
+        callback_rcv.await.unwrap()
+    });
+    assert_eq!(pressure.num_transactions, 2);
+    assert!(pressure.capacity >= pressure.num_transactions);
+}
+
+#[test]
+fn test_client_get_transaction_by_hash() {
+    let smp = MockSharedMempool::new(None);
+    let txn = TestTransaction::new(0, 0, 1).make_signed_transaction();
+    let hash = Transaction::UserTransaction(txn.clone()).hash();
+    {
+        let mut pool = smp
+            .mempool
+            .lock()
+            .expect("[mempool test] failed to acquire lock");
+        assert!(batch_add_signed_txn(&mut pool, vec![txn.clone()]).is_ok());
+    }
+
+    let mut ac_client = smp.ac_client.clone();
+    let mut get_by_hash = |hash| {
+        let (callback, callback_rcv) = oneshot::channel();
+        block_on(async {
+            assert!(ac_client
+                .send(MempoolClientRequest::GetTransactionByHash(hash, callback))
+                .await
+                .is_ok());
+            callback_rcv.await.unwrap()
+        })
+    };
+    assert_eq!(get_by_hash(hash), Some(txn.clone()));
+    assert_eq!(get_by_hash(HashValue::zero()), None);
+
+    smp.mempool
+        .lock()
+        .expect("[mempool test] failed to acquire lock")
+        .remove_transaction(&txn.sender(), txn.sequence_number(), false);
+    assert_eq!(get_by_hash(hash), None);
+}
+
 #[test]
 fn test_broadcast_ack_single_account_single_peer() {
     let batch_size = 3;
@@ -1023,3 +1336,59 @@ fn test_rebroadcast_too_many_txns() {
         .collect::<Vec<_>>();
     assert_eq!(vec![2, 3], seq_nums);
 }
+
+#[test]
+fn test_journal_replay() {
+    let journal_dir = TempPath::new();
+    let journal_path = journal_dir.path().join("mempool_journal");
+    let (mut journal, entries) = Journal::open(&journal_path).unwrap();
+    assert!(entries.is_empty());
+
+    let expired_txn = TestTransaction::new(0, 0, 1)
+        .make_signed_transaction_with_expiration_time(Duration::from_secs(1));
+    let txns = vec![
+        TestTransaction::new(1, 0, 1).make_signed_transaction(),
+        TestTransaction::new(1, 1, 1).make_signed_transaction(),
+        TestTransaction::new(2, 0, 1).make_signed_transaction(),
+    ];
+    journal
+        .append(&[
+            JournalEntry::new(expired_txn, TimelineState::NotReady),
+            JournalEntry::new(txns[0].clone(), TimelineState::NotReady),
+            JournalEntry::new(txns[1].clone(), TimelineState::NotReady),
+            JournalEntry::new(txns[2].clone(), TimelineState::NonQualified),
+        ])
+        .unwrap();
+    drop(journal);
+    // a record truncated by a crash is dropped
+    OpenOptions::new()
+        .append(true)
+        .open(&journal_path)
+        .unwrap()
+        .write_all(&[100, 0, 0, 0, 1])
+        .unwrap();
+
+    let mut expected = txns.clone();
+    expected.sort_by_key(|txn| (txn.sender(), txn.sequence_number()));
+
+    // the journal is the same, compacted or not, when the node restarts again
+    for _ in 0..2 {
+        let mut config = NodeConfig::random();
+        config.mempool.journal_path = Some(journal_path.clone());
+        let peer = PeerId::random();
+        let mut smp = SharedMempoolNetwork::default();
+        init_single_shared_mempool(&mut smp, peer, config);
+        // the transactions to broadcast and the ones received from peers are replayed separately
+        smp.wait_for_event(&peer, SharedMempoolNotification::NewTransactions);
+        smp.wait_for_event(&peer, SharedMempoolNotification::NewTransactions);
+
+        let mut mempool = smp.mempools.get(&peer).unwrap().lock().unwrap();
+        let mut block = mempool.get_block(100, HashSet::new());
+        block.sort_by_key(|txn| (txn.sender(), txn.sequence_number()));
+        assert_eq!(block, expected);
+        // only the transactions which qualified for broadcast before the restart are broadcast
+        let (timeline, _) = mempool.read_timeline(0, 10);
+        let timeline: Vec<_> = timeline.into_iter().map(|(_id, txn)| txn).collect();
+        assert_eq!(timeline, txns[0..2].to_vec());
+    }
+}
@@ -2,18 +2,24 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    manifest::state_snapshot::{StateSnapshotBackup, StateSnapshotChunk},
+    manifest::{
+        epoch_ending::{EpochEndingBackup, EpochEndingChunk},
+        state_snapshot::{StateSnapshotBackup, StateSnapshotChunk},
+        transaction::{TransactionBackup, TransactionChunk},
+    },
+    metadata::Metadata,
     storage::{BackupHandleRef, BackupStorage, FileHandle},
     ReadRecordBytes,
 };
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, ensure, Result};
 use bytes::Bytes;
 use futures::stream::TryStreamExt;
 use libra_crypto::HashValue;
 use libra_types::{
     account_state_blob::AccountStateBlob, ledger_info::LedgerInfoWithSignatures,
-    proof::TransactionInfoWithProof, transaction::Version,
+    proof::TransactionInfoWithProof, transaction::Version, waypoint::Waypoint,
 };
+use libradb::backup::DbState;
 use std::{mem::size_of, sync::Arc};
 use structopt::StructOpt;
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
@@ -61,6 +67,12 @@ impl BackupServiceClient {
             .compat())
     }
 
+    pub async fn get_db_state(&self) -> Result<Option<DbState>> {
+        let mut buf = Vec::new();
+        self.get("db_state").await?.read_to_end(&mut buf).await?;
+        Ok(lcs::from_bytes(&buf)?)
+    }
+
     pub async fn get_latest_state_root(&self) -> Result<(Version, HashValue)> {
         let mut buf = Vec::new();
         self.get("latest_state_root")
@@ -91,9 +103,49 @@ impl BackupServiceClient {
             .await?;
         Ok(buf)
     }
+
+    async fn get_transactions(
+        &self,
+        start_version: Version,
+        num_transactions: u64,
+    ) -> Result<impl AsyncRead> {
+        self.get(&format!(
+            "transactions/{}/{}",
+            start_version, num_transactions
+        ))
+        .await
+    }
+
+    async fn get_transaction_range_proof(
+        &self,
+        first_version: Version,
+        last_version: Version,
+    ) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        self.get(&format!(
+            "transaction_range_proof/{}/{}",
+            first_version, last_version,
+        ))
+        .await?
+        .read_to_end(&mut buf)
+        .await?;
+        Ok(buf)
+    }
+
+    async fn get_epoch_ending_ledger_infos(
+        &self,
+        start_epoch: u64,
+        end_epoch: u64,
+    ) -> Result<impl AsyncRead> {
+        self.get(&format!(
+            "epoch_ending_ledger_infos/{}/{}",
+            start_epoch, end_epoch
+        ))
+        .await
+    }
 }
 
-#[derive(StructOpt)]
+#[derive(Clone, StructOpt)]
 pub struct GlobalBackupOpt {
     #[structopt(long = "max-chunk-size", about = "Maximum chunk file size in bytes.")]
     pub max_chunk_size: usize,
@@ -111,6 +163,8 @@ pub struct StateSnapshotBackupOpt {
 pub struct StateSnapshotBackupController {
     version: Version,
     max_chunk_size: usize,
+    /// Appended to the name of the backup, if set, to keep it apart from earlier attempts.
+    name_suffix: Option<String>,
     client: Arc<BackupServiceClient>,
     storage: Arc<dyn BackupStorage>,
 }
@@ -125,11 +179,19 @@ impl StateSnapshotBackupController {
         Self {
             version: opt.version,
             max_chunk_size: global_opt.max_chunk_size,
+            name_suffix: None,
             client,
             storage,
         }
     }
 
+    /// Appends `suffix` to the name of the backup, so that it doesn't collide with what an
+    /// earlier attempt at the same snapshot left behind.
+    pub fn with_name_suffix(mut self, suffix: String) -> Self {
+        self.name_suffix = Some(suffix);
+        self
+    }
+
     pub async fn run(self) -> Result<FileHandle> {
         let backup_handle = self.storage.create_backup(&self.backup_name()).await?;
 
@@ -188,13 +250,20 @@ impl StateSnapshotBackupController {
             .await?;
         chunks.push(chunk);
 
-        self.write_manifest(&backup_handle, chunks).await
+        let manifest_handle = self.write_manifest(&backup_handle, chunks).await?;
+        Metadata::new_state_snapshot_backup(self.version, manifest_handle.clone())
+            .save_to(&*self.storage)
+            .await?;
+        Ok(manifest_handle)
     }
 }
 
 impl StateSnapshotBackupController {
     fn backup_name(&self) -> String {
-        format!("state_ver_{}", self.version)
+        match &self.name_suffix {
+            Some(suffix) => format!("state_ver_{}.{}", self.version, suffix),
+            None => format!("state_ver_{}", self.version),
+        }
     }
 
     fn manifest_name() -> &'static str {
@@ -233,6 +302,7 @@ impl StateSnapshotBackupController {
             .create_for_write(backup_handle, &Self::chunk_name(first_idx))
             .await?;
         chunk_file.write_all(&chunk_bytes).await?;
+        chunk_file.shutdown().await?;
         let (proof_handle, mut proof_file) = self
             .storage
             .create_for_write(backup_handle, &Self::chunk_proof_name(first_idx, last_idx))
@@ -245,6 +315,7 @@ impl StateSnapshotBackupController {
             &mut proof_file,
         )
         .await?;
+        proof_file.shutdown().await?;
 
         Ok(StateSnapshotChunk {
             first_idx,
@@ -271,6 +342,7 @@ impl StateSnapshotBackupController {
             .create_for_write(&backup_handle, Self::proof_name())
             .await?;
         proof_file.write_all(&proof_bytes).await?;
+        proof_file.shutdown().await?;
 
         let manifest = StateSnapshotBackup {
             version: self.version,
@@ -286,6 +358,375 @@ impl StateSnapshotBackupController {
         manifest_file
             .write_all(&serde_json::to_vec(&manifest)?)
             .await?;
+        manifest_file.shutdown().await?;
+
+        Ok(manifest_handle)
+    }
+}
+
+#[derive(StructOpt)]
+pub struct TransactionBackupOpt {
+    #[structopt(long = "start-version", help = "First transaction to backup.")]
+    pub start_version: Version,
+
+    #[structopt(long = "num-transactions", help = "Number of transactions to backup")]
+    pub num_transactions: u64,
+}
+
+pub struct TransactionBackupController {
+    start_version: Version,
+    num_transactions: u64,
+    max_chunk_size: usize,
+    client: Arc<BackupServiceClient>,
+    storage: Arc<dyn BackupStorage>,
+}
+
+impl TransactionBackupController {
+    pub fn new(
+        opt: TransactionBackupOpt,
+        global_opt: GlobalBackupOpt,
+        client: Arc<BackupServiceClient>,
+        storage: Arc<dyn BackupStorage>,
+    ) -> Self {
+        Self {
+            start_version: opt.start_version,
+            num_transactions: opt.num_transactions,
+            max_chunk_size: global_opt.max_chunk_size,
+            client,
+            storage,
+        }
+    }
+
+    pub async fn run(self) -> Result<FileHandle> {
+        ensure!(self.num_transactions > 0, "No transactions to backup.");
+        let backup_handle = self.storage.create_backup(&self.backup_name()).await?;
+
+        let mut chunks = Vec::new();
+        let mut chunk_bytes = Vec::new();
+        let mut chunk_first_version = self.start_version;
+        let mut current_version = self.start_version;
+
+        let mut transactions_file = self
+            .client
+            .get_transactions(self.start_version, self.num_transactions)
+            .await?;
+        while let Some(record_bytes) = transactions_file.read_record_bytes().await? {
+            if !chunk_bytes.is_empty()
+                && chunk_bytes.len() + size_of::<u32>() + record_bytes.len() > self.max_chunk_size
+            {
+                println!("Reached max_chunk_size.");
+
+                let chunk = self
+                    .write_chunk(
+                        &backup_handle,
+                        &chunk_bytes,
+                        chunk_first_version,
+                        current_version - 1,
+                    )
+                    .await?;
+                chunks.push(chunk);
+                chunk_bytes = vec![];
+                chunk_first_version = current_version;
+            }
+
+            chunk_bytes.extend(&(record_bytes.len() as u32).to_be_bytes());
+            chunk_bytes.extend(&record_bytes);
+            current_version += 1;
+        }
+
+        ensure!(
+            current_version == self.start_version + self.num_transactions,
+            "Got {} transactions from the backup service, expecting {}.",
+            current_version - self.start_version,
+            self.num_transactions,
+        );
+
+        println!("Last chunk.");
+        let chunk = self
+            .write_chunk(
+                &backup_handle,
+                &chunk_bytes,
+                chunk_first_version,
+                current_version - 1,
+            )
+            .await?;
+        chunks.push(chunk);
+
+        let last_version = current_version - 1;
+        let manifest_handle = self
+            .write_manifest(&backup_handle, last_version, chunks)
+            .await?;
+        Metadata::new_transaction_backup(self.start_version, last_version, manifest_handle.clone())
+            .save_to(&*self.storage)
+            .await?;
+        Ok(manifest_handle)
+    }
+}
+
+impl TransactionBackupController {
+    fn backup_name(&self) -> String {
+        format!(
+            "transaction_{}-{}",
+            self.start_version,
+            self.start_version + self.num_transactions - 1
+        )
+    }
+
+    fn manifest_name() -> &'static str {
+        "transaction.manifest"
+    }
+
+    fn chunk_name(first_version: Version) -> String {
+        format!("{}-.chunk", first_version)
+    }
+
+    fn chunk_proof_name(first_version: Version, last_version: Version) -> String {
+        format!("{}-{}.proof", first_version, last_version)
+    }
+
+    async fn write_chunk(
+        &self,
+        backup_handle: &BackupHandleRef,
+        chunk_bytes: &[u8],
+        first_version: Version,
+        last_version: Version,
+    ) -> Result<TransactionChunk> {
+        let (transactions_handle, mut transactions_file) = self
+            .storage
+            .create_for_write(backup_handle, &Self::chunk_name(first_version))
+            .await?;
+        transactions_file.write_all(&chunk_bytes).await?;
+        transactions_file.shutdown().await?;
+
+        let proof_bytes = self
+            .client
+            .get_transaction_range_proof(first_version, last_version)
+            .await?;
+        let (proof_handle, mut proof_file) = self
+            .storage
+            .create_for_write(
+                backup_handle,
+                &Self::chunk_proof_name(first_version, last_version),
+            )
+            .await?;
+        proof_file.write_all(&proof_bytes).await?;
+        proof_file.shutdown().await?;
+
+        Ok(TransactionChunk {
+            first_version,
+            last_version,
+            transactions: transactions_handle,
+            proof: proof_handle,
+        })
+    }
+
+    async fn write_manifest(
+        &self,
+        backup_handle: &BackupHandleRef,
+        last_version: Version,
+        chunks: Vec<TransactionChunk>,
+    ) -> Result<FileHandle> {
+        let manifest = TransactionBackup {
+            first_version: self.start_version,
+            last_version,
+            chunks,
+        };
+        manifest.verify()?;
+
+        let (manifest_handle, mut manifest_file) = self
+            .storage
+            .create_for_write(&backup_handle, Self::manifest_name())
+            .await?;
+        manifest_file
+            .write_all(&serde_json::to_vec(&manifest)?)
+            .await?;
+        manifest_file.shutdown().await?;
+
+        Ok(manifest_handle)
+    }
+}
+
+#[derive(StructOpt)]
+pub struct EpochEndingBackupOpt {
+    #[structopt(long = "start-epoch", help = "First epoch to backup.")]
+    pub start_epoch: u64,
+
+    #[structopt(
+        long = "end-epoch",
+        help = "Epoch before which epoch ending backup stops."
+    )]
+    pub end_epoch: u64,
+}
+
+pub struct EpochEndingBackupController {
+    start_epoch: u64,
+    end_epoch: u64,
+    max_chunk_size: usize,
+    client: Arc<BackupServiceClient>,
+    storage: Arc<dyn BackupStorage>,
+}
+
+impl EpochEndingBackupController {
+    pub fn new(
+        opt: EpochEndingBackupOpt,
+        global_opt: GlobalBackupOpt,
+        client: Arc<BackupServiceClient>,
+        storage: Arc<dyn BackupStorage>,
+    ) -> Self {
+        Self {
+            start_epoch: opt.start_epoch,
+            end_epoch: opt.end_epoch,
+            max_chunk_size: global_opt.max_chunk_size,
+            client,
+            storage,
+        }
+    }
+
+    pub async fn run(self) -> Result<FileHandle> {
+        ensure!(
+            self.start_epoch < self.end_epoch,
+            "No epochs to backup. start_epoch: {}, end_epoch: {}",
+            self.start_epoch,
+            self.end_epoch,
+        );
+        let backup_handle = self.storage.create_backup(&self.backup_name()).await?;
+
+        let mut chunks = Vec::new();
+        let mut waypoints = Vec::new();
+        let mut chunk_bytes = Vec::new();
+        let mut chunk_first_epoch = self.start_epoch;
+        let mut current_epoch = self.start_epoch;
+
+        let mut ledger_infos_file = self
+            .client
+            .get_epoch_ending_ledger_infos(self.start_epoch, self.end_epoch)
+            .await?;
+        while let Some(record_bytes) = ledger_infos_file.read_record_bytes().await? {
+            if !chunk_bytes.is_empty()
+                && chunk_bytes.len() + size_of::<u32>() + record_bytes.len() > self.max_chunk_size
+            {
+                println!("Reached max_chunk_size.");
+
+                let chunk = self
+                    .write_chunk(
+                        &backup_handle,
+                        &chunk_bytes,
+                        chunk_first_epoch,
+                        current_epoch - 1,
+                    )
+                    .await?;
+                chunks.push(chunk);
+                chunk_bytes = vec![];
+                chunk_first_epoch = current_epoch;
+            }
+
+            let li: LedgerInfoWithSignatures = lcs::from_bytes(&record_bytes)?;
+            ensure!(
+                li.ledger_info().epoch() == current_epoch,
+                "Unexpected epoch. Expected: {}, actual: {}",
+                current_epoch,
+                li.ledger_info().epoch(),
+            );
+            waypoints.push(Waypoint::new_epoch_boundary(li.ledger_info())?);
+
+            chunk_bytes.extend(&(record_bytes.len() as u32).to_be_bytes());
+            chunk_bytes.extend(&record_bytes);
+            current_epoch += 1;
+        }
+
+        ensure!(
+            current_epoch == self.end_epoch,
+            "Backup service returned ledger infos up to epoch {}, expecting up to {}.",
+            current_epoch,
+            self.end_epoch,
+        );
+
+        println!("Last chunk.");
+        let chunk = self
+            .write_chunk(
+                &backup_handle,
+                &chunk_bytes,
+                chunk_first_epoch,
+                current_epoch - 1,
+            )
+            .await?;
+        chunks.push(chunk);
+
+        // Non-empty, since there's at least one epoch.
+        let first_version = waypoints[0].version();
+        let last_version = waypoints[waypoints.len() - 1].version();
+        let manifest_handle = self
+            .write_manifest(&backup_handle, waypoints, chunks)
+            .await?;
+        Metadata::new_epoch_ending_backup(
+            self.start_epoch,
+            self.end_epoch - 1,
+            first_version,
+            last_version,
+            manifest_handle.clone(),
+        )
+        .save_to(&*self.storage)
+        .await?;
+        Ok(manifest_handle)
+    }
+}
+
+impl EpochEndingBackupController {
+    fn backup_name(&self) -> String {
+        format!("epoch_ending_{}-{}", self.start_epoch, self.end_epoch - 1)
+    }
+
+    fn manifest_name() -> &'static str {
+        "epoch_ending.manifest"
+    }
+
+    fn chunk_name(first_epoch: u64) -> String {
+        format!("{}-.chunk", first_epoch)
+    }
+
+    async fn write_chunk(
+        &self,
+        backup_handle: &BackupHandleRef,
+        chunk_bytes: &[u8],
+        first_epoch: u64,
+        last_epoch: u64,
+    ) -> Result<EpochEndingChunk> {
+        let (chunk_handle, mut chunk_file) = self
+            .storage
+            .create_for_write(backup_handle, &Self::chunk_name(first_epoch))
+            .await?;
+        chunk_file.write_all(&chunk_bytes).await?;
+        chunk_file.shutdown().await?;
+
+        Ok(EpochEndingChunk {
+            first_epoch,
+            last_epoch,
+            ledger_infos: chunk_handle,
+        })
+    }
+
+    async fn write_manifest(
+        &self,
+        backup_handle: &BackupHandleRef,
+        waypoints: Vec<Waypoint>,
+        chunks: Vec<EpochEndingChunk>,
+    ) -> Result<FileHandle> {
+        let manifest = EpochEndingBackup {
+            first_epoch: self.start_epoch,
+            last_epoch: self.end_epoch - 1,
+            waypoints,
+            chunks,
+        };
+        manifest.verify()?;
+
+        let (manifest_handle, mut manifest_file) = self
+            .storage
+            .create_for_write(&backup_handle, Self::manifest_name())
+            .await?;
+        manifest_file
+            .write_all(&serde_json::to_vec(&manifest)?)
+            .await?;
+        manifest_file.shutdown().await?;
 
         Ok(manifest_handle)
     }
@@ -0,0 +1,41 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use backup_cli::{
+    backup::{BackupServiceClient, BackupServiceClientOpt, GlobalBackupOpt},
+    coordinator::{BackupCoordinator, BackupCoordinatorOpt},
+    storage::StorageOpt,
+};
+use std::sync::Arc;
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+struct Opt {
+    #[structopt(flatten)]
+    global: GlobalBackupOpt,
+
+    #[structopt(flatten)]
+    client: BackupServiceClientOpt,
+
+    #[structopt(flatten)]
+    coordinator: BackupCoordinatorOpt,
+
+    #[structopt(subcommand)]
+    storage: StorageOpt,
+}
+
+#[tokio::main]
+async fn main() {
+    let opt = Opt::from_args();
+    let client = Arc::new(BackupServiceClient::new_with_opt(opt.client));
+    let storage = opt
+        .storage
+        .init_storage()
+        .await
+        .expect("Failed initializing storage.");
+
+    BackupCoordinator::new(opt.coordinator, opt.global, client, storage)
+        .run()
+        .await
+        .expect("Backup coordinator exited with error.");
+}
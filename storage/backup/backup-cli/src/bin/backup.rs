@@ -3,10 +3,11 @@
 
 use backup_cli::{
     backup::{
-        BackupServiceClient, BackupServiceClientOpt, GlobalBackupOpt,
-        StateSnapshotBackupController, StateSnapshotBackupOpt,
+        BackupServiceClient, BackupServiceClientOpt, EpochEndingBackupController,
+        EpochEndingBackupOpt, GlobalBackupOpt, StateSnapshotBackupController,
+        StateSnapshotBackupOpt, TransactionBackupController, TransactionBackupOpt,
     },
-    storage::local_fs::{LocalFs, LocalFsOpt},
+    storage::StorageOpt,
 };
 use std::sync::Arc;
 use structopt::StructOpt;
@@ -16,27 +17,84 @@ struct Opt {
     #[structopt(flatten)]
     global: GlobalBackupOpt,
 
-    #[structopt(flatten)]
-    state_snapshot: StateSnapshotBackupOpt,
-
     #[structopt(flatten)]
     client: BackupServiceClientOpt,
 
-    #[structopt(flatten)]
-    storage: LocalFsOpt,
+    #[structopt(subcommand)]
+    backup_type: BackupType,
+}
+
+#[derive(StructOpt)]
+enum BackupType {
+    EpochEnding {
+        #[structopt(flatten)]
+        opt: EpochEndingBackupOpt,
+
+        #[structopt(subcommand)]
+        storage: StorageOpt,
+    },
+    StateSnapshot {
+        #[structopt(flatten)]
+        opt: StateSnapshotBackupOpt,
+
+        #[structopt(subcommand)]
+        storage: StorageOpt,
+    },
+    Transaction {
+        #[structopt(flatten)]
+        opt: TransactionBackupOpt,
+
+        #[structopt(subcommand)]
+        storage: StorageOpt,
+    },
 }
 
 #[tokio::main]
 async fn main() {
     let opt = Opt::from_args();
     let client = Arc::new(BackupServiceClient::new_with_opt(opt.client));
-    let storage = Arc::new(LocalFs::new_with_opt(opt.storage));
 
-    let manifest =
-        StateSnapshotBackupController::new(opt.state_snapshot, opt.global, client, storage)
-            .run()
-            .await
-            .expect("Failed to backup account state.");
+    let manifest = match opt.backup_type {
+        BackupType::EpochEnding {
+            opt: backup_opt,
+            storage,
+        } => {
+            let storage = storage
+                .init_storage()
+                .await
+                .expect("Failed initializing storage.");
+            EpochEndingBackupController::new(backup_opt, opt.global, client, storage)
+                .run()
+                .await
+                .expect("Failed to backup epoch ending ledger infos.")
+        }
+        BackupType::StateSnapshot {
+            opt: backup_opt,
+            storage,
+        } => {
+            let storage = storage
+                .init_storage()
+                .await
+                .expect("Failed initializing storage.");
+            StateSnapshotBackupController::new(backup_opt, opt.global, client, storage)
+                .run()
+                .await
+                .expect("Failed to backup account state.")
+        }
+        BackupType::Transaction {
+            opt: backup_opt,
+            storage,
+        } => {
+            let storage = storage
+                .init_storage()
+                .await
+                .expect("Failed initializing storage.");
+            TransactionBackupController::new(backup_opt, opt.global, client, storage)
+                .run()
+                .await
+                .expect("Failed to backup transactions.")
+        }
+    };
 
     println!("Success. Manifest saved to {}", &manifest);
 }
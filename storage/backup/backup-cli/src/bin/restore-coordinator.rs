@@ -0,0 +1,51 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use backup_cli::{
+    coordinator::{RestoreCoordinator, RestoreCoordinatorOpt},
+    restore::GlobalRestoreOpt,
+    storage::StorageOpt,
+};
+use libradb::LibraDB;
+use std::sync::Arc;
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+struct Opt {
+    #[structopt(flatten)]
+    global: GlobalRestoreOpt,
+
+    #[structopt(flatten)]
+    coordinator: RestoreCoordinatorOpt,
+
+    #[structopt(subcommand)]
+    storage: StorageOpt,
+}
+
+#[tokio::main]
+async fn main() {
+    let opt = Opt::from_args();
+
+    let db = Arc::new(
+        LibraDB::open(
+            &opt.global.db_dir,
+            false, /* read_only */
+            None,  /* pruner */
+            None,  /* ledger_pruner */
+            false, /* account_state_history */
+        )
+        .expect("Failed opening DB."),
+    );
+    let verifier = opt.global.verifier(Arc::clone(&db));
+    let storage = opt
+        .storage
+        .init_storage()
+        .await
+        .expect("Failed initializing storage.");
+
+    RestoreCoordinator::new(opt.coordinator, storage, db, verifier)
+        .run()
+        .await
+        .expect("Failed restoring DB.");
+    println!("Finished restoring DB to target version.");
+}
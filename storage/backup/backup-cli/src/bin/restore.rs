@@ -2,8 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use backup_cli::{
-    restore::{GlobalRestoreOpt, StateSnapshotRestoreController, StateSnapshotRestoreOpt},
-    storage::local_fs::{LocalFs, LocalFsOpt},
+    restore::{
+        EpochEndingRestoreController, EpochEndingRestoreOpt, GlobalRestoreOpt,
+        StateSnapshotRestoreController, StateSnapshotRestoreOpt, TransactionRestoreController,
+        TransactionRestoreOpt,
+    },
+    storage::StorageOpt,
 };
 use libradb::LibraDB;
 use std::sync::Arc;
@@ -14,11 +18,33 @@ struct Opt {
     #[structopt(flatten)]
     global: GlobalRestoreOpt,
 
-    #[structopt(flatten)]
-    state_snapshot: StateSnapshotRestoreOpt,
+    #[structopt(subcommand)]
+    restore_type: RestoreType,
+}
 
-    #[structopt(flatten)]
-    storage: LocalFsOpt,
+#[derive(StructOpt)]
+enum RestoreType {
+    EpochEnding {
+        #[structopt(flatten)]
+        opt: EpochEndingRestoreOpt,
+
+        #[structopt(subcommand)]
+        storage: StorageOpt,
+    },
+    StateSnapshot {
+        #[structopt(flatten)]
+        opt: StateSnapshotRestoreOpt,
+
+        #[structopt(subcommand)]
+        storage: StorageOpt,
+    },
+    Transaction {
+        #[structopt(flatten)]
+        opt: TransactionRestoreOpt,
+
+        #[structopt(subcommand)]
+        storage: StorageOpt,
+    },
 }
 
 #[tokio::main]
@@ -27,17 +53,49 @@ async fn main() {
 
     let db = Arc::new(
         LibraDB::open(
-            opt.global.db_dir,
+            &opt.global.db_dir,
             false, /* read_only */
             None,  /* pruner */
+            None,  /* ledger_pruner */
+            false, /* account_state_history */
         )
         .expect("Failed opening DB."),
     );
-    let storage = Arc::new(LocalFs::new_with_opt(opt.storage));
-    StateSnapshotRestoreController::new(opt.state_snapshot, storage, db)
-        .run()
-        .await
-        .expect("Failed restoring state_snapshot.");
+    let verifier = opt.global.verifier(Arc::clone(&db));
 
-    println!("Finished restoring account state.");
+    match opt.restore_type {
+        RestoreType::EpochEnding { opt, storage } => {
+            let storage = storage
+                .init_storage()
+                .await
+                .expect("Failed initializing storage.");
+            EpochEndingRestoreController::new(opt, storage, db, verifier)
+                .run()
+                .await
+                .expect("Failed restoring epoch ending ledger infos.");
+            println!("Finished restoring epoch ending ledger infos.");
+        }
+        RestoreType::StateSnapshot { opt, storage } => {
+            let storage = storage
+                .init_storage()
+                .await
+                .expect("Failed initializing storage.");
+            StateSnapshotRestoreController::new(opt, storage, db, verifier)
+                .run()
+                .await
+                .expect("Failed restoring state_snapshot.");
+            println!("Finished restoring account state.");
+        }
+        RestoreType::Transaction { opt, storage } => {
+            let storage = storage
+                .init_storage()
+                .await
+                .expect("Failed initializing storage.");
+            TransactionRestoreController::new(opt, storage, db, verifier)
+                .run()
+                .await
+                .expect("Failed restoring transactions.");
+            println!("Finished restoring transactions.");
+        }
+    }
 }
@@ -0,0 +1,308 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup::{
+        BackupServiceClient, GlobalBackupOpt, StateSnapshotBackupController, StateSnapshotBackupOpt,
+    },
+    metadata::{
+        cache::{sync_and_load, MetadataCacheOpt},
+        Metadata,
+    },
+    restore::{
+        EpochEndingRestoreController, EpochEndingRestoreOpt, StateSnapshotRestoreController,
+        StateSnapshotRestoreOpt, TransactionRestoreController, TransactionRestoreOpt,
+    },
+    storage::BackupStorage,
+    verifier::RestoreVerifier,
+};
+use anyhow::{ensure, format_err, Result};
+use libra_types::{ledger_info::LedgerInfoWithSignatures, transaction::Version};
+use libradb::LibraDB;
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub struct BackupCoordinatorOpt {
+    #[structopt(
+        long = "state-snapshot-interval",
+        help = "Take a state snapshot every this many versions."
+    )]
+    pub state_snapshot_interval: u64,
+
+    #[structopt(
+        long = "poll-interval-secs",
+        default_value = "60",
+        help = "Seconds to wait between polling the backup service for the latest DB state."
+    )]
+    pub poll_interval_secs: u64,
+}
+
+/// Keeps backing up a running node by polling its backup service, taking a state snapshot
+/// whenever the committed version passes a multiple of `state_snapshot_interval`.
+/// What's been backed up is learned from the metadata in the backup storage, so the coordinator
+/// picks up where it left off after restarts. Backups are named uniquely, so that retrying one
+/// doesn't collide with whatever a failed or interrupted attempt left in the storage.
+pub struct BackupCoordinator {
+    state_snapshot_interval: u64,
+    poll_interval: Duration,
+    global_opt: GlobalBackupOpt,
+    client: Arc<BackupServiceClient>,
+    storage: Arc<dyn BackupStorage>,
+}
+
+impl BackupCoordinator {
+    pub fn new(
+        opt: BackupCoordinatorOpt,
+        global_opt: GlobalBackupOpt,
+        client: Arc<BackupServiceClient>,
+        storage: Arc<dyn BackupStorage>,
+    ) -> Self {
+        Self {
+            state_snapshot_interval: opt.state_snapshot_interval,
+            poll_interval: Duration::from_secs(opt.poll_interval_secs),
+            global_opt,
+            client,
+            storage,
+        }
+    }
+
+    /// Runs forever, unless the coordinator fails to learn about existing backups on start.
+    /// Failures on individual backups are logged and retried in the next round.
+    pub async fn run(self) -> Result<()> {
+        ensure!(
+            self.state_snapshot_interval > 0,
+            "state_snapshot_interval must be positive."
+        );
+
+        let mut last_snapshot_version = self.load_last_state_snapshot_version().await?;
+        println!(
+            "Backup coordinator started. Last state snapshot version: {:?}",
+            last_snapshot_version
+        );
+
+        loop {
+            match self.run_once(last_snapshot_version).await {
+                Ok(version) => last_snapshot_version = version,
+                Err(e) => eprintln!("Backup coordinator error: {:?}", e),
+            }
+            tokio::time::delay_for(self.poll_interval).await;
+        }
+    }
+
+    /// Takes a state snapshot if one is due, returns the version of the latest state snapshot.
+    pub async fn run_once(
+        &self,
+        last_snapshot_version: Option<Version>,
+    ) -> Result<Option<Version>> {
+        let db_state = match self.client.get_db_state().await? {
+            Some(db_state) => db_state,
+            None => {
+                println!("DB is empty, nothing to backup.");
+                return Ok(last_snapshot_version);
+            }
+        };
+
+        match next_state_snapshot_version(
+            last_snapshot_version,
+            db_state.committed_version,
+            self.state_snapshot_interval,
+        ) {
+            Some(version) => {
+                println!(
+                    "Taking state snapshot at version {}. DB epoch: {}, committed version: {}",
+                    version, db_state.epoch, db_state.committed_version,
+                );
+                let manifest = StateSnapshotBackupController::new(
+                    StateSnapshotBackupOpt { version },
+                    self.global_opt.clone(),
+                    Arc::clone(&self.client),
+                    Arc::clone(&self.storage),
+                )
+                .with_name_suffix(unique_suffix()?)
+                .run()
+                .await?;
+                println!("State snapshot taken. Manifest saved to {}", manifest);
+                Ok(Some(version))
+            }
+            None => Ok(last_snapshot_version),
+        }
+    }
+
+    pub async fn load_last_state_snapshot_version(&self) -> Result<Option<Version>> {
+        Ok(Metadata::load_all(&*self.storage)
+            .await?
+            .into_iter()
+            .filter_map(|metadata| match metadata {
+                Metadata::StateSnapshotBackup(s) => Some(s.version),
+                _ => None,
+            })
+            .max())
+    }
+}
+
+#[derive(StructOpt)]
+pub struct RestoreCoordinatorOpt {
+    #[structopt(flatten)]
+    pub metadata_cache_opt: MetadataCacheOpt,
+
+    #[structopt(long = "target-version", help = "Version to restore the DB to.")]
+    pub target_version: Version,
+}
+
+/// Restores an empty DB to `target_version` with whatever is found in the backup storage:
+/// the latest state snapshot at or before the target version is restored, and the transactions
+/// after it are replayed by the executor.
+pub struct RestoreCoordinator {
+    metadata_cache_opt: MetadataCacheOpt,
+    target_version: Version,
+    storage: Arc<dyn BackupStorage>,
+    db: Arc<LibraDB>,
+    verifier: Option<Arc<RestoreVerifier>>,
+}
+
+impl RestoreCoordinator {
+    pub fn new(
+        opt: RestoreCoordinatorOpt,
+        storage: Arc<dyn BackupStorage>,
+        db: Arc<LibraDB>,
+        verifier: Option<Arc<RestoreVerifier>>,
+    ) -> Self {
+        Self {
+            metadata_cache_opt: opt.metadata_cache_opt,
+            target_version: opt.target_version,
+            storage,
+            db,
+            verifier,
+        }
+    }
+
+    pub async fn run(self) -> Result<()> {
+        let metadata_view = sync_and_load(&self.metadata_cache_opt, &*self.storage).await?;
+        let state_snapshot = metadata_view
+            .select_state_snapshot(self.target_version)
+            .ok_or_else(|| {
+                format_err!(
+                    "No state snapshot found at or before target version {}.",
+                    self.target_version,
+                )
+            })?;
+        let snapshot_version = state_snapshot.version;
+        let epoch_ending_backups =
+            metadata_view.select_epoch_ending_backups(self.target_version)?;
+        println!(
+            "Restoring to version {} from state snapshot at version {}.",
+            self.target_version, snapshot_version,
+        );
+
+        // Ledger infos after the state snapshot are not saved but kept for replaying, since the
+        // DB can't have ledger infos beyond its latest transaction.
+        let mut epoch_history: Vec<LedgerInfoWithSignatures> = Vec::new();
+        for backup in epoch_ending_backups {
+            let previous_epoch_ending = match backup.first_epoch {
+                0 => None,
+                epoch => epoch_history.get((epoch - 1) as usize).cloned(),
+            };
+            let lis = EpochEndingRestoreController::new(
+                EpochEndingRestoreOpt {
+                    manifest_handle: backup.manifest,
+                    target_version: Some(snapshot_version),
+                },
+                Arc::clone(&self.storage),
+                Arc::clone(&self.db),
+                self.verifier.clone(),
+            )
+            .with_previous_epoch_ending(previous_epoch_ending)
+            .run()
+            .await?;
+            // Backups can overlap.
+            for li in lis {
+                if li.ledger_info().epoch() == epoch_history.len() as u64 {
+                    epoch_history.push(li);
+                }
+            }
+        }
+
+        let epoch_history = Arc::new(epoch_history);
+        StateSnapshotRestoreController::new(
+            StateSnapshotRestoreOpt {
+                manifest_handle: state_snapshot.manifest,
+                version: snapshot_version,
+            },
+            Arc::clone(&self.storage),
+            Arc::clone(&self.db),
+            self.verifier.clone(),
+        )
+        .with_epoch_history(Arc::clone(&epoch_history))
+        .run()
+        .await?;
+
+        // Transactions are restored from the latest ledger info saved, since the DB needs the
+        // transaction info at that version to start up the executor.
+        let first_version = epoch_history
+            .iter()
+            .map(|li| li.ledger_info().version())
+            .filter(|version| *version <= snapshot_version)
+            .max()
+            .ok_or_else(|| format_err!("No epoch ending at or before the state snapshot."))?;
+        let transaction_backups =
+            metadata_view.select_transaction_backups(first_version, self.target_version)?;
+        for backup in transaction_backups {
+            TransactionRestoreController::new(
+                TransactionRestoreOpt {
+                    manifest_handle: backup.manifest,
+                    target_version: Some(self.target_version),
+                },
+                Arc::clone(&self.storage),
+                Arc::clone(&self.db),
+                self.verifier.clone(),
+            )
+            .with_replay(snapshot_version, Arc::clone(&epoch_history))
+            .run()
+            .await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the current time in nanoseconds, which tells attempts at the same backup apart.
+fn unique_suffix() -> Result<String> {
+    Ok(SystemTime::now()
+        .duration_since(UNIX_EPOCH)?
+        .as_nanos()
+        .to_string())
+}
+
+/// Returns the latest multiple of `interval` that's committed but not yet backed up, if any.
+fn next_state_snapshot_version(
+    last_snapshot_version: Option<Version>,
+    committed_version: Version,
+    interval: u64,
+) -> Option<Version> {
+    let target = committed_version / interval * interval;
+    match last_snapshot_version {
+        Some(last) if last >= target => None,
+        _ => Some(target),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::next_state_snapshot_version;
+
+    #[test]
+    fn test_next_state_snapshot_version() {
+        assert_eq!(next_state_snapshot_version(None, 0, 100), Some(0));
+        assert_eq!(next_state_snapshot_version(None, 250, 100), Some(200));
+        assert_eq!(next_state_snapshot_version(Some(200), 250, 100), None);
+        assert_eq!(next_state_snapshot_version(Some(200), 299, 100), None);
+        assert_eq!(next_state_snapshot_version(Some(200), 300, 100), Some(300));
+        assert_eq!(next_state_snapshot_version(Some(200), 720, 100), Some(700));
+        // Snapshots taken manually at versions not aligned with the interval count too.
+        assert_eq!(next_state_snapshot_version(Some(250), 299, 100), None);
+    }
+}
@@ -2,9 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod backup;
+pub mod coordinator;
 pub mod manifest;
+pub mod metadata;
 pub mod restore;
 pub mod storage;
+pub mod verifier;
 
 #[cfg(test)]
 mod tests;
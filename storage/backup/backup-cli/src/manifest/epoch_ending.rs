@@ -0,0 +1,77 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::storage::FileHandle;
+use anyhow::{ensure, Result};
+use libra_types::waypoint::Waypoint;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize)]
+pub struct EpochEndingChunk {
+    /// The first epoch ended by a ledger info in this chunk.
+    pub first_epoch: u64,
+    /// The last epoch ended by a ledger info in this chunk.
+    pub last_epoch: u64,
+    /// Repeated `len(record) + record` where `record` is LCS serialized
+    /// `LedgerInfoWithSignatures`, each of which is the last one of an epoch, carrying the
+    /// validator set of the next epoch.
+    pub ledger_infos: FileHandle,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct EpochEndingBackup {
+    /// The first epoch whose ending ledger info is in this backup.
+    pub first_epoch: u64,
+    /// The last epoch whose ending ledger info is in this backup.
+    pub last_epoch: u64,
+    /// Waypoints of all the ledger infos in this backup, in the order of epochs. These are
+    /// re-calculated and checked against the ledger infos upon restore.
+    pub waypoints: Vec<Waypoint>,
+    /// All the ledger infos in chunks.
+    pub chunks: Vec<EpochEndingChunk>,
+}
+
+impl EpochEndingBackup {
+    /// Makes sure the chunks are consecutive and cover exactly the epochs claimed.
+    pub fn verify(&self) -> Result<()> {
+        ensure!(
+            self.first_epoch <= self.last_epoch,
+            "Bad epoch range: [{}, {}]",
+            self.first_epoch,
+            self.last_epoch,
+        );
+        ensure!(
+            self.waypoints.len() as u64 == self.last_epoch - self.first_epoch + 1,
+            "Number of waypoints ({}) doesn't match number of epochs ([{}, {}])",
+            self.waypoints.len(),
+            self.first_epoch,
+            self.last_epoch,
+        );
+        ensure!(!self.chunks.is_empty(), "No chunks.");
+
+        let mut next_epoch = self.first_epoch;
+        for chunk in &self.chunks {
+            ensure!(
+                chunk.first_epoch == next_epoch,
+                "Chunk ranges not continuous. Expected first epoch: {}, actual: {}.",
+                next_epoch,
+                chunk.first_epoch,
+            );
+            ensure!(
+                chunk.last_epoch >= chunk.first_epoch,
+                "Chunk range invalid. [{}, {}]",
+                chunk.first_epoch,
+                chunk.last_epoch,
+            );
+            next_epoch = chunk.last_epoch + 1;
+        }
+        ensure!(
+            next_epoch == self.last_epoch + 1,
+            "Chunks don't cover all epochs. Last epoch in chunks: {}, expected: {}",
+            next_epoch - 1,
+            self.last_epoch,
+        );
+
+        Ok(())
+    }
+}
@@ -1,4 +1,6 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod epoch_ending;
 pub mod state_snapshot;
+pub mod transaction;
@@ -0,0 +1,72 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::storage::FileHandle;
+use anyhow::{ensure, Result};
+use libra_types::transaction::Version;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize)]
+pub struct TransactionChunk {
+    /// Version of the first transaction in this chunk.
+    pub first_version: Version,
+    /// Version of the last transaction in this chunk.
+    pub last_version: Version,
+    /// Repeated `len(record) + record` where `record` is LCS serialized tuple
+    /// `(Transaction, TransactionInfo, Vec<ContractEvent>)`
+    pub transactions: FileHandle,
+    /// LCS serialized `(TransactionAccumulatorRangeProof, LedgerInfoWithSignatures)`.
+    /// The `TransactionAccumulatorRangeProof` links the transactions to the
+    /// `LedgerInfoWithSignatures`, and the `LedgerInfoWithSignatures` can be verified by the
+    /// signatures it carries, against the validator set in the epoch. (Hence proper
+    /// `EpochEndingBackup` is needed for verification.)
+    pub proof: FileHandle,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct TransactionBackup {
+    /// Version of the first transaction in this backup.
+    pub first_version: Version,
+    /// Version of the last transaction in this backup.
+    pub last_version: Version,
+    /// All transactions in chunks.
+    pub chunks: Vec<TransactionChunk>,
+}
+
+impl TransactionBackup {
+    /// Makes sure the chunks are consecutive and cover exactly the versions claimed.
+    pub fn verify(&self) -> Result<()> {
+        ensure!(
+            self.first_version <= self.last_version,
+            "Bad version range: [{}, {}]",
+            self.first_version,
+            self.last_version,
+        );
+        ensure!(!self.chunks.is_empty(), "No chunks.");
+
+        let mut next_version = self.first_version;
+        for chunk in &self.chunks {
+            ensure!(
+                chunk.first_version == next_version,
+                "Chunk ranges not continuous. Expected first version: {}, actual: {}.",
+                next_version,
+                chunk.first_version,
+            );
+            ensure!(
+                chunk.last_version >= chunk.first_version,
+                "Chunk range invalid. [{}, {}]",
+                chunk.first_version,
+                chunk.last_version,
+            );
+            next_version = chunk.last_version + 1;
+        }
+        ensure!(
+            next_version == self.last_version + 1,
+            "Chunks don't cover all versions. Last version in chunks: {}, expected: {}",
+            next_version - 1,
+            self.last_version,
+        );
+
+        Ok(())
+    }
+}
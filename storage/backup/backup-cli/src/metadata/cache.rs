@@ -0,0 +1,111 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    metadata::{view::MetadataView, Metadata},
+    storage::{BackupStorage, FileHandle},
+};
+use anyhow::{anyhow, Result};
+use libra_crypto::HashValue;
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+};
+use structopt::StructOpt;
+use tokio::{
+    fs::{create_dir_all, read_dir, read_to_string, remove_file, rename, OpenOptions},
+    io::{AsyncReadExt, AsyncWriteExt},
+};
+
+#[derive(StructOpt)]
+pub struct MetadataCacheOpt {
+    #[structopt(
+        long = "metadata-cache-dir",
+        parse(from_os_str),
+        help = "Local dir to cache metadata files in, so that files already cached are not \
+        downloaded again from the backup storage on each run. [Defaults to a dir under the \
+        system temporary dir.]"
+    )]
+    dir: Option<PathBuf>,
+}
+
+impl MetadataCacheOpt {
+    const DEFAULT_DIR: &'static str = "libra_backup_metadata_cache";
+
+    pub fn new(dir: Option<PathBuf>) -> Self {
+        Self { dir }
+    }
+
+    fn cache_dir(&self) -> PathBuf {
+        self.dir
+            .clone()
+            .unwrap_or_else(|| std::env::temp_dir().join(Self::DEFAULT_DIR))
+    }
+}
+
+/// Syncs the local cache with the metadata files in the backup storage, then loads all metadata
+/// from the cache.
+/// Metadata files are never modified once saved, so the ones already cached are not downloaded
+/// again, and the ones no longer in the backup storage are removed from the cache.
+pub async fn sync_and_load(
+    opt: &MetadataCacheOpt,
+    storage: &dyn BackupStorage,
+) -> Result<MetadataView> {
+    let cache_dir = opt.cache_dir();
+    create_dir_all(&cache_dir).await?;
+
+    let mut cached = HashSet::new();
+    let mut entries = read_dir(&cache_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        cached.insert(
+            entry
+                .file_name()
+                .into_string()
+                .map_err(|s| anyhow!("into_string failed for OsString '{:?}'", s))?,
+        );
+    }
+
+    let remote: HashMap<String, FileHandle> = storage
+        .list_metadata_files()
+        .await?
+        .into_iter()
+        .map(|file_handle| (cache_file_name(&file_handle), file_handle))
+        .collect();
+
+    for stale in cached.iter().filter(|name| !remote.contains_key(*name)) {
+        remove_file(cache_dir.join(stale)).await?;
+    }
+
+    let mut metadata_vec = Vec::new();
+    for (name, file_handle) in &remote {
+        let path = cache_dir.join(name);
+        if !cached.contains(name) {
+            let mut buf = Vec::new();
+            storage
+                .open_for_read(file_handle)
+                .await?
+                .read_to_end(&mut buf)
+                .await?;
+            // Written aside then renamed, so that a partially written file is never taken as
+            // cached. A left over temporary file is removed as stale on the next run.
+            let tmp_path = cache_dir.join(format!("{}.tmp", name));
+            let mut file = OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(&tmp_path)
+                .await?;
+            file.write_all(&buf).await?;
+            file.shutdown().await?;
+            rename(&tmp_path, &path).await?;
+        }
+        metadata_vec.extend(Metadata::from_text_lines(&read_to_string(&path).await?)?);
+    }
+
+    Ok(metadata_vec.into())
+}
+
+/// File handles can contain anything, hence the hash of it is used as the name of the local file.
+fn cache_file_name(file_handle: &FileHandle) -> String {
+    HashValue::sha3_256_of(file_handle.as_bytes()).to_hex()
+}
@@ -0,0 +1,128 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod cache;
+pub mod view;
+
+#[cfg(test)]
+mod tests;
+
+use crate::storage::{BackupStorage, FileHandle};
+use anyhow::Result;
+use libra_types::transaction::Version;
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncReadExt;
+
+/// A line of metadata saved to the backup storage upon finishing each backup, so that what's
+/// been backed up can be found out without scanning the storage for manifests.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Metadata {
+    EpochEndingBackup(EpochEndingBackupMeta),
+    StateSnapshotBackup(StateSnapshotBackupMeta),
+    TransactionBackup(TransactionBackupMeta),
+}
+
+impl Metadata {
+    pub fn new_epoch_ending_backup(
+        first_epoch: u64,
+        last_epoch: u64,
+        first_version: Version,
+        last_version: Version,
+        manifest: FileHandle,
+    ) -> Self {
+        Self::EpochEndingBackup(EpochEndingBackupMeta {
+            first_epoch,
+            last_epoch,
+            first_version,
+            last_version,
+            manifest,
+        })
+    }
+
+    pub fn new_state_snapshot_backup(version: Version, manifest: FileHandle) -> Self {
+        Self::StateSnapshotBackup(StateSnapshotBackupMeta { version, manifest })
+    }
+
+    pub fn new_transaction_backup(
+        first_version: Version,
+        last_version: Version,
+        manifest: FileHandle,
+    ) -> Self {
+        Self::TransactionBackup(TransactionBackupMeta {
+            first_version,
+            last_version,
+            manifest,
+        })
+    }
+
+    /// Name of the metadata line, unique to the backup it describes.
+    pub fn name(&self) -> String {
+        match self {
+            Self::EpochEndingBackup(e) => {
+                format!("epoch_ending_{}-{}.meta", e.first_epoch, e.last_epoch)
+            }
+            Self::StateSnapshotBackup(s) => format!("state_snapshot_ver_{}.meta", s.version),
+            Self::TransactionBackup(t) => {
+                format!("transaction_{}-{}.meta", t.first_version, t.last_version)
+            }
+        }
+    }
+
+    pub fn to_text_line(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub async fn save_to(&self, storage: &dyn BackupStorage) -> Result<FileHandle> {
+        storage
+            .save_metadata_line(&self.name(), &self.to_text_line()?)
+            .await
+    }
+
+    /// Reads all metadata lines saved in the storage.
+    pub async fn load_all(storage: &dyn BackupStorage) -> Result<Vec<Metadata>> {
+        let mut res = Vec::new();
+        for file_handle in storage.list_metadata_files().await? {
+            let mut buf = String::new();
+            storage
+                .open_for_read(&file_handle)
+                .await?
+                .read_to_string(&mut buf)
+                .await?;
+            res.extend(Self::from_text_lines(&buf)?);
+        }
+        Ok(res)
+    }
+
+    /// Parses the content of a metadata file, which can have multiple lines.
+    pub fn from_text_lines(content: &str) -> Result<Vec<Metadata>> {
+        content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Ok(serde_json::from_str(line)?))
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct EpochEndingBackupMeta {
+    pub first_epoch: u64,
+    pub last_epoch: u64,
+    /// Version of the ledger info ending `first_epoch`.
+    pub first_version: Version,
+    /// Version of the ledger info ending `last_epoch`.
+    pub last_version: Version,
+    pub manifest: FileHandle,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StateSnapshotBackupMeta {
+    pub version: Version,
+    pub manifest: FileHandle,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TransactionBackupMeta {
+    pub first_version: Version,
+    pub last_version: Version,
+    pub manifest: FileHandle,
+}
@@ -0,0 +1,74 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::metadata::{view::MetadataView, Metadata};
+
+fn view() -> MetadataView {
+    vec![
+        Metadata::new_epoch_ending_backup(0, 2, 0, 20, "e0-2".to_string()),
+        Metadata::new_epoch_ending_backup(1, 4, 10, 40, "e1-4".to_string()),
+        Metadata::new_epoch_ending_backup(5, 6, 50, 60, "e5-6".to_string()),
+        Metadata::new_epoch_ending_backup(8, 9, 80, 90, "e8-9".to_string()),
+        Metadata::new_state_snapshot_backup(30, "s30".to_string()),
+        Metadata::new_state_snapshot_backup(10, "s10".to_string()),
+        Metadata::new_state_snapshot_backup(70, "s70".to_string()),
+        Metadata::new_transaction_backup(0, 49, "t0-49".to_string()),
+        Metadata::new_transaction_backup(0, 29, "t0-29".to_string()),
+        Metadata::new_transaction_backup(30, 59, "t30-59".to_string()),
+        Metadata::new_transaction_backup(40, 79, "t40-79".to_string()),
+        Metadata::new_transaction_backup(90, 99, "t90-99".to_string()),
+    ]
+    .into()
+}
+
+#[test]
+fn test_select_state_snapshot() {
+    let view = view();
+    assert_eq!(view.select_state_snapshot(9), None);
+    assert_eq!(view.select_state_snapshot(10).unwrap().manifest, "s10");
+    assert_eq!(view.select_state_snapshot(69).unwrap().manifest, "s30");
+    assert_eq!(view.select_state_snapshot(1000).unwrap().manifest, "s70");
+}
+
+#[test]
+fn test_select_transaction_backups() {
+    let view = view();
+    let manifests = |first, last| {
+        view.select_transaction_backups(first, last)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.manifest)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(manifests(0, 0), vec!["t0-49"]);
+    assert_eq!(manifests(20, 70), vec!["t0-49", "t40-79"]);
+    assert_eq!(manifests(50, 59), vec!["t40-79"]);
+    assert!(view.select_transaction_backups(70, 85).is_err());
+    assert!(view.select_transaction_backups(100, 100).is_err());
+}
+
+#[test]
+fn test_select_epoch_ending_backups() {
+    let view = view();
+    let manifests = |target| {
+        view.select_epoch_ending_backups(target)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.manifest)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(manifests(20), vec!["e0-2"]);
+    assert_eq!(manifests(21), vec!["e0-2", "e1-4"]);
+    // No backup has epoch 7.
+    assert_eq!(manifests(1000), vec!["e0-2", "e1-4", "e5-6"]);
+
+    let view: MetadataView = vec![Metadata::new_epoch_ending_backup(
+        1,
+        2,
+        10,
+        20,
+        "e1-2".to_string(),
+    )]
+    .into();
+    assert!(view.select_epoch_ending_backups(20).is_err());
+}
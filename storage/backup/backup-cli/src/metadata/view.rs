@@ -0,0 +1,97 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::metadata::{
+    EpochEndingBackupMeta, Metadata, StateSnapshotBackupMeta, TransactionBackupMeta,
+};
+use anyhow::{ensure, format_err, Result};
+use libra_types::transaction::Version;
+
+/// All backups known from the metadata, sorted, for choosing what to restore.
+#[derive(Debug, Default)]
+pub struct MetadataView {
+    epoch_ending_backups: Vec<EpochEndingBackupMeta>,
+    state_snapshot_backups: Vec<StateSnapshotBackupMeta>,
+    transaction_backups: Vec<TransactionBackupMeta>,
+}
+
+impl From<Vec<Metadata>> for MetadataView {
+    fn from(metadata_vec: Vec<Metadata>) -> Self {
+        let mut view = Self::default();
+        for metadata in metadata_vec {
+            match metadata {
+                Metadata::EpochEndingBackup(e) => view.epoch_ending_backups.push(e),
+                Metadata::StateSnapshotBackup(s) => view.state_snapshot_backups.push(s),
+                Metadata::TransactionBackup(t) => view.transaction_backups.push(t),
+            }
+        }
+        view.epoch_ending_backups
+            .sort_by_key(|e| (e.first_epoch, e.last_epoch));
+        view.state_snapshot_backups.sort_by_key(|s| s.version);
+        view.transaction_backups
+            .sort_by_key(|t| (t.first_version, t.last_version));
+        view
+    }
+}
+
+impl MetadataView {
+    /// Returns the latest state snapshot at or before `target_version`.
+    pub fn select_state_snapshot(
+        &self,
+        target_version: Version,
+    ) -> Option<StateSnapshotBackupMeta> {
+        self.state_snapshot_backups
+            .iter()
+            .rev()
+            .find(|s| s.version <= target_version)
+            .cloned()
+    }
+
+    /// Returns transaction backups covering `[first_version, last_version]` without gaps,
+    /// preferring the ones reaching further so that fewer backups are restored.
+    pub fn select_transaction_backups(
+        &self,
+        first_version: Version,
+        last_version: Version,
+    ) -> Result<Vec<TransactionBackupMeta>> {
+        let mut res = Vec::new();
+        let mut next_version = first_version;
+        while next_version <= last_version {
+            let backup = self
+                .transaction_backups
+                .iter()
+                .filter(|t| t.first_version <= next_version && t.last_version >= next_version)
+                .max_by_key(|t| t.last_version)
+                .ok_or_else(|| {
+                    format_err!("No transaction backup found for version {}.", next_version)
+                })?;
+            res.push(backup.clone());
+            next_version = backup.last_version + 1;
+        }
+        Ok(res)
+    }
+
+    /// Returns epoch ending backups covering epochs from 0 onwards without gaps, until reaching
+    /// one that ends at or after `target_version`, or running out of continuous backups.
+    pub fn select_epoch_ending_backups(
+        &self,
+        target_version: Version,
+    ) -> Result<Vec<EpochEndingBackupMeta>> {
+        let mut res: Vec<EpochEndingBackupMeta> = Vec::new();
+        let mut next_epoch = 0;
+        while let Some(backup) = self
+            .epoch_ending_backups
+            .iter()
+            .filter(|e| e.first_epoch <= next_epoch && e.last_epoch >= next_epoch)
+            .max_by_key(|e| e.last_epoch)
+        {
+            res.push(backup.clone());
+            if backup.last_version >= target_version {
+                break;
+            }
+            next_epoch = backup.last_epoch + 1;
+        }
+        ensure!(!res.is_empty(), "No epoch ending backup found for epoch 0.");
+        Ok(res)
+    }
+}
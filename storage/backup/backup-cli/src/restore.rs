@@ -2,17 +2,37 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    manifest::state_snapshot::StateSnapshotBackup,
-    storage::{BackupStorage, FileHandle},
+    manifest::{
+        epoch_ending::EpochEndingBackup, state_snapshot::StateSnapshotBackup,
+        transaction::TransactionBackup,
+    },
+    storage::{BackupStorage, FileHandle, FileHandleRef},
+    verifier::RestoreVerifier,
     ReadRecordBytes,
 };
-use anyhow::Result;
-use libra_crypto::HashValue;
+use anyhow::{ensure, format_err, Result};
+use executor::Executor;
+use executor_types::ChunkExecutor;
+use libra_crypto::{
+    hash::{CryptoHash, ACCUMULATOR_PLACEHOLDER_HASH},
+    HashValue,
+};
 use libra_types::{
-    account_state_blob::AccountStateBlob, proof::SparseMerkleRangeProof, transaction::Version,
+    account_state_blob::AccountStateBlob,
+    contract_event::ContractEvent,
+    ledger_info::LedgerInfoWithSignatures,
+    proof::{
+        SparseMerkleRangeProof, TransactionAccumulatorInternalNode,
+        TransactionAccumulatorRangeProof, TransactionInfoWithProof, TransactionListProof,
+    },
+    transaction::{Transaction, TransactionInfo, TransactionListWithProof, Version},
+    waypoint::Waypoint,
 };
+use libra_vm::LibraVM;
 use libradb::LibraDB;
-use std::{path::PathBuf, sync::Arc};
+use serde::de::DeserializeOwned;
+use std::{cmp::min, path::PathBuf, sync::Arc};
+use storage_interface::{DbReader, DbReaderWriter, DbWriter};
 use structopt::StructOpt;
 use tokio::io::AsyncReadExt;
 
@@ -20,6 +40,28 @@ use tokio::io::AsyncReadExt;
 pub struct GlobalRestoreOpt {
     #[structopt(long = "target-db-dir", parse(from_os_str))]
     pub db_dir: PathBuf,
+
+    #[structopt(
+        long = "trust-waypoint",
+        help = "Trusted waypoint, can be repeated. Providing any enables verification: every \
+        LedgerInfo in the backups must match a trusted waypoint or be signed by the validator \
+        set of its epoch."
+    )]
+    pub trust_waypoints: Vec<Waypoint>,
+}
+
+impl GlobalRestoreOpt {
+    /// Returns a verifier if running in verification mode.
+    pub fn verifier(&self, db: Arc<LibraDB>) -> Option<Arc<RestoreVerifier>> {
+        if self.trust_waypoints.is_empty() {
+            None
+        } else {
+            Some(Arc::new(RestoreVerifier::new(
+                self.trust_waypoints.clone(),
+                db,
+            )))
+        }
+    }
 }
 
 #[derive(StructOpt)]
@@ -33,8 +75,10 @@ pub struct StateSnapshotRestoreOpt {
 pub struct StateSnapshotRestoreController {
     storage: Arc<dyn BackupStorage>,
     db: Arc<LibraDB>,
+    verifier: Option<Arc<RestoreVerifier>>,
     version: Version,
     manifest_handle: FileHandle,
+    epoch_history: Option<Arc<Vec<LedgerInfoWithSignatures>>>,
 }
 
 impl StateSnapshotRestoreController {
@@ -42,15 +86,25 @@ impl StateSnapshotRestoreController {
         opt: StateSnapshotRestoreOpt,
         storage: Arc<dyn BackupStorage>,
         db: Arc<LibraDB>,
+        verifier: Option<Arc<RestoreVerifier>>,
     ) -> Self {
         Self {
             storage,
             db,
+            verifier,
             version: opt.version,
             manifest_handle: opt.manifest_handle,
+            epoch_history: None,
         }
     }
 
+    /// Verifies the ledger info proving the snapshot against `epoch_history`, the epoch ending
+    /// ledger infos from epoch 0 on, instead of those in the DB.
+    pub fn with_epoch_history(mut self, epoch_history: Arc<Vec<LedgerInfoWithSignatures>>) -> Self {
+        self.epoch_history = Some(epoch_history);
+        self
+    }
+
     pub async fn run(self) -> Result<()> {
         let mut manifest_bytes = Vec::new();
         self.storage
@@ -60,6 +114,23 @@ impl StateSnapshotRestoreController {
             .await?;
         let manifest: StateSnapshotBackup = serde_json::from_slice(&manifest_bytes)?;
 
+        // Verify the root hash before writing anything to the DB.
+        let (txn_info_with_proof, li): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
+            load_lcs_file(&*self.storage, &manifest.proof).await?;
+        if let Some(verifier) = &self.verifier {
+            match &self.epoch_history {
+                Some(epoch_history) => verifier.verify_with_epoch_history(&li, epoch_history)?,
+                None => verifier.verify(&li)?,
+            }
+        }
+        txn_info_with_proof.verify(li.ledger_info(), manifest.version)?;
+        ensure!(
+            txn_info_with_proof.transaction_info().state_root_hash() == manifest.root_hash,
+            "Root hash mismatch with that in proof. root hash: {:x}, expected: {:x}",
+            manifest.root_hash,
+            txn_info_with_proof.transaction_info().state_root_hash(),
+        );
+
         let mut receiver = self
             .db
             .get_state_restore_receiver(self.version, manifest.root_hash)?;
@@ -101,3 +172,494 @@ impl StateSnapshotRestoreController {
         Ok(proof)
     }
 }
+
+#[derive(StructOpt)]
+pub struct TransactionRestoreOpt {
+    #[structopt(long = "transaction-manifest")]
+    pub manifest_handle: FileHandle,
+
+    #[structopt(
+        long = "target-version",
+        help = "Transactions after this version are not restored."
+    )]
+    pub target_version: Option<Version>,
+}
+
+pub struct TransactionRestoreController {
+    storage: Arc<dyn BackupStorage>,
+    db: Arc<LibraDB>,
+    verifier: Option<Arc<RestoreVerifier>>,
+    manifest_handle: FileHandle,
+    target_version: Version,
+    replay: Option<ReplayOpt>,
+}
+
+/// Transactions after `from_version` are replayed by the executor instead of being saved
+/// directly, which requires the state at `from_version` to be in the DB.
+struct ReplayOpt {
+    from_version: Version,
+    /// Epoch ending ledger infos from epoch 0 on, which are needed for splitting chunks at epoch
+    /// boundaries, and verifying ledger infos in epochs not yet in the DB.
+    epoch_history: Arc<Vec<LedgerInfoWithSignatures>>,
+}
+
+impl TransactionRestoreController {
+    pub fn new(
+        opt: TransactionRestoreOpt,
+        storage: Arc<dyn BackupStorage>,
+        db: Arc<LibraDB>,
+        verifier: Option<Arc<RestoreVerifier>>,
+    ) -> Self {
+        Self {
+            storage,
+            db,
+            verifier,
+            manifest_handle: opt.manifest_handle,
+            target_version: opt.target_version.unwrap_or(Version::max_value()),
+            replay: None,
+        }
+    }
+
+    /// Replays transactions after `from_version` with the executor, instead of saving them
+    /// directly. `epoch_history` must have all the epoch ending ledger infos up to the target
+    /// version.
+    pub fn with_replay(
+        mut self,
+        from_version: Version,
+        epoch_history: Arc<Vec<LedgerInfoWithSignatures>>,
+    ) -> Self {
+        self.replay = Some(ReplayOpt {
+            from_version,
+            epoch_history,
+        });
+        self
+    }
+
+    pub async fn run(self) -> Result<()> {
+        let manifest: TransactionBackup =
+            load_json_file(&*self.storage, &self.manifest_handle).await?;
+        manifest.verify()?;
+        ensure!(
+            self.target_version >= manifest.first_version,
+            "Target version {} is before the first version in the backup {}.",
+            self.target_version,
+            manifest.first_version,
+        );
+
+        let mut executor = None;
+        for (idx, chunk) in manifest.chunks.into_iter().enumerate() {
+            if chunk.first_version > self.target_version {
+                break;
+            }
+
+            let (txns, txn_infos, events) = self.read_transaction_chunk(chunk.transactions).await?;
+            ensure!(
+                txns.len() as u64 == chunk.last_version - chunk.first_version + 1,
+                "Number of items in chunk doesn't match that in manifest. first_version: {}, \
+                 last_version: {}, items in chunk: {}",
+                chunk.first_version,
+                chunk.last_version,
+                txns.len(),
+            );
+
+            let (range_proof, ledger_info): (
+                TransactionAccumulatorRangeProof,
+                LedgerInfoWithSignatures,
+            ) = load_lcs_file(&*self.storage, &chunk.proof).await?;
+            self.verify_ledger_info(&ledger_info)?;
+
+            // Make sure the transactions are linked to the ledger info by the range proof.
+            let txn_list_proof = TransactionListProof::new(range_proof, txn_infos);
+            let txn_hashes: Vec<HashValue> = txns.iter().map(CryptoHash::hash).collect();
+            txn_list_proof.verify(
+                ledger_info.ledger_info(),
+                Some(chunk.first_version),
+                &txn_hashes,
+            )?;
+
+            // The left siblings of the first transaction are exactly the frozen subtrees of the
+            // accumulator before it (from bottom to top), which are needed if we are not
+            // restoring from genesis.
+            if idx == 0 {
+                let mut frozen_subtrees = txn_list_proof.left_siblings().clone();
+                frozen_subtrees.reverse();
+                self.db
+                    .confirm_or_save_frozen_subtrees(chunk.first_version, &frozen_subtrees)?;
+            }
+
+            let last_version = min(chunk.last_version, self.target_version);
+            let last_to_save = match &self.replay {
+                Some(replay) => min(replay.from_version, last_version),
+                None => last_version,
+            };
+            if chunk.first_version <= last_to_save {
+                let num_to_save = (last_to_save - chunk.first_version + 1) as usize;
+                self.db.save_transactions_for_restore(
+                    chunk.first_version,
+                    &txns[..num_to_save],
+                    &txn_list_proof.transaction_infos()[..num_to_save],
+                    &events[..num_to_save],
+                )?;
+            }
+
+            if let Some(replay) = &self.replay {
+                if last_version > replay.from_version {
+                    if executor.is_none() {
+                        // `Executor::new()` panics otherwise.
+                        ensure!(
+                            self.db.get_startup_info()?.is_some(),
+                            "Replaying transactions requires a ledger info in the DB.",
+                        );
+                        executor = Some(Executor::<LibraVM>::new(DbReaderWriter {
+                            reader: Arc::clone(&self.db) as Arc<dyn DbReader>,
+                            writer: Arc::clone(&self.db) as Arc<dyn DbWriter>,
+                        }));
+                    }
+                    Self::replay_chunk(
+                        executor.as_mut().expect("Created above."),
+                        replay,
+                        chunk.first_version,
+                        last_version,
+                        txns,
+                        txn_list_proof,
+                        ledger_info,
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl TransactionRestoreController {
+    fn verify_ledger_info(&self, li: &LedgerInfoWithSignatures) -> Result<()> {
+        let verifier = match &self.verifier {
+            Some(verifier) => verifier,
+            None => return Ok(()),
+        };
+        match &self.replay {
+            Some(replay) => verifier.verify_with_epoch_history(li, &replay.epoch_history),
+            None => verifier.verify(li),
+        }
+    }
+
+    /// Replays the transactions in a chunk (already verified against `ledger_info`) up to
+    /// `last_version`. The executor doesn't take transactions crossing epoch boundaries, so the
+    /// chunk is replayed in pieces ending at each epoch ending. All pieces start from the first
+    /// transaction in the chunk, since the executor skips the ones already committed, and the
+    /// proofs for them can be derived from the proof for the whole chunk.
+    fn replay_chunk(
+        executor: &mut Executor<LibraVM>,
+        replay: &ReplayOpt,
+        first_version: Version,
+        last_version: Version,
+        txns: Vec<Transaction>,
+        txn_list_proof: TransactionListProof,
+        ledger_info: LedgerInfoWithSignatures,
+    ) -> Result<()> {
+        let txn_info_hashes: Vec<HashValue> = txn_list_proof
+            .transaction_infos()
+            .iter()
+            .map(CryptoHash::hash)
+            .collect();
+
+        let mut pieces = Vec::new();
+        for epoch_ending_li in replay.epoch_history.iter() {
+            let version = epoch_ending_li.ledger_info().version();
+            if version > replay.from_version && version >= first_version && version < last_version {
+                pieces.push((
+                    version,
+                    epoch_ending_li.clone(),
+                    range_proof_to_last_leaf(txn_list_proof.left_siblings(), version),
+                ));
+            }
+        }
+        pieces.push((
+            last_version,
+            ledger_info.clone(),
+            prefix_range_proof(
+                txn_list_proof.ledger_info_to_transaction_infos_proof(),
+                first_version,
+                &txn_info_hashes,
+                last_version,
+            )?,
+        ));
+
+        for (piece_last_version, target_li, range_proof) in pieces {
+            let num_txns = (piece_last_version - first_version + 1) as usize;
+            executor.execute_and_commit_chunk(
+                TransactionListWithProof::new(
+                    txns[..num_txns].to_vec(),
+                    None, /* events */
+                    Some(first_version),
+                    TransactionListProof::new(
+                        range_proof,
+                        txn_list_proof.transaction_infos()[..num_txns].to_vec(),
+                    ),
+                ),
+                target_li,
+                None, /* epoch_change_li */
+            )?;
+        }
+
+        Ok(())
+    }
+
+    async fn read_transaction_chunk(
+        &self,
+        file_handle: FileHandle,
+    ) -> Result<(
+        Vec<Transaction>,
+        Vec<TransactionInfo>,
+        Vec<Vec<ContractEvent>>,
+    )> {
+        let mut file = self.storage.open_for_read(&file_handle).await?;
+
+        let mut txns = Vec::new();
+        let mut txn_infos = Vec::new();
+        let mut events = Vec::new();
+
+        while let Some(record_bytes) = file.read_record_bytes().await? {
+            let (txn, txn_info, txn_events) = lcs::from_bytes(&record_bytes)?;
+            txns.push(txn);
+            txn_infos.push(txn_info);
+            events.push(txn_events);
+        }
+
+        Ok((txns, txn_infos, events))
+    }
+}
+
+#[derive(StructOpt)]
+pub struct EpochEndingRestoreOpt {
+    #[structopt(long = "epoch-ending-manifest")]
+    pub manifest_handle: FileHandle,
+
+    #[structopt(
+        long = "target-version",
+        help = "Ledger infos after this version are verified but not saved."
+    )]
+    pub target_version: Option<Version>,
+}
+
+pub struct EpochEndingRestoreController {
+    storage: Arc<dyn BackupStorage>,
+    db: Arc<LibraDB>,
+    verifier: Option<Arc<RestoreVerifier>>,
+    manifest_handle: FileHandle,
+    target_version: Version,
+    previous_epoch_ending: Option<LedgerInfoWithSignatures>,
+}
+
+impl EpochEndingRestoreController {
+    pub fn new(
+        opt: EpochEndingRestoreOpt,
+        storage: Arc<dyn BackupStorage>,
+        db: Arc<LibraDB>,
+        verifier: Option<Arc<RestoreVerifier>>,
+    ) -> Self {
+        Self {
+            storage,
+            db,
+            verifier,
+            manifest_handle: opt.manifest_handle,
+            target_version: opt.target_version.unwrap_or(Version::max_value()),
+            previous_epoch_ending: None,
+        }
+    }
+
+    /// Verifies the first ledger info in the backup against `li`, instead of the one in the DB,
+    /// which is useful when the previous backup is restored without saving all ledger infos.
+    pub fn with_previous_epoch_ending(mut self, li: Option<LedgerInfoWithSignatures>) -> Self {
+        self.previous_epoch_ending = li;
+        self
+    }
+
+    /// Returns all the verified ledger infos, including those not saved because they are after
+    /// the target version.
+    pub async fn run(self) -> Result<Vec<LedgerInfoWithSignatures>> {
+        let manifest: EpochEndingBackup =
+            load_json_file(&*self.storage, &self.manifest_handle).await?;
+        manifest.verify()?;
+
+        let mut next_epoch = manifest.first_epoch;
+        let mut previous_li = self.previous_epoch_ending.clone();
+        let mut verified_lis = Vec::new();
+        for chunk in manifest.chunks {
+            let lis = self.read_ledger_info_chunk(chunk.ledger_infos).await?;
+            ensure!(
+                lis.len() as u64 == chunk.last_epoch - chunk.first_epoch + 1,
+                "Number of items in chunk doesn't match that in manifest. first_epoch: {}, \
+                 last_epoch: {}, items in chunk: {}",
+                chunk.first_epoch,
+                chunk.last_epoch,
+                lis.len(),
+            );
+
+            for li in &lis {
+                ensure!(
+                    li.ledger_info().epoch() == next_epoch,
+                    "LedgerInfo epoch not expected. Expected: {}, actual: {}.",
+                    next_epoch,
+                    li.ledger_info().epoch(),
+                );
+                let waypoint = manifest.waypoints[(next_epoch - manifest.first_epoch) as usize];
+                waypoint.verify(li.ledger_info())?;
+                if let Some(verifier) = &self.verifier {
+                    verifier.verify_with_previous_epoch_ending(li, previous_li.as_ref())?;
+                }
+                previous_li = Some(li.clone());
+                next_epoch += 1;
+            }
+
+            let lis_to_save: Vec<_> = lis
+                .iter()
+                .filter(|li| li.ledger_info().version() <= self.target_version)
+                .cloned()
+                .collect();
+            self.db.save_ledger_infos(&lis_to_save)?;
+            verified_lis.extend(lis);
+        }
+
+        Ok(verified_lis)
+    }
+}
+
+impl EpochEndingRestoreController {
+    async fn read_ledger_info_chunk(
+        &self,
+        file_handle: FileHandle,
+    ) -> Result<Vec<LedgerInfoWithSignatures>> {
+        let mut file = self.storage.open_for_read(&file_handle).await?;
+
+        let mut chunk = vec![];
+
+        while let Some(record_bytes) = file.read_record_bytes().await? {
+            chunk.push(lcs::from_bytes(&record_bytes)?);
+        }
+
+        Ok(chunk)
+    }
+}
+
+async fn read_all(storage: &dyn BackupStorage, file_handle: &FileHandleRef) -> Result<Vec<u8>> {
+    let mut file = storage.open_for_read(file_handle).await?;
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf).await?;
+    Ok(buf)
+}
+
+async fn load_json_file<T: DeserializeOwned>(
+    storage: &dyn BackupStorage,
+    file_handle: &FileHandleRef,
+) -> Result<T> {
+    Ok(serde_json::from_slice(
+        &read_all(storage, file_handle).await?,
+    )?)
+}
+
+async fn load_lcs_file<T: DeserializeOwned>(
+    storage: &dyn BackupStorage,
+    file_handle: &FileHandleRef,
+) -> Result<T> {
+    Ok(lcs::from_bytes(&read_all(storage, file_handle).await?)?)
+}
+
+/// Given `proof` for leaves `[first_leaf_index, first_leaf_index + leaf_hashes.len())`, derives
+/// the proof for `[first_leaf_index, new_last_leaf_index]` against the same root hash.
+///
+/// The left siblings stay the same. The right siblings of the new range are either computed from
+/// the leaves dropped from the range, or taken from the original proof where the two ranges end
+/// in the same subtree.
+pub(crate) fn prefix_range_proof(
+    proof: &TransactionAccumulatorRangeProof,
+    first_leaf_index: u64,
+    leaf_hashes: &[HashValue],
+    new_last_leaf_index: u64,
+) -> Result<TransactionAccumulatorRangeProof> {
+    ensure!(!leaf_hashes.is_empty(), "No leaves.");
+    let last_leaf_index = first_leaf_index + leaf_hashes.len() as u64 - 1;
+    ensure!(
+        first_leaf_index <= new_last_leaf_index && new_last_leaf_index <= last_leaf_index,
+        "New last leaf {} out of range [{}, {}].",
+        new_last_leaf_index,
+        first_leaf_index,
+        last_leaf_index,
+    );
+
+    let mut left_sibling_iter = proof.left_siblings().iter().peekable();
+    let mut right_sibling_iter = proof.right_siblings().iter().peekable();
+    let mut new_right_siblings = Vec::new();
+
+    // Walk up the tree the same way `AccumulatorRangeProof::verify()` does, keeping the hashes
+    // of all nodes on each level covering the original range.
+    let mut first = first_leaf_index;
+    let mut last = last_leaf_index;
+    let mut new_last = new_last_leaf_index;
+    let mut hashes = leaf_hashes.to_vec();
+    while hashes.len() > 1
+        || left_sibling_iter.peek().is_some()
+        || right_sibling_iter.peek().is_some()
+    {
+        let left_sibling = if first % 2 == 1 {
+            Some(
+                *left_sibling_iter
+                    .next()
+                    .ok_or_else(|| format_err!("Missing left sibling."))?,
+            )
+        } else {
+            None
+        };
+        let right_sibling = if last % 2 == 0 {
+            Some(
+                *right_sibling_iter
+                    .next()
+                    .ok_or_else(|| format_err!("Missing right sibling."))?,
+            )
+        } else {
+            None
+        };
+
+        if new_last % 2 == 0 {
+            new_right_siblings.push(if new_last < last {
+                hashes[(new_last + 1 - first) as usize]
+            } else {
+                right_sibling.expect("Both ranges end with a left child.")
+            });
+        }
+
+        let level: Vec<_> = left_sibling
+            .into_iter()
+            .chain(hashes.into_iter())
+            .chain(right_sibling.into_iter())
+            .collect();
+        hashes = level
+            .chunks_exact(2)
+            .map(|pair| TransactionAccumulatorInternalNode::new(pair[0], pair[1]).hash())
+            .collect();
+        first /= 2;
+        last /= 2;
+        new_last /= 2;
+    }
+
+    Ok(TransactionAccumulatorRangeProof::new(
+        proof.left_siblings().clone(),
+        new_right_siblings,
+    ))
+}
+
+/// Derives the proof for a range of leaves against the accumulator in which the range ends with
+/// the last leaf, from the left siblings of the first leaf in the range. All subtrees on the
+/// right of the last leaf are empty.
+pub(crate) fn range_proof_to_last_leaf(
+    left_siblings: &[HashValue],
+    last_leaf_index: u64,
+) -> TransactionAccumulatorRangeProof {
+    let root_level = (last_leaf_index + 1).next_power_of_two().trailing_zeros();
+    let right_siblings = (0..root_level)
+        .filter(|level| (last_leaf_index >> level) % 2 == 0)
+        .map(|_| *ACCUMULATOR_PLACEHOLDER_HASH)
+        .collect();
+    TransactionAccumulatorRangeProof::new(left_siblings.to_vec(), right_siblings)
+}
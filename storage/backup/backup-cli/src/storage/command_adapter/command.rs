@@ -0,0 +1,208 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::storage::command_adapter::config::EnvVar;
+use anyhow::{ensure, Result};
+use futures::{ready, Future};
+use std::{
+    fmt::{Debug, Formatter},
+    pin::Pin,
+    process::{ExitStatus, Stdio},
+    task::{Context, Poll},
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    process::{Child, ChildStdin, ChildStdout},
+};
+
+pub(super) struct Command {
+    cmd_str: String,
+    env_vars: Vec<EnvVar>,
+}
+
+impl Command {
+    pub fn new(cmd_str: &str, env_vars: Vec<EnvVar>) -> Self {
+        Self {
+            cmd_str: cmd_str.to_string(),
+            env_vars,
+        }
+    }
+
+    pub fn spawn(self) -> Result<SpawnedCommand> {
+        SpawnedCommand::spawn(self)
+    }
+}
+
+impl Debug for Command {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // Values are left out since they can be credentials.
+        write!(f, "\"{}\" with env vars [", self.cmd_str)?;
+        for v in &self.env_vars {
+            write!(f, "{}, ", v.key)?;
+        }
+        write!(f, "]")
+    }
+}
+
+fn command_error(command: &Command, status: ExitStatus) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::Other,
+        format!("Command {:?} failed with exit status: {}", command, status),
+    )
+}
+
+pub(super) struct SpawnedCommand {
+    command: Command,
+    child: Child,
+}
+
+impl SpawnedCommand {
+    pub fn spawn(command: Command) -> Result<Self> {
+        let child = tokio::process::Command::new("bash")
+            .args(&["-c", &command.cmd_str])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .envs(command.env_vars.iter().map(|v| (&v.key, &v.value)))
+            .kill_on_drop(true)
+            .spawn()?;
+        Ok(Self { command, child })
+    }
+
+    pub fn stdout(&mut self) -> &mut ChildStdout {
+        self.child.stdout.as_mut().unwrap()
+    }
+
+    pub fn stdin(&mut self) -> &mut ChildStdin {
+        self.child.stdin.as_mut().unwrap()
+    }
+
+    /// Drops stdin so the command sees EOF on it.
+    pub fn close_stdin(&mut self) {
+        self.child.stdin = None;
+    }
+
+    /// Closes stdin, then reads from stdout until EOF, checking the exit status in the end.
+    pub fn into_data_source(mut self) -> ChildStdoutAsDataSource {
+        self.child.stdin = None;
+        let stdout = self.child.stdout.take();
+        ChildStdoutAsDataSource {
+            command: self.command,
+            stdout,
+            child: Some(Box::pin(self.child)),
+        }
+    }
+
+    /// Closes stdout, then writes to stdin until shut down, checking the exit status upon it.
+    pub fn into_data_sink(mut self) -> ChildStdinAsDataSink {
+        self.child.stdout = None;
+        let stdin = self.child.stdin.take();
+        ChildStdinAsDataSink {
+            command: self.command,
+            stdin,
+            child: Some(Box::pin(self.child)),
+        }
+    }
+
+    /// Closes both stdin and stdout and waits for the command to exit successfully.
+    pub async fn join(mut self) -> Result<()> {
+        self.child.stdin = None;
+        self.child.stdout = None;
+        let status = self.child.await?;
+        ensure!(
+            status.success(),
+            "Command {:?} failed with exit status: {}",
+            self.command,
+            status,
+        );
+        Ok(())
+    }
+}
+
+pub(super) struct ChildStdoutAsDataSource {
+    command: Command,
+    /// `None` after EOF is reached.
+    stdout: Option<ChildStdout>,
+    /// `None` after the command exited successfully.
+    child: Option<Pin<Box<Child>>>,
+}
+
+impl AsyncRead for ChildStdoutAsDataSource {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = self.get_mut();
+
+        if let Some(stdout) = this.stdout.as_mut() {
+            let bytes = ready!(Pin::new(stdout).poll_read(cx, buf))?;
+            if bytes > 0 || buf.is_empty() {
+                return Poll::Ready(Ok(bytes));
+            }
+            this.stdout = None;
+        }
+
+        if let Some(child) = this.child.as_mut() {
+            let status = ready!(child.as_mut().poll(cx))?;
+            this.child = None;
+            if !status.success() {
+                return Poll::Ready(Err(command_error(&this.command, status)));
+            }
+        }
+
+        Poll::Ready(Ok(0))
+    }
+}
+
+pub(super) struct ChildStdinAsDataSink {
+    command: Command,
+    /// `None` after being shut down.
+    stdin: Option<ChildStdin>,
+    /// `None` after the command exited successfully.
+    child: Option<Pin<Box<Child>>>,
+}
+
+impl AsyncWrite for ChildStdinAsDataSink {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        match self.get_mut().stdin.as_mut() {
+            Some(stdin) => Pin::new(stdin).poll_write(cx, buf),
+            None => Poll::Ready(Err(std::io::Error::new(
+                std::io::ErrorKind::BrokenPipe,
+                "Writing to a data sink that's already shut down.",
+            ))),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut().stdin.as_mut() {
+            Some(stdin) => Pin::new(stdin).poll_flush(cx),
+            None => Poll::Ready(Ok(())),
+        }
+    }
+
+    /// Closes stdin so the command sees EOF, and waits for it to exit successfully.
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+
+        if let Some(stdin) = this.stdin.as_mut() {
+            ready!(Pin::new(stdin).poll_shutdown(cx))?;
+            // Dropping closes the pipe.
+            this.stdin = None;
+        }
+
+        if let Some(child) = this.child.as_mut() {
+            let status = ready!(child.as_mut().poll(cx))?;
+            this.child = None;
+            if !status.success() {
+                return Poll::Ready(Err(command_error(&this.command, status)));
+            }
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
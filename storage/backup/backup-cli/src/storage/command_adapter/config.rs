@@ -0,0 +1,72 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use serde::Deserialize;
+use std::path::Path;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct EnvVar {
+    pub key: String,
+    pub value: String,
+}
+
+impl EnvVar {
+    pub fn new(key: String, value: String) -> Self {
+        Self { key, value }
+    }
+
+    pub fn backup_name(value: String) -> Self {
+        Self::new("BACKUP_NAME".to_string(), value)
+    }
+
+    pub fn backup_handle(value: String) -> Self {
+        Self::new("BACKUP_HANDLE".to_string(), value)
+    }
+
+    pub fn file_name(value: String) -> Self {
+        Self::new("FILE_NAME".to_string(), value)
+    }
+
+    pub fn file_handle(value: String) -> Self {
+        Self::new("FILE_HANDLE".to_string(), value)
+    }
+}
+
+/// Shell commands run by `CommandAdapter`, one for each `BackupStorage` method. Each command is
+/// run by `bash -c`, with its inputs exposed as environment variables.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Commands {
+    /// Input: $BACKUP_NAME
+    /// Output: backup handle, printed to stdout.
+    pub create_backup: String,
+    /// Input: $BACKUP_HANDLE, $FILE_NAME
+    /// Output: file handle, printed as the first line of stdout. File content is then streamed
+    /// in through stdin, and the command is expected to exit with 0 once it's been persisted.
+    pub create_for_write: String,
+    /// Input: $FILE_HANDLE
+    /// Output: file content, streamed to stdout.
+    pub open_for_read: String,
+    /// Input: $FILE_NAME, the content line comes in through stdin.
+    /// Output: file handle, printed to stdout.
+    pub save_metadata_line: String,
+    /// Input: none
+    /// Output: file handles of all metadata files, one per line on stdout.
+    pub list_metadata_files: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct CommandAdapterConfig {
+    /// Extra environment variables set for all commands, useful for things like bucket names
+    /// and credentials.
+    #[serde(default)]
+    pub env_vars: Vec<EnvVar>,
+    pub commands: Commands,
+}
+
+impl CommandAdapterConfig {
+    pub async fn load_from_file(path: &Path) -> Result<Self> {
+        let bytes = tokio::fs::read(path).await?;
+        Ok(toml::from_slice(&bytes)?)
+    }
+}
@@ -0,0 +1,151 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#[cfg(test)]
+mod tests;
+
+mod command;
+pub mod config;
+
+use super::{BackupHandle, BackupHandleRef, FileHandle, FileHandleRef};
+
+use crate::storage::{
+    command_adapter::{
+        command::Command,
+        config::{CommandAdapterConfig, EnvVar},
+    },
+    BackupStorage,
+};
+use anyhow::{ensure, Result};
+use async_trait::async_trait;
+use std::path::PathBuf;
+use structopt::StructOpt;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+
+#[derive(StructOpt)]
+pub struct CommandAdapterOpt {
+    #[structopt(
+        long = "config",
+        parse(from_os_str),
+        help = "Config file for the command adapter backup store."
+    )]
+    pub config: PathBuf,
+}
+
+/// A storage backend that delegates everything to user configured shell commands, making it
+/// possible to back up to all kinds of remote storage with their own CLI tools.
+pub struct CommandAdapter {
+    config: CommandAdapterConfig,
+}
+
+impl CommandAdapter {
+    pub fn new(config: CommandAdapterConfig) -> Self {
+        Self { config }
+    }
+
+    pub async fn new_with_opt(opt: CommandAdapterOpt) -> Result<Self> {
+        let config = CommandAdapterConfig::load_from_file(&opt.config).await?;
+        Ok(Self::new(config))
+    }
+
+    fn cmd(&self, cmd_str: &str, env_vars: Vec<EnvVar>) -> Command {
+        Command::new(
+            cmd_str,
+            self.config
+                .env_vars
+                .iter()
+                .cloned()
+                .chain(env_vars.into_iter())
+                .collect(),
+        )
+    }
+}
+
+/// Trims the trailing new line and checks the handle is not empty.
+fn to_handle(mut output: String) -> Result<String> {
+    output.truncate(output.trim_end().len());
+    ensure!(!output.is_empty(), "Command didn't output a handle.");
+    Ok(output)
+}
+
+#[async_trait]
+impl BackupStorage for CommandAdapter {
+    async fn create_backup(&self, name: &str) -> Result<BackupHandle> {
+        let mut child = self
+            .cmd(
+                &self.config.commands.create_backup,
+                vec![EnvVar::backup_name(name.to_string())],
+            )
+            .spawn()?;
+        let mut output = String::new();
+        child.stdout().read_to_string(&mut output).await?;
+        child.join().await?;
+        to_handle(output)
+    }
+
+    async fn create_for_write(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &str,
+    ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
+        let mut child = self
+            .cmd(
+                &self.config.commands.create_for_write,
+                vec![
+                    EnvVar::backup_handle(backup_handle.to_string()),
+                    EnvVar::file_name(name.to_string()),
+                ],
+            )
+            .spawn()?;
+        let mut output = String::new();
+        BufReader::new(child.stdout())
+            .read_line(&mut output)
+            .await?;
+        let file_handle = to_handle(output)?;
+        Ok((file_handle, Box::new(child.into_data_sink())))
+    }
+
+    async fn open_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        let child = self
+            .cmd(
+                &self.config.commands.open_for_read,
+                vec![EnvVar::file_handle(file_handle.to_string())],
+            )
+            .spawn()?;
+        Ok(Box::new(child.into_data_source()))
+    }
+
+    async fn save_metadata_line(&self, name: &str, content: &str) -> Result<FileHandle> {
+        let mut child = self
+            .cmd(
+                &self.config.commands.save_metadata_line,
+                vec![EnvVar::file_name(name.to_string())],
+            )
+            .spawn()?;
+        child.stdin().write_all(content.as_bytes()).await?;
+        child.stdin().write_all(b"\n").await?;
+        child.close_stdin();
+        let mut output = String::new();
+        child.stdout().read_to_string(&mut output).await?;
+        child.join().await?;
+        to_handle(output)
+    }
+
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+        let mut child = self
+            .cmd(&self.config.commands.list_metadata_files, vec![])
+            .spawn()?;
+        let mut output = String::new();
+        child.stdout().read_to_string(&mut output).await?;
+        child.join().await?;
+        Ok(output
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+}
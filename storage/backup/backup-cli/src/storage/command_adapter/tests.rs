@@ -0,0 +1,138 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use libra_temppath::TempPath;
+use proptest::{
+    collection::{hash_map, vec},
+    prelude::*,
+};
+use std::collections::HashMap;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    runtime::Runtime,
+};
+
+fn get_store(tmpdir: &TempPath) -> CommandAdapter {
+    let mut config: CommandAdapterConfig =
+        toml::from_str(include_str!("sample_configs/local_fs.toml")).unwrap();
+    config.env_vars = vec![EnvVar::new(
+        "FOLDER".to_string(),
+        tmpdir.path().to_str().unwrap().to_string(),
+    )];
+    CommandAdapter::new(config)
+}
+
+async fn test_write_and_read_impl(backups: HashMap<String, HashMap<String, Vec<u8>>>) {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    let store = get_store(&tmpdir);
+
+    let mut handles = Vec::new();
+    for (backup_name, files) in &backups {
+        let backup_handle = store.create_backup(backup_name).await.unwrap();
+        for (name, content) in files {
+            let (handle, mut file) = store.create_for_write(&backup_handle, name).await.unwrap();
+            file.write_all(content).await.unwrap();
+            file.shutdown().await.unwrap();
+            handles.push((handle, content));
+        }
+    }
+
+    for (handle, content) in handles {
+        let mut file = store.open_for_read(&handle).await.unwrap();
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(content, &buf);
+    }
+}
+
+async fn test_save_and_list_metadata_impl(lines: HashMap<String, String>) {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    let store = get_store(&tmpdir);
+
+    assert!(store.list_metadata_files().await.unwrap().is_empty());
+    for (name, content) in &lines {
+        store.save_metadata_line(name, content).await.unwrap();
+    }
+    // Names are unique.
+    let (name, content) = lines.iter().next().unwrap();
+    assert!(store.save_metadata_line(name, content).await.is_err());
+
+    let mut listed = Vec::new();
+    for handle in store.list_metadata_files().await.unwrap() {
+        let mut file = store.open_for_read(&handle).await.unwrap();
+        let mut buf = String::new();
+        file.read_to_string(&mut buf).await.unwrap();
+        listed.push(buf);
+    }
+    listed.sort();
+    let mut expected: Vec<_> = lines.values().map(|line| format!("{}\n", line)).collect();
+    expected.sort();
+    assert_eq!(listed, expected);
+}
+
+#[test]
+fn test_failed_commands() {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    let store = get_store(&tmpdir);
+
+    let mut rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        // Reading a file that doesn't exist fails upon EOF.
+        let mut file = store.open_for_read("no_such_backup/file").await.unwrap();
+        let mut buf = Vec::new();
+        assert!(file.read_to_end(&mut buf).await.is_err());
+
+        // Writing to an existing file fails upon shutdown.
+        let backup_handle = store.create_backup("backup").await.unwrap();
+        let (_, mut file) = store
+            .create_for_write(&backup_handle, "file")
+            .await
+            .unwrap();
+        file.shutdown().await.unwrap();
+        let (_, mut file) = store
+            .create_for_write(&backup_handle, "file")
+            .await
+            .unwrap();
+        assert!(file.shutdown().await.is_err());
+    });
+}
+
+fn arb_file_name() -> impl Strategy<Value = String> {
+    r"[-A-Za-z0-9_.]{1, 50}".prop_filter("no . and ..", |s| s != "." && s != "..")
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_write_and_read(
+        backups in hash_map(
+            arb_file_name(), // backup_name
+            hash_map(
+                arb_file_name(), // file name
+                vec(any::<u8>(), 1..1000), // file content
+                1..10
+            ),
+            1..10
+        )
+    ) {
+        let mut rt = Runtime::new().unwrap();
+        rt.block_on(test_write_and_read_impl(backups));
+    }
+
+    #[test]
+    fn test_save_and_list_metadata(
+        lines in hash_map(
+            arb_file_name(), // file name
+            r"[ -~]{1, 100}", // printable ASCII, one line
+            1..10
+        )
+    ) {
+        let mut rt = Runtime::new().unwrap();
+        rt.block_on(test_save_and_list_metadata_impl(lines));
+    }
+}
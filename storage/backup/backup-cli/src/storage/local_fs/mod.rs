@@ -9,11 +9,11 @@ use super::{BackupHandle, BackupHandleRef, FileHandle, FileHandleRef};
 use crate::storage::BackupStorage;
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use std::path::PathBuf;
+use std::{io::ErrorKind, path::PathBuf};
 use structopt::StructOpt;
 use tokio::{
-    fs::{create_dir, OpenOptions},
-    io::{AsyncRead, AsyncWrite},
+    fs::{create_dir, create_dir_all, read_dir, OpenOptions},
+    io::{AsyncRead, AsyncWrite, AsyncWriteExt},
 };
 
 #[derive(StructOpt)]
@@ -24,29 +24,69 @@ pub struct LocalFsOpt {
         help = "Target local dir to hold backups."
     )]
     pub dir: PathBuf,
+
+    #[structopt(
+        long = "rename-existing-backups",
+        help = "Instead of failing, add a numeric suffix to the name of a backup if one by the \
+                same name exists, e.g. left behind by an earlier attempt that didn't finish."
+    )]
+    pub rename_existing_backups: bool,
 }
 
 /// A storage backend that stores everything in a local directory.
 pub struct LocalFs {
     /// The path where everything is stored.
     dir: PathBuf,
+    /// Whether `create_backup` adds a suffix to the name of a backup which already exists,
+    /// instead of failing.
+    rename_existing_backups: bool,
 }
 
 impl LocalFs {
+    const METADATA_DIR: &'static str = "metadata";
+
     pub fn new(dir: PathBuf) -> Self {
-        Self { dir }
+        Self {
+            dir,
+            rename_existing_backups: false,
+        }
     }
 
     pub fn new_with_opt(opt: LocalFsOpt) -> Self {
-        Self::new(opt.dir)
+        Self {
+            dir: opt.dir,
+            rename_existing_backups: opt.rename_existing_backups,
+        }
     }
+
+    fn metadata_dir(&self) -> PathBuf {
+        self.dir.join(Self::METADATA_DIR)
+    }
+}
+
+fn path_to_handle(path: PathBuf) -> Result<FileHandle> {
+    path.into_os_string()
+        .into_string()
+        .map_err(|s| anyhow!("into_string failed for OsString '{:?}'", s))
 }
 
 #[async_trait]
 impl BackupStorage for LocalFs {
+    /// Fails if a backup by the same name exists, unless `rename_existing_backups` is set, in which
+    /// case a numeric suffix is added to the name to tell the two apart.
     async fn create_backup(&self, name: &str) -> Result<BackupHandle> {
-        create_dir(self.dir.join(name)).await?;
-        Ok(name.to_string())
+        let mut backup_handle = name.to_string();
+        let mut attempt = 0;
+        loop {
+            match create_dir(self.dir.join(&backup_handle)).await {
+                Ok(()) => return Ok(backup_handle),
+                Err(e) if e.kind() == ErrorKind::AlreadyExists && self.rename_existing_backups => {
+                    attempt += 1;
+                    backup_handle = format!("{}.{}", name, attempt);
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
     }
 
     async fn create_for_write(
@@ -54,13 +94,7 @@ impl BackupStorage for LocalFs {
         backup_handle: &BackupHandleRef,
         name: &str,
     ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
-        let file_handle = self
-            .dir
-            .join(backup_handle)
-            .join(name)
-            .into_os_string()
-            .into_string()
-            .map_err(|s| anyhow!("into_string failed for OsString '{:?}'", s))?;
+        let file_handle = path_to_handle(self.dir.join(backup_handle).join(name))?;
         let file = OpenOptions::new()
             .write(true)
             .create_new(true)
@@ -77,4 +111,33 @@ impl BackupStorage for LocalFs {
         let file = OpenOptions::new().read(true).open(file_handle).await?;
         Ok(Box::new(file))
     }
+
+    async fn save_metadata_line(&self, name: &str, content: &str) -> Result<FileHandle> {
+        let dir = self.metadata_dir();
+        create_dir_all(&dir).await?;
+        let file_handle = path_to_handle(dir.join(name))?;
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&file_handle)
+            .await?;
+        file.write_all(content.as_bytes()).await?;
+        file.write_all(b"\n").await?;
+        file.shutdown().await?;
+        Ok(file_handle)
+    }
+
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+        let dir = self.metadata_dir();
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut res = Vec::new();
+        let mut entries = read_dir(dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            res.push(path_to_handle(entry.path())?);
+        }
+        Ok(res)
+    }
 }
@@ -50,6 +50,61 @@ async fn test_write_and_read_impl(backups: HashMap<String, HashMap<String, Vec<u
     }
 }
 
+#[test]
+fn test_create_backup_twice() {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    let store = LocalFs::new(tmpdir.path().to_path_buf());
+    let renaming_store = LocalFs::new_with_opt(LocalFsOpt {
+        dir: tmpdir.path().to_path_buf(),
+        rename_existing_backups: true,
+    });
+
+    let mut rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        assert_eq!(store.create_backup("backup").await.unwrap(), "backup");
+        assert!(store.create_backup("backup").await.is_err());
+        assert_eq!(
+            renaming_store.create_backup("backup").await.unwrap(),
+            "backup.1"
+        );
+        assert_eq!(
+            renaming_store.create_backup("backup").await.unwrap(),
+            "backup.2"
+        );
+    });
+}
+
+#[test]
+fn test_save_and_list_metadata() {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    let store = LocalFs::new(tmpdir.path().to_path_buf());
+
+    let mut rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        assert!(store.list_metadata_files().await.unwrap().is_empty());
+
+        let handle1 = store.save_metadata_line("m1", "line 1").await.unwrap();
+        let handle2 = store.save_metadata_line("m2", "line 2").await.unwrap();
+        assert!(store.save_metadata_line("m1", "line 1").await.is_err());
+
+        let mut listed = store.list_metadata_files().await.unwrap();
+        listed.sort();
+        assert_eq!(listed, vec![handle1.clone(), handle2]);
+
+        let mut buf = String::new();
+        store
+            .open_for_read(&handle1)
+            .await
+            .unwrap()
+            .read_to_string(&mut buf)
+            .await
+            .unwrap();
+        assert_eq!(buf, "line 1\n");
+    });
+}
+
 fn arb_file_name() -> impl Strategy<Value = String> {
     r"[-A-Za-z0-9_.]{1, 50}".prop_filter("no . and ..", |s| s != "." && s != "..")
 }
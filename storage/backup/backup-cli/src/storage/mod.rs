@@ -1,10 +1,15 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod command_adapter;
 pub mod local_fs;
 
 use anyhow::Result;
 use async_trait::async_trait;
+use command_adapter::{CommandAdapter, CommandAdapterOpt};
+use local_fs::{LocalFs, LocalFsOpt};
+use std::sync::Arc;
+use structopt::StructOpt;
 use tokio::io::{AsyncRead, AsyncWrite};
 
 pub type BackupHandle = String;
@@ -22,6 +27,7 @@ pub trait BackupStorage {
     async fn create_backup(&self, name: &str) -> Result<BackupHandle>;
     /// Ask to create a file for write, `backup_handle` was returned by `create_backup` to identify
     /// the current backup.
+    /// The returned writer must be shut down before the file is considered fully written.
     async fn create_for_write(
         &self,
         backup_handle: &BackupHandleRef,
@@ -32,4 +38,27 @@ pub trait BackupStorage {
         &self,
         file_handle: &FileHandleRef,
     ) -> Result<Box<dyn AsyncRead + Send + Unpin>>;
+    /// Save a line of metadata, identified by `name`, which is unique among all metadata lines.
+    /// Metadata lives outside of any backup and is meant to be listed and read back as a whole,
+    /// for example to find out which backups exist.
+    async fn save_metadata_line(&self, name: &str, content: &str) -> Result<FileHandle>;
+    /// List all metadata files ever saved, each can be read by `open_for_read`.
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>>;
+}
+
+#[derive(StructOpt)]
+pub enum StorageOpt {
+    #[structopt(about = "Select the LocalFs backup store.")]
+    LocalFs(LocalFsOpt),
+    #[structopt(about = "Select the CommandAdapter backup store.")]
+    CommandAdapter(CommandAdapterOpt),
+}
+
+impl StorageOpt {
+    pub async fn init_storage(self) -> Result<Arc<dyn BackupStorage>> {
+        Ok(match self {
+            StorageOpt::LocalFs(opt) => Arc::new(LocalFs::new_with_opt(opt)),
+            StorageOpt::CommandAdapter(opt) => Arc::new(CommandAdapter::new_with_opt(opt).await?),
+        })
+    }
 }
@@ -3,19 +3,51 @@
 
 use crate::{
     backup::{
-        BackupServiceClient, GlobalBackupOpt, StateSnapshotBackupController, StateSnapshotBackupOpt,
+        BackupServiceClient, EpochEndingBackupController, EpochEndingBackupOpt, GlobalBackupOpt,
+        StateSnapshotBackupController, StateSnapshotBackupOpt, TransactionBackupController,
+        TransactionBackupOpt,
+    },
+    coordinator::{
+        BackupCoordinator, BackupCoordinatorOpt, RestoreCoordinator, RestoreCoordinatorOpt,
+    },
+    metadata::{cache::MetadataCacheOpt, Metadata},
+    restore::{
+        prefix_range_proof, range_proof_to_last_leaf, EpochEndingRestoreController,
+        EpochEndingRestoreOpt, StateSnapshotRestoreController, StateSnapshotRestoreOpt,
+        TransactionRestoreController, TransactionRestoreOpt,
     },
-    restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
     storage::{local_fs::LocalFs, BackupStorage},
+    verifier::RestoreVerifier,
 };
+use anyhow::Result;
 use backup_service::start_backup_service;
-use libra_config::config::NodeConfig;
+use executor::{db_bootstrapper::bootstrap_db_if_empty, Executor};
+use executor_test_helpers::{
+    extract_signer, gen_block_id, gen_block_metadata, gen_ledger_info_with_sigs,
+    get_test_signed_transaction,
+};
+use executor_types::BlockExecutor;
+use libra_config::{config::NodeConfig, utils::get_genesis_txn};
+use libra_crypto::{
+    ed25519::Ed25519PrivateKey,
+    hash::{CryptoHash, TransactionAccumulatorHasher, SPARSE_MERKLE_PLACEHOLDER_HASH},
+    HashValue, PrivateKey, Uniform,
+};
 use libra_proptest_helpers::ValueGenerator;
 use libra_temppath::TempPath;
-use libra_types::transaction::PRE_GENESIS_VERSION;
+use libra_types::{
+    account_config::association_address,
+    on_chain_config::VMPublishingOption,
+    proof::accumulator::InMemoryAccumulator,
+    transaction::{authenticator::AuthenticationKey, Transaction, PRE_GENESIS_VERSION},
+    waypoint::Waypoint,
+};
+use libra_vm::LibraVM;
 use libradb::{test_helper::arb_blocks_to_commit, LibraDB};
+use rand::{rngs::StdRng, SeedableRng};
 use std::sync::Arc;
-use storage_interface::{DbReader, DbWriter};
+use storage_interface::{DbReader, DbReaderWriter, DbWriter};
+use transaction_builder::{encode_mint_lbr_to_address_script, encode_publishing_option_script};
 
 fn tmp_db_empty() -> (TempPath, Arc<LibraDB>) {
     let tmpdir = TempPath::new();
@@ -42,6 +74,77 @@ fn tmp_db_with_random_content() -> (TempPath, Arc<LibraDB>) {
     (tmpdir, db)
 }
 
+/// Unlike the random content, the transactions committed here can be replayed by the VM. Epoch 1
+/// ends at version 5, with a change of the publishing option, and epoch 2 has one more
+/// transaction. Returns the waypoint of genesis as well.
+fn tmp_db_with_executed_transactions() -> (TempPath, Arc<LibraDB>, Waypoint) {
+    let (mut config, genesis_key) = config_builder::test_config();
+    let signer = extract_signer(&mut config);
+    let validator_account = config.validator_network.as_ref().unwrap().peer_id();
+    let tmpdir = TempPath::new();
+    let (db, db_rw) = DbReaderWriter::wrap(LibraDB::new_for_test(&tmpdir));
+    let waypoint = bootstrap_db_if_empty::<LibraVM>(&db_rw, get_genesis_txn(&config).unwrap())
+        .unwrap()
+        .unwrap();
+    let mut executor = Executor::<LibraVM>::new(db_rw);
+
+    let mut rng = StdRng::from_seed([1u8; 32]);
+    let mut association_sequence_number = 1;
+    let mut association_txn = |script| {
+        let txn = get_test_signed_transaction(
+            association_address(),
+            association_sequence_number,
+            genesis_key.clone(),
+            genesis_key.public_key(),
+            Some(script),
+        );
+        association_sequence_number += 1;
+        txn
+    };
+    let mut mint_to_new_account = || {
+        let auth_key =
+            AuthenticationKey::ed25519(&Ed25519PrivateKey::generate(&mut rng).public_key());
+        encode_mint_lbr_to_address_script(
+            &auth_key.derived_address(),
+            auth_key.prefix().to_vec(),
+            1_000_000,
+        )
+    };
+
+    let blocks: Vec<(u64, Vec<Transaction>)> = vec![
+        (
+            1,
+            (0..3)
+                .map(|_| association_txn(mint_to_new_account()))
+                .collect(),
+        ),
+        (
+            1,
+            vec![
+                Transaction::BlockMetadata(gen_block_metadata(1, validator_account)),
+                association_txn(encode_publishing_option_script(
+                    VMPublishingOption::CustomScripts,
+                )),
+            ],
+        ),
+        (2, vec![association_txn(mint_to_new_account())]),
+    ];
+    for (index, (epoch, block)) in blocks.into_iter().enumerate() {
+        let block_id = gen_block_id(index as u8 + 1);
+        let output = executor
+            .execute_block((block_id, block), executor.committed_block_id())
+            .unwrap();
+        executor
+            .commit_blocks(
+                vec![block_id],
+                gen_ledger_info_with_sigs(epoch, output, block_id, vec![&signer]),
+            )
+            .unwrap();
+    }
+
+    (tmpdir, db, waypoint)
+}
+
 #[test]
 fn end_to_end() {
     let (_src_db_dir, src_db) = tmp_db_with_random_content();
@@ -76,6 +179,7 @@ fn end_to_end() {
             },
             store,
             Arc::clone(&tgt_db),
+            None, /* verifier */
         )
         .run(),
     )
@@ -88,3 +192,566 @@ fn end_to_end() {
         state_root_hash,
     );
 }
+
+#[test]
+fn end_to_end_transaction_and_epoch_ending() {
+    let (_src_db_dir, src_db) = tmp_db_with_random_content();
+    let (_tgt_db_dir, tgt_db) = tmp_db_empty();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+
+    let config = NodeConfig::random();
+    let mut rt = start_backup_service(config.storage.backup_service_port, Arc::clone(&src_db));
+    let client = Arc::new(BackupServiceClient::new(config.storage.backup_service_port));
+
+    let latest_li = src_db.get_latest_ledger_info().unwrap();
+    let num_txns = latest_li.ledger_info().version() + 1;
+    let num_epochs = if latest_li.ledger_info().next_epoch_state().is_some() {
+        latest_li.ledger_info().epoch() + 1
+    } else {
+        latest_li.ledger_info().epoch()
+    };
+
+    let epoch_ending_manifest = rt
+        .block_on(
+            EpochEndingBackupController::new(
+                EpochEndingBackupOpt {
+                    start_epoch: 0,
+                    end_epoch: num_epochs,
+                },
+                GlobalBackupOpt {
+                    max_chunk_size: 500,
+                },
+                Arc::clone(&client),
+                Arc::clone(&store),
+            )
+            .run(),
+        )
+        .unwrap();
+    let transaction_manifest = rt
+        .block_on(
+            TransactionBackupController::new(
+                TransactionBackupOpt {
+                    start_version: 0,
+                    num_transactions: num_txns,
+                },
+                GlobalBackupOpt {
+                    max_chunk_size: 500,
+                },
+                client,
+                Arc::clone(&store),
+            )
+            .run(),
+        )
+        .unwrap();
+
+    rt.block_on(
+        EpochEndingRestoreController::new(
+            EpochEndingRestoreOpt {
+                manifest_handle: epoch_ending_manifest,
+                target_version: None,
+            },
+            Arc::clone(&store),
+            Arc::clone(&tgt_db),
+            None, /* verifier */
+        )
+        .run(),
+    )
+    .unwrap();
+    rt.block_on(
+        TransactionRestoreController::new(
+            TransactionRestoreOpt {
+                manifest_handle: transaction_manifest,
+                target_version: None,
+            },
+            store,
+            Arc::clone(&tgt_db),
+            None, /* verifier */
+        )
+        .run(),
+    )
+    .unwrap();
+
+    let src_backup_handler = src_db.get_backup_handler();
+    let tgt_backup_handler = tgt_db.get_backup_handler();
+    assert_eq!(
+        tgt_backup_handler
+            .get_transaction_iter(0, num_txns)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap(),
+        src_backup_handler
+            .get_transaction_iter(0, num_txns)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap(),
+    );
+    assert_eq!(
+        tgt_backup_handler
+            .get_epoch_ending_ledger_info_iter(0, num_epochs)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap(),
+        src_backup_handler
+            .get_epoch_ending_ledger_info_iter(0, num_epochs)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap(),
+    );
+    assert_eq!(
+        tgt_db.get_latest_tree_state().unwrap().num_transactions,
+        num_txns,
+    );
+}
+
+#[test]
+fn state_snapshot_restore_verification() {
+    let (_src_db_dir, src_db) = tmp_db_with_random_content();
+    let (_tgt_db_dir, tgt_db) = tmp_db_empty();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+
+    let config = NodeConfig::random();
+    let mut rt = start_backup_service(config.storage.backup_service_port, Arc::clone(&src_db));
+    let client = Arc::new(BackupServiceClient::new(config.storage.backup_service_port));
+    let (version, state_root_hash) = rt.block_on(client.get_latest_state_root()).unwrap();
+    let manifest_handle = rt
+        .block_on(
+            StateSnapshotBackupController::new(
+                StateSnapshotBackupOpt { version },
+                GlobalBackupOpt {
+                    max_chunk_size: 500,
+                },
+                client,
+                Arc::clone(&store),
+            )
+            .run(),
+        )
+        .unwrap();
+
+    // The proof of the snapshot is anchored to the latest ledger info.
+    let li = src_db.get_latest_ledger_info().unwrap();
+    let restore = |waypoint: Waypoint| {
+        StateSnapshotRestoreController::new(
+            StateSnapshotRestoreOpt {
+                manifest_handle: manifest_handle.clone(),
+                version: PRE_GENESIS_VERSION,
+            },
+            Arc::clone(&store),
+            Arc::clone(&tgt_db),
+            Some(Arc::new(RestoreVerifier::new(
+                vec![waypoint],
+                Arc::clone(&tgt_db),
+            ))),
+        )
+        .run()
+    };
+
+    // A waypoint at the same version but for a different ledger info is rejected, before anything
+    // is written to the DB.
+    let bad_waypoint: Waypoint = format!(
+        "{}:{}",
+        li.ledger_info().version(),
+        HashValue::zero().to_hex()
+    )
+    .parse()
+    .unwrap();
+    assert!(rt.block_on(restore(bad_waypoint)).is_err());
+    assert_eq!(
+        tgt_db
+            .get_latest_tree_state()
+            .unwrap()
+            .account_state_root_hash,
+        *SPARSE_MERKLE_PLACEHOLDER_HASH,
+    );
+
+    rt.block_on(restore(Waypoint::new_any(li.ledger_info())))
+        .unwrap();
+    assert_eq!(
+        tgt_db
+            .get_latest_tree_state()
+            .unwrap()
+            .account_state_root_hash,
+        state_root_hash,
+    );
+}
+
+#[test]
+fn backup_coordinator_resumes() {
+    let (_src_db_dir, src_db) = tmp_db_with_random_content();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+
+    let config = NodeConfig::random();
+    let mut rt = start_backup_service(config.storage.backup_service_port, src_db);
+    let client = Arc::new(BackupServiceClient::new(config.storage.backup_service_port));
+    let db_state = rt.block_on(client.get_db_state()).unwrap().unwrap();
+    let new_coordinator = || {
+        BackupCoordinator::new(
+            BackupCoordinatorOpt {
+                state_snapshot_interval: 1,
+                poll_interval_secs: 1,
+            },
+            GlobalBackupOpt {
+                max_chunk_size: 500,
+            },
+            Arc::clone(&client),
+            Arc::clone(&store),
+        )
+    };
+
+    let coordinator = new_coordinator();
+    assert_eq!(
+        rt.block_on(coordinator.load_last_state_snapshot_version())
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        rt.block_on(coordinator.run_once(None)).unwrap(),
+        Some(db_state.committed_version)
+    );
+
+    // A restarted coordinator learns about the snapshot from the metadata and doesn't take it
+    // again.
+    let coordinator = new_coordinator();
+    let last_snapshot_version = rt
+        .block_on(coordinator.load_last_state_snapshot_version())
+        .unwrap();
+    assert_eq!(last_snapshot_version, Some(db_state.committed_version));
+    assert_eq!(
+        rt.block_on(coordinator.run_once(last_snapshot_version))
+            .unwrap(),
+        last_snapshot_version
+    );
+    let metadata = rt.block_on(Metadata::load_all(&*store)).unwrap();
+    assert_eq!(metadata.len(), 1);
+    assert!(matches!(
+        &metadata[0],
+        Metadata::StateSnapshotBackup(s) if s.version == db_state.committed_version
+    ));
+}
+
+#[test]
+fn backup_coordinator_retries_unfinished_backup() {
+    let (_src_db_dir, src_db) = tmp_db_with_random_content();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+
+    let config = NodeConfig::random();
+    let mut rt = start_backup_service(config.storage.backup_service_port, src_db);
+    let client = Arc::new(BackupServiceClient::new(config.storage.backup_service_port));
+    let db_state = rt.block_on(client.get_db_state()).unwrap().unwrap();
+    let coordinator = BackupCoordinator::new(
+        BackupCoordinatorOpt {
+            state_snapshot_interval: 1,
+            poll_interval_secs: 1,
+        },
+        GlobalBackupOpt {
+            max_chunk_size: 500,
+        },
+        Arc::clone(&client),
+        Arc::clone(&store),
+    );
+
+    // An attempt that crashed after creating the backup, before saving its metadata, looks like no
+    // attempt at all to the coordinator, which takes the snapshot again despite what's left behind.
+    let leftover =
+        rt.block_on(store.create_backup(&format!("state_ver_{}", db_state.committed_version)));
+    assert!(leftover.is_ok());
+    for _ in 0..2 {
+        assert_eq!(
+            rt.block_on(coordinator.run_once(None)).unwrap(),
+            Some(db_state.committed_version)
+        );
+    }
+}
+
+#[test]
+fn transaction_restore_to_target_version() {
+    let (_src_db_dir, src_db) = tmp_db_with_random_content();
+    let (_tgt_db_dir, tgt_db) = tmp_db_empty();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+
+    let config = NodeConfig::random();
+    let mut rt = start_backup_service(config.storage.backup_service_port, Arc::clone(&src_db));
+    let client = Arc::new(BackupServiceClient::new(config.storage.backup_service_port));
+
+    let num_txns = src_db.get_latest_version().unwrap() + 1;
+    let target_version = num_txns / 2;
+    let transaction_manifest = rt
+        .block_on(
+            TransactionBackupController::new(
+                TransactionBackupOpt {
+                    start_version: 0,
+                    num_transactions: num_txns,
+                },
+                GlobalBackupOpt {
+                    max_chunk_size: 500,
+                },
+                client,
+                Arc::clone(&store),
+            )
+            .run(),
+        )
+        .unwrap();
+
+    rt.block_on(
+        TransactionRestoreController::new(
+            TransactionRestoreOpt {
+                manifest_handle: transaction_manifest,
+                target_version: Some(target_version),
+            },
+            store,
+            Arc::clone(&tgt_db),
+            None, /* verifier */
+        )
+        .run(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        tgt_db
+            .get_backup_handler()
+            .get_transaction_iter(0, target_version + 1)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap(),
+        src_db
+            .get_backup_handler()
+            .get_transaction_iter(0, target_version + 1)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap(),
+    );
+    assert_eq!(
+        tgt_db.get_latest_tree_state().unwrap().num_transactions,
+        target_version + 1,
+    );
+}
+
+#[test]
+fn restore_coordinator_end_to_end() {
+    let (_src_db_dir, src_db) = tmp_db_with_random_content();
+    let (_tgt_db_dir, tgt_db) = tmp_db_empty();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let metadata_cache_dir = TempPath::new();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+
+    let config = NodeConfig::random();
+    let mut rt = start_backup_service(config.storage.backup_service_port, Arc::clone(&src_db));
+    let client = Arc::new(BackupServiceClient::new(config.storage.backup_service_port));
+
+    let latest_li = src_db.get_latest_ledger_info().unwrap();
+    let version = latest_li.ledger_info().version();
+    let num_epochs = if latest_li.ledger_info().next_epoch_state().is_some() {
+        latest_li.ledger_info().epoch() + 1
+    } else {
+        latest_li.ledger_info().epoch()
+    };
+    let global_opt = GlobalBackupOpt {
+        max_chunk_size: 500,
+    };
+    rt.block_on(
+        EpochEndingBackupController::new(
+            EpochEndingBackupOpt {
+                start_epoch: 0,
+                end_epoch: num_epochs,
+            },
+            global_opt.clone(),
+            Arc::clone(&client),
+            Arc::clone(&store),
+        )
+        .run(),
+    )
+    .unwrap();
+    rt.block_on(
+        TransactionBackupController::new(
+            TransactionBackupOpt {
+                start_version: 0,
+                num_transactions: version + 1,
+            },
+            global_opt.clone(),
+            Arc::clone(&client),
+            Arc::clone(&store),
+        )
+        .run(),
+    )
+    .unwrap();
+    let (_, state_root_hash) = rt.block_on(client.get_latest_state_root()).unwrap();
+    rt.block_on(
+        StateSnapshotBackupController::new(
+            StateSnapshotBackupOpt { version },
+            global_opt,
+            client,
+            Arc::clone(&store),
+        )
+        .run(),
+    )
+    .unwrap();
+
+    // Restoring to the version of the state snapshot requires nothing to be replayed.
+    rt.block_on(
+        RestoreCoordinator::new(
+            RestoreCoordinatorOpt {
+                metadata_cache_opt: MetadataCacheOpt::new(Some(
+                    metadata_cache_dir.path().to_path_buf(),
+                )),
+                target_version: version,
+            },
+            store,
+            Arc::clone(&tgt_db),
+            None, /* verifier */
+        )
+        .run(),
+    )
+    .unwrap();
+
+    let tree_state = tgt_db.get_latest_tree_state().unwrap();
+    assert_eq!(tree_state.num_transactions, version + 1);
+    assert_eq!(tree_state.account_state_root_hash, state_root_hash);
+}
+
+#[test]
+fn restore_coordinator_replays_past_state_snapshot() {
+    let (_src_db_dir, src_db, genesis_waypoint) = tmp_db_with_executed_transactions();
+    let (_tgt_db_dir, tgt_db) = tmp_db_empty();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let metadata_cache_dir = TempPath::new();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+
+    let config = NodeConfig::random();
+    let mut rt = start_backup_service(config.storage.backup_service_port, Arc::clone(&src_db));
+    let client = Arc::new(BackupServiceClient::new(config.storage.backup_service_port));
+
+    let latest_li = src_db.get_latest_ledger_info().unwrap();
+    let version = latest_li.ledger_info().version();
+    assert_eq!(version, 6);
+    assert_eq!(latest_li.ledger_info().epoch(), 2);
+    let global_opt = GlobalBackupOpt {
+        max_chunk_size: 500,
+    };
+    rt.block_on(
+        EpochEndingBackupController::new(
+            EpochEndingBackupOpt {
+                start_epoch: 0,
+                end_epoch: 2,
+            },
+            global_opt.clone(),
+            Arc::clone(&client),
+            Arc::clone(&store),
+        )
+        .run(),
+    )
+    .unwrap();
+    rt.block_on(
+        TransactionBackupController::new(
+            TransactionBackupOpt {
+                start_version: 0,
+                num_transactions: version + 1,
+            },
+            global_opt.clone(),
+            Arc::clone(&client),
+            Arc::clone(&store),
+        )
+        .run(),
+    )
+    .unwrap();
+    // The snapshot is in the middle of epoch 1, so the transactions after it are replayed across
+    // the epoch boundary at version 5. The ledger info of epoch 1 proving the snapshot is only
+    // restored into the epoch history, since it's after the snapshot.
+    rt.block_on(
+        StateSnapshotBackupController::new(
+            StateSnapshotBackupOpt { version: 3 },
+            global_opt,
+            client,
+            Arc::clone(&store),
+        )
+        .run(),
+    )
+    .unwrap();
+
+    rt.block_on(
+        RestoreCoordinator::new(
+            RestoreCoordinatorOpt {
+                metadata_cache_opt: MetadataCacheOpt::new(Some(
+                    metadata_cache_dir.path().to_path_buf(),
+                )),
+                target_version: version,
+            },
+            store,
+            Arc::clone(&tgt_db),
+            Some(Arc::new(RestoreVerifier::new(
+                vec![genesis_waypoint],
+                Arc::clone(&tgt_db),
+            ))),
+        )
+        .run(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        tgt_db.get_latest_tree_state().unwrap(),
+        src_db.get_latest_tree_state().unwrap(),
+    );
+}
+
+#[test]
+fn derived_range_proofs() {
+    let (_src_db_dir, src_db) = tmp_db_with_random_content();
+    let latest_li = src_db.get_latest_ledger_info().unwrap();
+    let latest_version = latest_li.ledger_info().version();
+    let txn_list = src_db
+        .get_transactions(0, latest_version + 1, latest_version, false)
+        .unwrap();
+    let all_hashes: Vec<HashValue> = txn_list
+        .proof
+        .transaction_infos()
+        .iter()
+        .map(CryptoHash::hash)
+        .collect();
+    let num_txns = all_hashes.len() as u64;
+
+    for first_version in (0..num_txns).step_by(7) {
+        let txn_list = src_db
+            .get_transactions(
+                first_version,
+                num_txns - first_version,
+                latest_version,
+                false,
+            )
+            .unwrap();
+        let hashes = &all_hashes[first_version as usize..];
+        let proof = txn_list.proof.ledger_info_to_transaction_infos_proof();
+
+        for new_last_version in (first_version..num_txns).step_by(5) {
+            let num_leaves = (new_last_version - first_version + 1) as usize;
+
+            // Against the same accumulator as the original proof.
+            prefix_range_proof(proof, first_version, hashes, new_last_version)
+                .unwrap()
+                .verify(
+                    latest_li.ledger_info().transaction_accumulator_hash(),
+                    Some(first_version),
+                    &hashes[..num_leaves],
+                )
+                .unwrap();
+
+            // Against the accumulator ending at the new last leaf.
+            let root_hash = InMemoryAccumulator::<TransactionAccumulatorHasher>::from_leaves(
+                &all_hashes[..=new_last_version as usize],
+            )
+            .root_hash();
+            range_proof_to_last_leaf(proof.left_siblings(), new_last_version)
+                .verify(root_hash, Some(first_version), &hashes[..num_leaves])
+                .unwrap();
+        }
+    }
+}
@@ -0,0 +1,116 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{anyhow, ensure, Result};
+use libra_types::{
+    epoch_change::Verifier, ledger_info::LedgerInfoWithSignatures, transaction::Version,
+    waypoint::Waypoint,
+};
+use libradb::LibraDB;
+use std::{collections::HashMap, sync::Arc};
+
+/// `RestoreVerifier` decides whether a `LedgerInfoWithSignatures` found in a backup can be trusted,
+/// when restoring from backups in untrusted storage.
+///
+/// A ledger info is trusted if either:
+///   1. it matches one of the trusted waypoints given by the operator, or
+///   2. it carries enough signatures from the validator set of its epoch, which is carried by
+///      the epoch ending ledger info of the previous epoch, which must be trusted itself.
+///
+/// Epoch ending ledger infos that are already in the target DB are considered trusted, so the
+/// chain of epoch changes is expected to be restored (and verified) from the first trusted
+/// waypoint up, before any transactions or state snapshots that rely on it.
+pub struct RestoreVerifier {
+    trusted_waypoints: HashMap<Version, Waypoint>,
+    db: Arc<LibraDB>,
+}
+
+impl RestoreVerifier {
+    pub fn new(trusted_waypoints: Vec<Waypoint>, db: Arc<LibraDB>) -> Self {
+        Self {
+            trusted_waypoints: trusted_waypoints
+                .into_iter()
+                .map(|w| (w.version(), w))
+                .collect(),
+            db,
+        }
+    }
+
+    /// Verifies `li` against the trusted waypoints and the epoch history in the DB.
+    pub fn verify(&self, li: &LedgerInfoWithSignatures) -> Result<()> {
+        self.verify_with_previous_epoch_ending(li, None)
+    }
+
+    /// Verifies `li` against the trusted waypoints and `epoch_history`, the epoch ending ledger
+    /// infos from epoch 0 on, which are verified but not necessarily saved in the DB yet.
+    pub fn verify_with_epoch_history(
+        &self,
+        li: &LedgerInfoWithSignatures,
+        epoch_history: &[LedgerInfoWithSignatures],
+    ) -> Result<()> {
+        let epoch = li.ledger_info().epoch();
+        if epoch == 0 {
+            return self.verify(li);
+        }
+        let previous = epoch_history
+            .get((epoch - 1) as usize)
+            .ok_or_else(|| anyhow!("Epoch {} not found in epoch history.", epoch - 1))?;
+        self.verify_with_previous_epoch_ending(li, Some(previous))
+    }
+
+    /// Verifies an epoch ending ledger info. `previous` is the epoch ending ledger info of the
+    /// previous epoch if it's not yet in the DB, which is the case when walking the epoch ending
+    /// ledger infos in a backup.
+    pub fn verify_with_previous_epoch_ending(
+        &self,
+        li: &LedgerInfoWithSignatures,
+        previous: Option<&LedgerInfoWithSignatures>,
+    ) -> Result<()> {
+        let ledger_info = li.ledger_info();
+
+        if let Some(waypoint) = self.trusted_waypoints.get(&ledger_info.version()) {
+            return waypoint.verify(ledger_info);
+        }
+
+        let epoch = ledger_info.epoch();
+        ensure!(
+            epoch > 0,
+            "LedgerInfo of epoch 0 at version {} doesn't match any trusted waypoint.",
+            ledger_info.version(),
+        );
+
+        let previous = match previous {
+            Some(previous) => previous.clone(),
+            None => self
+                .db
+                .get_epoch_change_ledger_infos(epoch - 1, epoch)?
+                .0
+                .pop()
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Can't verify LedgerInfo at version {}: ledger info ending epoch {} is \
+                         neither in the DB nor trusted by a waypoint.",
+                        ledger_info.version(),
+                        epoch - 1,
+                    )
+                })?,
+        };
+        ensure!(
+            previous.ledger_info().epoch() + 1 == epoch,
+            "Previous epoch ending LedgerInfo is of epoch {}, expecting {}.",
+            previous.ledger_info().epoch(),
+            epoch - 1,
+        );
+
+        previous
+            .ledger_info()
+            .next_epoch_state()
+            .ok_or_else(|| {
+                anyhow!(
+                    "LedgerInfo at version {} doesn't carry the next EpochState.",
+                    previous.ledger_info().version(),
+                )
+            })?
+            .verify(li)
+    }
+}
@@ -1,7 +1,7 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::Result;
+use anyhow::{ensure, Result};
 use bytes::Bytes;
 use futures::stream;
 use hyper::Body;
@@ -10,13 +10,21 @@ use libra_logger::prelude::*;
 use libra_types::transaction::Version;
 use libradb::backup::BackupHandler;
 use serde::Serialize;
-use std::convert::Infallible;
+use std::{cmp::min, convert::Infallible};
 use warp::{
     filters::BoxedFilter,
     reply::{Reply, Response},
     Filter, Rejection,
 };
 
+/// Number of records serialized at a time by the endpoints streaming ranges of records.
+const RECORDS_PER_CHUNK: u64 = 1000;
+
+fn get_db_state(backup_handler: &BackupHandler) -> Result<Box<dyn Reply>> {
+    let bytes = lcs::to_bytes(&backup_handler.get_db_state()?)?;
+    Ok(Box::new(bytes))
+}
+
 fn get_latest_state_root(backup_handler: &BackupHandler) -> Result<Box<dyn Reply>> {
     let bytes = lcs::to_bytes(&backup_handler.get_latest_state_root()?)?;
     Ok(Box::new(bytes))
@@ -45,6 +53,72 @@ fn get_state_root_proof(
     Ok(Box::new(bytes))
 }
 
+fn get_transactions(
+    backup_handler: &BackupHandler,
+    start_version: Version,
+    num_transactions: u64,
+) -> Result<Box<dyn Reply>> {
+    ensure!(
+        start_version.checked_add(num_transactions).is_some(),
+        "Too many transactions requested."
+    );
+    let bh = backup_handler.clone();
+    Ok(size_prefixed_lcs_bytes_chunked_stream(
+        num_transactions,
+        move |offset, limit| {
+            size_prefixed_lcs_bytes(bh.get_transaction_iter(start_version + offset, limit)?)
+        },
+    ))
+}
+
+fn get_transaction_range_proof(
+    backup_handler: &BackupHandler,
+    first_version: Version,
+    last_version: Version,
+) -> Result<Box<dyn Reply>> {
+    let bytes =
+        lcs::to_bytes(&backup_handler.get_transaction_range_proof(first_version, last_version)?)?;
+    Ok(Box::new(bytes))
+}
+
+fn get_epoch_ending_ledger_infos(
+    backup_handler: &BackupHandler,
+    start_epoch: u64,
+    end_epoch: u64,
+) -> Result<Box<dyn Reply>> {
+    ensure!(
+        start_epoch <= end_epoch,
+        "Bad epoch range: [{}, {})",
+        start_epoch,
+        end_epoch
+    );
+    let bh = backup_handler.clone();
+    Ok(size_prefixed_lcs_bytes_chunked_stream(
+        end_epoch - start_epoch,
+        move |offset, limit| {
+            let chunk_start_epoch = start_epoch + offset;
+            size_prefixed_lcs_bytes(
+                bh.get_epoch_ending_ledger_info_iter(chunk_start_epoch, chunk_start_epoch + limit)?,
+            )
+        },
+    ))
+}
+
+/// Same format as `size_prefixed_lcs_bytes_stream()`, but all records are collected in memory.
+fn size_prefixed_lcs_bytes<I, R>(iter: I) -> Result<Vec<u8>>
+where
+    I: Iterator<Item = Result<R>>,
+    R: Serialize,
+{
+    let mut bytes = Vec::new();
+    for res in iter {
+        let record_bytes = lcs::to_bytes(&res?)?;
+        bytes.extend(&(record_bytes.len() as u32).to_be_bytes());
+        bytes.extend(&record_bytes);
+    }
+    Ok(bytes)
+}
+
 fn size_prefixed_lcs_bytes_stream<I, R>(iter: I) -> Box<dyn Reply>
 where
     I: Iterator<Item = Result<R>> + Send + Sync + 'static,
@@ -66,6 +140,22 @@ where
     Box::new(Response::new(Body::wrap_stream(stream::iter(iter))))
 }
 
+/// Streams `num_records` records in the format of `size_prefixed_lcs_bytes_stream()`, serializing
+/// `RECORDS_PER_CHUNK` of them at a time with `get_chunk(offset, limit)`. This is for iterators that
+/// borrow from the DB, which can't be moved into a stream: one is created for each chunk instead,
+/// so that only a chunk is held in memory.
+fn size_prefixed_lcs_bytes_chunked_stream<F>(num_records: u64, get_chunk: F) -> Box<dyn Reply>
+where
+    F: Fn(u64, u64) -> Result<Vec<u8>> + Send + Sync + 'static,
+{
+    let chunks = (0..num_records)
+        .step_by(RECORDS_PER_CHUNK as usize)
+        .map(move |offset| {
+            get_chunk(offset, min(RECORDS_PER_CHUNK, num_records - offset)).map(Bytes::from)
+        });
+    Box::new(Response::new(Body::wrap_stream(stream::iter(chunks))))
+}
+
 /// Return 500 on any error raised by the request handler.
 fn unwrap_or_500(result: Result<Box<dyn Reply>>) -> Box<dyn Reply> {
     match result {
@@ -84,6 +174,13 @@ async fn handle_rejection(err: Rejection) -> Result<impl Reply, Infallible> {
 }
 
 pub(crate) fn get_routes(backup_handler: BackupHandler) -> BoxedFilter<(impl Reply,)> {
+    // GET db_state
+    let bh = backup_handler.clone();
+    let db_state = warp::path::end()
+        .map(move || get_db_state(&bh))
+        .map(unwrap_or_500)
+        .recover(handle_rejection);
+
     // GET latest_state_root
     let bh = backup_handler.clone();
     let latest_state_root = warp::path::end()
@@ -106,19 +203,71 @@ pub(crate) fn get_routes(backup_handler: BackupHandler) -> BoxedFilter<(impl Rep
         .recover(handle_rejection);
 
     // GET state_root_proof/<version>
-    let bh = backup_handler;
+    let bh = backup_handler.clone();
     let state_root_proof = warp::path!(Version)
         .map(move |version| get_state_root_proof(&bh, version))
         .map(unwrap_or_500)
         .recover(handle_rejection);
 
+    // GET transactions/<start_version>/<num_transactions>
+    let bh = backup_handler.clone();
+    let transactions = warp::path!(Version / u64)
+        .map(move |start_version, num_transactions| {
+            get_transactions(&bh, start_version, num_transactions)
+        })
+        .map(unwrap_or_500)
+        .recover(handle_rejection);
+
+    // GET transaction_range_proof/<first_version>/<last_version>
+    let bh = backup_handler.clone();
+    let transaction_range_proof = warp::path!(Version / Version)
+        .map(move |first_version, last_version| {
+            get_transaction_range_proof(&bh, first_version, last_version)
+        })
+        .map(unwrap_or_500)
+        .recover(handle_rejection);
+
+    // GET epoch_ending_ledger_infos/<start_epoch>/<end_epoch>
+    let bh = backup_handler;
+    let epoch_ending_ledger_infos = warp::path!(u64 / u64)
+        .map(move |start_epoch, end_epoch| {
+            get_epoch_ending_ledger_infos(&bh, start_epoch, end_epoch)
+        })
+        .map(unwrap_or_500)
+        .recover(handle_rejection);
+
     // Route by endpoint name.
     let routes = warp::any()
-        .and(warp::path("latest_state_root").and(latest_state_root))
+        .and(warp::path("db_state").and(db_state))
+        .or(warp::path("latest_state_root").and(latest_state_root))
         .or(warp::path("state_range_proof").and(state_range_proof))
         .or(warp::path("state_snapshot").and(state_snapshot))
-        .or(warp::path("state_root_proof").and(state_root_proof));
+        .or(warp::path("state_root_proof").and(state_root_proof))
+        .or(warp::path("transactions").and(transactions))
+        .or(warp::path("transaction_range_proof").and(transaction_range_proof))
+        .or(warp::path("epoch_ending_ledger_infos").and(epoch_ending_ledger_infos));
 
     // Serve all routes for GET only.
     warp::get().and(routes).boxed()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunked_stream_matches_collected_records() {
+        let num_records = 2 * RECORDS_PER_CHUNK + 1;
+        let reply = size_prefixed_lcs_bytes_chunked_stream(num_records, |offset, limit| {
+            size_prefixed_lcs_bytes((offset..offset + limit).map(Ok))
+        });
+        let body = reply.into_response().into_body();
+        let bytes = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(hyper::body::to_bytes(body))
+            .unwrap();
+
+        let expected = size_prefixed_lcs_bytes((0..num_records).map(Ok)).unwrap();
+        assert_eq!(bytes.as_ref(), expected.as_slice());
+    }
+}
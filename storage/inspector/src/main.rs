@@ -3,24 +3,39 @@
 
 #![forbid(unsafe_code)]
 
-use anyhow::Result;
+use anyhow::{format_err, Result};
+use jellyfish_merkle::{
+    nibble_path::NibblePath,
+    node_type::{Node, NodeKey},
+};
 use libra_logger::info;
-use libradb::LibraDB;
-use std::path::PathBuf;
-use storage_interface::DbReader;
-use transaction_builder::get_transaction_name;
-
+use libra_nibble::Nibble;
 use libra_types::{
     account_address::AccountAddress, account_config::AccountResource, account_state::AccountState,
+    event::EventKey, transaction::Version,
 };
-use std::convert::TryFrom;
+use libradb::LibraDB;
+use serde::Serialize;
+use serde_json::json;
+use std::{convert::TryFrom, path::PathBuf};
+use storage_interface::DbReader;
 use structopt::StructOpt;
+use transaction_builder::get_transaction_name;
 
 #[derive(Debug, StructOpt)]
 struct Opt {
     #[structopt(long, parse(from_os_str))]
     db: PathBuf,
 
+    /// Open the DB as a RocksDB secondary instance, so that it can be inspected while a node is
+    /// running on it.
+    #[structopt(long)]
+    secondary: bool,
+
+    /// Print results as JSON, one value per line.
+    #[structopt(long)]
+    json: bool,
+
     #[structopt(subcommand)] // Note that we mark a field as a subcommand
     cmd: Option<Command>,
 }
@@ -38,115 +53,289 @@ enum Command {
     },
     #[structopt(name = "list-accounts")]
     ListAccounts,
+    /// Print events by event key in hex, starting from a sequence number.
+    #[structopt(name = "print-events")]
+    PrintEvents {
+        #[structopt(parse(try_from_str = parse_event_key))]
+        key: EventKey,
+        #[structopt(long, default_value = "0")]
+        start_seq_num: u64,
+        #[structopt(long, default_value = "100")]
+        limit: u64,
+    },
+    /// Print all ledger infos persisted, i.e. the last one of each epoch.
+    #[structopt(name = "list-ledger-infos")]
+    ListLedgerInfos,
+    /// Print the last ledger info of an epoch.
+    #[structopt(name = "print-ledger-info")]
+    PrintLedgerInfo { epoch: u64 },
+    /// Print the ledger infos ending epochs in [start_epoch, end_epoch).
+    #[structopt(name = "list-epoch-changes")]
+    ListEpochChanges {
+        #[structopt(long, default_value = "0")]
+        start_epoch: u64,
+        #[structopt(long)]
+        end_epoch: Option<u64>,
+    },
+    /// Print a Jellyfish Merkle tree node by its version and nibble path in hex, e.g. "" for the
+    /// root node.
+    #[structopt(name = "print-jmt-node")]
+    PrintJMTNode {
+        version: Version,
+        #[structopt(parse(try_from_str = parse_nibble_path), default_value = "")]
+        nibble_path: NibblePath,
+    },
+    /// Print the approximate size of each column family.
+    #[structopt(name = "cf-sizes")]
+    CFSizes,
+    /// Recompute the transaction accumulator and check every TransactionInfo and ledger info
+    /// against it.
+    #[structopt(name = "verify")]
+    Verify,
+}
+
+fn parse_event_key(s: &str) -> Result<EventKey> {
+    Ok(EventKey::try_from(hex::decode(s)?.as_slice())?)
+}
+
+fn parse_nibble_path(s: &str) -> Result<NibblePath> {
+    s.chars()
+        .map(|c| {
+            c.to_digit(16)
+                .map(|n| Nibble::from(n as u8))
+                .ok_or_else(|| format_err!("Invalid nibble: {}", c))
+        })
+        .collect()
+}
+
+/// Prints `value` as a line of JSON if requested, otherwise as `text`.
+fn output<T: Serialize>(json: bool, value: &T, text: impl FnOnce(&T) -> String) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string(value)?);
+    } else {
+        println!("{}", text(value));
+    }
+    Ok(())
 }
 
 /// Print out latest information stored in the DB.
-fn print_head(db: &LibraDB) -> Result<()> {
+fn print_head(db: &LibraDB, json: bool) -> Result<()> {
     let si = db
-        .get_startup_info()
-        .expect("Can't get startup info")
-        .expect("StartupInfo is empty, database is empty.");
-
+        .get_startup_info()?
+        .ok_or_else(|| format_err!("StartupInfo is empty, database is empty."))?;
     let version = si.latest_ledger_info.ledger_info().version();
-    info!("Version: {}", version);
+    let num_accounts = db.get_backup_handler().get_account_iter(version)?.count();
 
-    info!(
-        "The latest ledger info: {}",
-        si.latest_ledger_info.ledger_info()
+    let text = format!(
+        "Version: {}\nThe latest ledger info: {}\nSignatures: {:?}\nCurrent EpochState: {}\n\
+         Total Accounts: {}",
+        version,
+        si.latest_ledger_info.ledger_info(),
+        si.latest_ledger_info.signatures(),
+        si.get_epoch_state(),
+        num_accounts,
     );
+    output(
+        json,
+        &json!({
+            "version": version,
+            "latest_ledger_info": si.latest_ledger_info,
+            "epoch_state": si.get_epoch_state(),
+            "num_accounts": num_accounts,
+        }),
+        |_| text,
+    )?;
 
-    info!("Signatures: {:?}", si.latest_ledger_info.signatures());
-
-    info!("Current EpochState: {}", si.get_epoch_state());
-
-    let backup = db.get_backup_handler();
-    let iter = backup.get_account_iter(version)?;
-    let num_account_state = iter.count();
-    info!("Total Accounts: {}", num_account_state);
-
-    print_txn(db, version);
-
-    Ok(())
+    print_txn(db, version, json)
 }
 
-fn print_txn(db: &LibraDB, version: u64) {
-    let tx = db
-        .get_transaction_with_proof(version, version, false)
-        .expect("Unable to load latest TXN");
-    println!(
-        "Transaction {}: {}",
-        version,
-        tx.transaction.format_for_client(get_transaction_name)
-    );
+fn print_txn(db: &LibraDB, version: u64, json: bool) -> Result<()> {
+    let tx = db.get_transaction_with_proof(version, version, false)?;
+    output(json, &(version, tx.transaction), |(version, txn)| {
+        format!(
+            "Transaction {}: {}",
+            version,
+            txn.format_for_client(get_transaction_name)
+        )
+    })
 }
 
-fn print_account(db: &LibraDB, addr: AccountAddress) {
-    let maybe_blob = db
-        .get_latest_account_state(addr)
-        .expect("Unable to read AccountState");
-    if let Some(blob) = maybe_blob {
-        match AccountResource::try_from(&blob) {
-            Ok(r) => {
-                println!("Account {}: {:?}", addr, r);
-            }
-            Err(e) => {
-                info!(
-                    "Account {} exists, but have no AccountResource: {}.",
-                    addr, e
-                );
-            }
-        }
-    } else {
-        info!("Account {} doesn't exists", addr);
-    }
+fn print_account(db: &LibraDB, addr: AccountAddress, json: bool) -> Result<()> {
+    let resource = db
+        .get_latest_account_state(addr)?
+        .map(|blob| AccountResource::try_from(&blob).map_err(|e| e.to_string()));
+    let text = match &resource {
+        Some(Ok(r)) => format!("Account {}: {:?}", addr, r),
+        Some(Err(e)) => format!(
+            "Account {} exists, but have no AccountResource: {}.",
+            addr, e
+        ),
+        None => format!("Account {} doesn't exists", addr),
+    };
+    output(
+        json,
+        &json!({
+            "address": addr,
+            "exists": resource.is_some(),
+            "account_resource": resource.and_then(|res| res.ok()),
+        }),
+        |_| text,
+    )
 }
 
-fn list_txns(db: &LibraDB) {
-    let version = db
-        .get_latest_version()
-        .expect("Unable to get latest version");
+fn list_txns(db: &LibraDB, json: bool) -> Result<()> {
+    let version = db.get_latest_version()?;
     let backup = db.get_backup_handler();
-    let iter = backup
-        .get_transaction_iter(0, version)
-        .expect("Unable to get txn iter");
-    for (v, tx) in iter.enumerate() {
-        println!(
-            "TXN {}: {}",
-            v,
-            tx.expect("Unable to read TX")
-                .format_for_client(get_transaction_name)
-        );
+    let iter = backup.get_transaction_iter(0, version + 1)?;
+    for (v, res) in iter.enumerate() {
+        let (tx, txn_info, _events) = res?;
+        output(json, &(v, tx, txn_info), |(v, tx, _txn_info)| {
+            format!("TXN {}: {}", v, tx.format_for_client(get_transaction_name))
+        })?;
     }
+    Ok(())
 }
 
-fn list_accounts(db: &LibraDB) {
-    let version = db
-        .get_latest_version()
-        .expect("Unable to get latest version");
+fn list_accounts(db: &LibraDB, json: bool) -> Result<()> {
+    let version = db.get_latest_version()?;
     let backup = db.get_backup_handler();
-    let iter = backup
-        .get_account_iter(version)
-        .expect("Unagle to get account iter");
+    let iter = backup.get_account_iter(version)?;
     let mut num_account = 0;
     for res in iter {
-        match res {
-            Ok((_, blob)) => {
-                let accs = AccountState::try_from(&blob).expect("Failed to read AccountState");
-                let addr = accs
-                    .get_account_address()
-                    .expect("Could not get address from state");
-                match addr {
-                    Some(x) => {
-                        num_account += 1;
-                        println!("Address: {:?}", x);
-                    }
-                    None => println!("Skipping: No address for AccountState: {:?}", accs),
-                }
+        let (_, blob) = res?;
+        let accs = AccountState::try_from(&blob)?;
+        match accs.get_account_address()? {
+            Some(addr) => {
+                num_account += 1;
+                output(json, &addr, |addr| format!("Address: {:?}", addr))?;
             }
-            Err(x) => println!("Got err iterating through AccountStateBlobs {:?}", x),
+            None => info!("Skipping: No address for AccountState: {:?}", accs),
         }
     }
     info!("Total Accounts: {}", num_account);
+    Ok(())
+}
+
+fn print_events(
+    db: &LibraDB,
+    key: &EventKey,
+    start_seq_num: u64,
+    limit: u64,
+    json: bool,
+) -> Result<()> {
+    for (version, event) in db.get_events(key, start_seq_num, true /* ascending */, limit)? {
+        output(json, &(version, event), |(version, event)| {
+            format!("Version {}: {}", version, event)
+        })?;
+    }
+    Ok(())
+}
+
+fn list_ledger_infos(db: &LibraDB, json: bool) -> Result<()> {
+    for li in db.get_inspection_handler().get_ledger_infos()? {
+        output(json, &li, |li| li.ledger_info().to_string())?;
+    }
+    Ok(())
+}
+
+fn print_ledger_info(db: &LibraDB, epoch: u64, json: bool) -> Result<()> {
+    let li = db
+        .get_inspection_handler()
+        .get_ledger_info_in_epoch(epoch)?;
+    output(json, &li, |li| {
+        format!("{}\nSignatures: {:?}", li.ledger_info(), li.signatures())
+    })
+}
+
+fn list_epoch_changes(
+    db: &LibraDB,
+    start_epoch: u64,
+    end_epoch: Option<u64>,
+    json: bool,
+) -> Result<()> {
+    let end_epoch = match end_epoch {
+        Some(epoch) => epoch,
+        None => db.get_latest_ledger_info()?.ledger_info().epoch(),
+    };
+    let mut next_epoch = start_epoch;
+    while next_epoch < end_epoch {
+        let (lis, _more) = db.get_epoch_change_ledger_infos(next_epoch, end_epoch)?;
+        if lis.is_empty() {
+            break;
+        }
+        for li in lis {
+            next_epoch = li.ledger_info().epoch() + 1;
+            output(json, &li, |li| {
+                format!(
+                    "{}\nNext EpochState: {}",
+                    li.ledger_info(),
+                    li.ledger_info()
+                        .next_epoch_state()
+                        .map_or_else(|| "None".to_string(), |e| e.to_string())
+                )
+            })?;
+        }
+    }
+    Ok(())
+}
+
+fn print_jmt_node(db: &LibraDB, node_key: NodeKey, json: bool) -> Result<()> {
+    let node = db
+        .get_inspection_handler()
+        .get_jmt_node(&node_key)?
+        .ok_or_else(|| format_err!("Node {:?} not found.", node_key))?;
+    let value = match &node {
+        Node::Null => json!({ "type": "null" }),
+        Node::Internal(internal_node) => json!({
+            "type": "internal",
+            "hash": node.hash(),
+            "children": (0..16u8)
+                .filter_map(|n| {
+                    internal_node.child(Nibble::from(n)).map(|child| {
+                        json!({
+                            "nibble": n,
+                            "hash": child.hash,
+                            "version": child.version,
+                            "is_leaf": child.is_leaf,
+                        })
+                    })
+                })
+                .collect::<Vec<_>>(),
+        }),
+        Node::Leaf(leaf_node) => json!({
+            "type": "leaf",
+            "hash": node.hash(),
+            "account_key": leaf_node.account_key(),
+            "blob": leaf_node.blob(),
+        }),
+    };
+    output(json, &value, |_| format!("{:?}: {:#?}", node_key, node))
+}
+
+fn print_cf_sizes(db: &LibraDB, json: bool) -> Result<()> {
+    let sizes = db.get_inspection_handler().get_cf_sizes()?;
+    output(json, &sizes, |sizes| {
+        sizes
+            .iter()
+            .map(|(cf_name, size)| format!("{}: {} bytes", cf_name, size))
+            .collect::<Vec<_>>()
+            .join("\n")
+    })
+}
+
+fn verify(db: &LibraDB, json: bool) -> Result<()> {
+    let summary = db
+        .get_inspection_handler()
+        .verify_transaction_accumulator()?;
+    output(json, &summary, |summary| {
+        format!(
+            "Verified versions [{}, {}] and {} ledger infos. Accumulator root hash: {}",
+            summary.first_version,
+            summary.latest_version,
+            summary.num_ledger_infos_verified,
+            summary.root_hash,
+        )
+    })
 }
 
 fn main() {
@@ -164,29 +353,55 @@ fn main() {
     let log_dir = tempfile::tempdir().expect("Unable to get temp dir");
     info!("Opening DB at: {:?}, log at {:?}", p, log_dir.path());
 
-    let db =
-        LibraDB::open(p, true /* readonly */, None /* pruner */).expect("Unable to open LibraDB");
+    let db = if opt.secondary {
+        LibraDB::open_as_secondary(p, log_dir.path(), None /* catch_up_interval */)
+    } else {
+        LibraDB::open(
+            p, true,  /* readonly */
+            None,  /* pruner */
+            None,  /* ledger_pruner */
+            false, /* account_state_history */
+        )
+    }
+    .expect("Unable to open LibraDB");
     info!("DB opened successfully.");
 
-    if let Some(cmd) = opt.cmd {
+    let json = opt.json;
+    let res = if let Some(cmd) = opt.cmd {
         match cmd {
-            Command::ListTXNs => {
-                list_txns(&db);
-            }
-            Command::PrintTXN { version } => {
-                print_txn(&db, version);
-            }
-            Command::PrintAccount { address } => {
-                print_account(&db, address);
-            }
-            Command::ListAccounts => {
-                list_accounts(&db);
-            }
+            Command::ListTXNs => list_txns(&db, json),
+            Command::PrintTXN { version } => print_txn(&db, version, json),
+            Command::PrintAccount { address } => print_account(&db, address, json),
+            Command::ListAccounts => list_accounts(&db, json),
+            Command::PrintEvents {
+                key,
+                start_seq_num,
+                limit,
+            } => print_events(&db, &key, start_seq_num, limit, json),
+            Command::ListLedgerInfos => list_ledger_infos(&db, json),
+            Command::PrintLedgerInfo { epoch } => print_ledger_info(&db, epoch, json),
+            Command::ListEpochChanges {
+                start_epoch,
+                end_epoch,
+            } => list_epoch_changes(&db, start_epoch, end_epoch, json),
+            Command::PrintJMTNode {
+                version,
+                nibble_path,
+            } => print_jmt_node(&db, NodeKey::new(version, nibble_path), json),
+            Command::CFSizes => print_cf_sizes(&db, json),
+            Command::Verify => verify(&db, json),
         }
     } else {
-        print_head(&db).expect("Unable to read information from DB");
+        let res = print_head(&db, json);
+        if !json {
+            Opt::clap().print_help().unwrap();
+            println!();
+        }
+        res
+    };
 
-        Opt::clap().print_help().unwrap();
-        println!();
+    if let Err(e) = res {
+        eprintln!("Error: {:?}", e);
+        std::process::exit(1);
     }
 }
@@ -0,0 +1,118 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides `BackfillThread` which indexes by hash, in the background, the transactions
+//! committed before the transaction by hash index existed.
+
+use crate::{
+    pruner,
+    schema::{
+        db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
+        transaction_by_hash::TransactionByHashSchema,
+    },
+    transaction_store::TransactionStore,
+};
+use anyhow::Result;
+use libra_crypto::hash::CryptoHash;
+use libra_logger::prelude::*;
+use libra_types::transaction::Version;
+use schemadb::{SchemaBatch, DB};
+use std::{
+    cmp::max,
+    sync::{
+        mpsc::{channel, Receiver, Sender, TryRecvError},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+};
+
+// The number of transactions indexed by hash in each batch written by the backfill.
+const TRANSACTION_BY_HASH_BACKFILL_BATCH_SIZE: u64 = 10000;
+
+/// Backfills the transaction by hash index in a thread created on construction and joined on
+/// destruction.
+pub(crate) struct BackfillThread {
+    /// The thread handle, only becomes `None` after joined in `drop()`.
+    thread: Option<JoinHandle<()>>,
+    /// Dropping the sender side quits the thread after the batch it's writing.
+    quit_sender: Option<Mutex<Sender<()>>>,
+}
+
+impl BackfillThread {
+    pub fn new(db: Arc<DB>, transaction_store: Arc<TransactionStore>) -> Self {
+        let (quit_sender, quit_receiver) = channel::<()>();
+
+        let thread = std::thread::Builder::new()
+            .name("libradb_backfill".into())
+            .spawn(move || {
+                if let Err(e) =
+                    backfill_transaction_by_hash_index(&db, &transaction_store, &quit_receiver)
+                {
+                    error!("Failed backfilling transaction by hash index: {:?}", e);
+                }
+            })
+            .expect("Creating backfill thread should succeed.");
+
+        Self {
+            thread: Some(thread),
+            quit_sender: Some(Mutex::new(quit_sender)),
+        }
+    }
+}
+
+impl Drop for BackfillThread {
+    fn drop(&mut self) {
+        self.quit_sender.take();
+        self.thread
+            .take()
+            .expect("Backfill thread must exist.")
+            .join()
+            .expect("Backfill thread should join peacefully.");
+    }
+}
+
+/// Returns the version since which the transaction by hash index is complete, if it's been built.
+pub(crate) fn get_transaction_by_hash_start_version(db: &DB) -> Result<Option<Version>> {
+    Ok(db
+        .get::<DbMetadataSchema>(&DbMetadataKey::TransactionByHashStartVersion)?
+        .map(DbMetadataValue::expect_version))
+}
+
+/// Indexes by hash the readable transactions before the version the index is complete since, from
+/// the latest to the earliest. That version is lowered with each batch written, so that reads
+/// can tell which transactions are indexed, and an interrupted backfill resumes where it stopped.
+fn backfill_transaction_by_hash_index(
+    db: &DB,
+    transaction_store: &TransactionStore,
+    quit_receiver: &Receiver<()>,
+) -> Result<()> {
+    let key = DbMetadataKey::TransactionByHashStartVersion;
+    let mut start_version = match get_transaction_by_hash_start_version(db)? {
+        Some(version) => version,
+        None => return Ok(()),
+    };
+
+    while let Err(TryRecvError::Empty) = quit_receiver.try_recv() {
+        // Checked on every batch, since the pruner can be running at the same time.
+        let least_readable_version = pruner::get_ledger_pruner_progress(db)?;
+        if start_version <= least_readable_version {
+            info!("Backfilled transaction by hash index.");
+            break;
+        }
+
+        let end_version = start_version;
+        start_version = max(
+            end_version.saturating_sub(TRANSACTION_BY_HASH_BACKFILL_BATCH_SIZE),
+            least_readable_version,
+        );
+        let mut batch = SchemaBatch::new();
+        let txns =
+            transaction_store.get_transaction_iter(start_version, end_version - start_version)?;
+        for (version, txn) in (start_version..).zip(txns) {
+            batch.put::<TransactionByHashSchema>(&txn?.hash(), &version)?;
+        }
+        batch.put::<DbMetadataSchema>(&key, &DbMetadataValue::Version(start_version))?;
+        db.write_schemas(batch)?;
+    }
+    Ok(())
+}
@@ -5,19 +5,22 @@
 mod test;
 
 use crate::{
-    ledger_store::{LedgerStore, TransactionInfoIter},
+    event_store::EventStore,
+    ledger_store::{EpochEndingLedgerInfoIter, LedgerStore, TransactionInfoIter},
     state_store::StateStore,
-    transaction_store::{TransactionIter, TransactionStore},
+    transaction_store::TransactionStore,
 };
-use anyhow::Result;
+use anyhow::{ensure, Result};
 use jellyfish_merkle::iterator::JellyfishMerkleIterator;
 use libra_crypto::hash::HashValue;
 use libra_types::{
     account_state_blob::AccountStateBlob,
+    contract_event::ContractEvent,
     ledger_info::LedgerInfoWithSignatures,
-    proof::{SparseMerkleRangeProof, TransactionInfoWithProof},
-    transaction::Version,
+    proof::{SparseMerkleRangeProof, TransactionAccumulatorRangeProof, TransactionInfoWithProof},
+    transaction::{Transaction, TransactionInfo, Version},
 };
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
 /// `BackupHandler` provides functionalities for LibraDB data backup.
@@ -26,6 +29,7 @@ pub struct BackupHandler {
     ledger_store: Arc<LedgerStore>,
     transaction_store: Arc<TransactionStore>,
     state_store: Arc<StateStore>,
+    event_store: Arc<EventStore>,
 }
 
 impl BackupHandler {
@@ -33,22 +37,62 @@ impl BackupHandler {
         ledger_store: Arc<LedgerStore>,
         transaction_store: Arc<TransactionStore>,
         state_store: Arc<StateStore>,
+        event_store: Arc<EventStore>,
     ) -> Self {
         Self {
             ledger_store,
             transaction_store,
             state_store,
+            event_store,
         }
     }
 
-    /// Gets an iterator that yields a range of transactions.
+    /// Gets an iterator that yields a range of transactions, together with the `TransactionInfo`
+    /// and events of each of them.
     pub fn get_transaction_iter(
         &self,
         start_version: Version,
         num_transactions: u64,
-    ) -> Result<TransactionIter> {
-        self.transaction_store
-            .get_transaction_iter(start_version, num_transactions)
+    ) -> Result<impl Iterator<Item = Result<(Transaction, TransactionInfo, Vec<ContractEvent>)>> + '_>
+    {
+        let txn_iter = self
+            .transaction_store
+            .get_transaction_iter(start_version, num_transactions)?;
+        let txn_info_iter = self
+            .ledger_store
+            .get_transaction_info_iter(start_version, num_transactions)?;
+        let events_iter = self
+            .event_store
+            .get_events_by_version_iter(start_version, num_transactions)?;
+
+        let zipped = txn_iter.zip(txn_info_iter).zip(events_iter).map(
+            |((txn_res, txn_info_res), events_res)| Ok((txn_res?, txn_info_res?, events_res?)),
+        );
+        Ok(zipped)
+    }
+
+    /// Gets the proof for a transaction chunk.
+    /// N.B. the `LedgerInfo` returned will always be in the same epoch of the `last_version`.
+    pub fn get_transaction_range_proof(
+        &self,
+        first_version: Version,
+        last_version: Version,
+    ) -> Result<(TransactionAccumulatorRangeProof, LedgerInfoWithSignatures)> {
+        ensure!(
+            last_version >= first_version,
+            "Bad transaction range: [{}, {}]",
+            first_version,
+            last_version
+        );
+        let num_transactions = last_version - first_version + 1;
+        let epoch = self.ledger_store.get_epoch(last_version)?;
+        let ledger_info = self.ledger_store.get_latest_ledger_info_in_epoch(epoch)?;
+        let accumulator_proof = self.ledger_store.get_transaction_range_proof(
+            Some(first_version),
+            num_transactions,
+            ledger_info.ledger_info().version(),
+        )?;
+        Ok((accumulator_proof, ledger_info))
     }
 
     /// Gets an iterator that yields a range of transaction infos.
@@ -84,6 +128,17 @@ impl BackupHandler {
             .get_account_state_range_proof(rightmost_key, version)
     }
 
+    /// Gets the current epoch and the latest committed version, or `None` if the DB is empty.
+    pub fn get_db_state(&self) -> Result<Option<DbState>> {
+        Ok(self
+            .ledger_store
+            .get_latest_ledger_info_option()
+            .map(|li| DbState {
+                epoch: li.ledger_info().next_block_epoch(),
+                committed_version: li.ledger_info().version(),
+            }))
+    }
+
     /// Gets the latest version and state root hash.
     pub fn get_latest_state_root(&self) -> Result<(Version, HashValue)> {
         let (version, txn_info) = self.ledger_store.get_latest_transaction_info()?;
@@ -104,4 +159,24 @@ impl BackupHandler {
 
         Ok((txn_info, ledger_info))
     }
+
+    /// Gets an iterator that yields the epoch ending ledger infos of epochs in
+    /// [`start_epoch`, `end_epoch`).
+    pub fn get_epoch_ending_ledger_info_iter(
+        &self,
+        start_epoch: u64,
+        end_epoch: u64,
+    ) -> Result<EpochEndingLedgerInfoIter> {
+        self.ledger_store
+            .get_epoch_ending_ledger_info_iter(start_epoch, end_epoch)
+    }
+}
+
+/// Progress of the DB, as seen by backup tools.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct DbState {
+    /// The epoch new blocks are committed in. All epochs before it have ended.
+    pub epoch: u64,
+    /// Version of the latest committed ledger info.
+    pub committed_version: Version,
 }
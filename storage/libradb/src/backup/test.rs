@@ -3,6 +3,7 @@
 
 use crate::{test_helper::arb_blocks_to_commit, LibraDB};
 use anyhow::Result;
+use libra_crypto::hash::CryptoHash;
 use libra_temppath::TempPath;
 use proptest::prelude::*;
 use storage_interface::DbWriter;
@@ -25,19 +26,69 @@ proptest! {
         let expected: Vec<_> = input
             .iter()
             .flat_map(|(txns_to_commit, _ledger_info_with_sigs)| {
-                txns_to_commit
-                    .iter()
-                    .map(|txn_to_commit| txn_to_commit.transaction().clone())
+                txns_to_commit.iter().map(|txn_to_commit| {
+                    (
+                        txn_to_commit.transaction().clone(),
+                        txn_to_commit.events().to_vec(),
+                    )
+                })
             })
             .collect();
         prop_assert_eq!(expected.len() as u64, cur_ver);
 
+        let backup_handler = db.get_backup_handler();
+        let actual = backup_handler
+            .get_transaction_iter(0, cur_ver)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        for (txn, txn_info, _events) in &actual {
+            prop_assert_eq!(txn.hash(), txn_info.transaction_hash());
+        }
+        let actual: Vec<_> = actual
+            .into_iter()
+            .map(|(txn, _txn_info, events)| (txn, events))
+            .collect();
+        prop_assert_eq!(actual, expected);
+
+        let (range_proof, ledger_info) = backup_handler
+            .get_transaction_range_proof(0, cur_ver - 1)
+            .unwrap();
+        prop_assert!(ledger_info.ledger_info().version() >= cur_ver - 1);
+        prop_assert_eq!(range_proof.left_siblings().len(), 0);
+    }
+
+    #[test]
+    fn test_get_epoch_ending_ledger_info_iter(input in arb_blocks_to_commit()) {
+        let tmp_dir = TempPath::new();
+        let db = LibraDB::new_for_test(&tmp_dir);
+
+        let mut cur_ver = 0;
+        for (txns_to_commit, ledger_info_with_sigs) in input.iter() {
+            db.save_transactions(&txns_to_commit, cur_ver, Some(ledger_info_with_sigs))
+                .unwrap();
+            cur_ver += txns_to_commit.len() as u64;
+        }
+
+        let expected: Vec<_> = input
+            .iter()
+            .map(|(_txns_to_commit, ledger_info_with_sigs)| ledger_info_with_sigs)
+            .filter(|li| li.ledger_info().next_epoch_state().is_some())
+            .cloned()
+            .collect();
+        let num_epoch_ending = expected.len() as u64;
+
         let actual = db
             .get_backup_handler()
-            .get_transaction_iter(0, cur_ver)
+            .get_epoch_ending_ledger_info_iter(0, num_epoch_ending)
             .unwrap()
             .collect::<Result<Vec<_>>>()
             .unwrap();
         prop_assert_eq!(actual, expected);
+
+        let latest_li = input.last().unwrap().1.ledger_info();
+        let db_state = db.get_backup_handler().get_db_state().unwrap().unwrap();
+        prop_assert_eq!(db_state.epoch, latest_li.next_block_epoch());
+        prop_assert_eq!(db_state.committed_version, latest_li.version());
     }
 }
@@ -3,6 +3,7 @@
 
 //! This module defines error types used by [`LibraDB`](crate::LibraDB).
 
+use libra_types::transaction::Version;
 use thiserror::Error;
 
 /// This enum defines errors commonly used among [`LibraDB`](crate::LibraDB) APIs.
@@ -14,4 +15,7 @@ pub enum LibraDbError {
     /// Requested too many items.
     #[error("Too many items requested: at least {0} requested, max is {1}")]
     TooManyRequested(u64, u64),
+    /// Requested ledger history is pruned.
+    #[error("Version {0} is pruned, the least readable version is {1}.")]
+    Pruned(Version, Version),
 }
@@ -28,8 +28,8 @@ use libra_types::{
     proof::{position::Position, EventAccumulatorProof, EventProof},
     transaction::Version,
 };
-use schemadb::{schema::ValueCodec, ReadOptions, DB};
-use std::{convert::TryFrom, sync::Arc};
+use schemadb::{schema::ValueCodec, ReadOptions, SchemaIterator, DB};
+use std::{convert::TryFrom, iter::Peekable, sync::Arc};
 
 pub(crate) struct EventStore {
     db: Arc<DB>,
@@ -59,6 +59,25 @@ impl EventStore {
         Ok(events)
     }
 
+    /// Gets an iterator that yields the events emitted by each of `num_versions` transactions
+    /// starting from `start_version`. An empty `Vec` is yielded for a transaction that emitted no
+    /// events.
+    pub fn get_events_by_version_iter(
+        &self,
+        start_version: Version,
+        num_versions: u64,
+    ) -> Result<EventsByVersionIter> {
+        let mut iter = self.db.iter::<EventSchema>(ReadOptions::default())?;
+        iter.seek(&start_version)?;
+        Ok(EventsByVersionIter {
+            inner: iter.peekable(),
+            expected_next_version: start_version,
+            end_version: start_version
+                .checked_add(num_versions)
+                .ok_or_else(|| format_err!("Too many versions requested."))?,
+        })
+    }
+
     /// Get the event raw data given transaction version and the index of the event queried.
     pub fn get_event_with_proof_by_version_and_index(
         &self,
@@ -254,5 +273,44 @@ impl HashReader for EmptyReader {
     }
 }
 
+pub struct EventsByVersionIter<'a> {
+    inner: Peekable<SchemaIterator<'a, EventSchema>>,
+    expected_next_version: Version,
+    end_version: Version,
+}
+
+impl<'a> EventsByVersionIter<'a> {
+    fn next_impl(&mut self) -> Result<Option<Vec<ContractEvent>>> {
+        if self.expected_next_version >= self.end_version {
+            return Ok(None);
+        }
+
+        let mut ret = Vec::new();
+        while let Some(res) = self.inner.peek() {
+            let ((version, _index), _event) = res
+                .as_ref()
+                .map_err(|e| format_err!("Hit error iterating events: {}", e))?;
+            if *version != self.expected_next_version {
+                break;
+            }
+
+            let ((_version, _index), event) =
+                self.inner.next().transpose()?.expect("Known to exist.");
+            ret.push(event);
+        }
+        self.expected_next_version += 1;
+
+        Ok(Some(ret))
+    }
+}
+
+impl<'a> Iterator for EventsByVersionIter<'a> {
+    type Item = Result<Vec<ContractEvent>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_impl().transpose()
+    }
+}
+
 #[cfg(test)]
 mod test;
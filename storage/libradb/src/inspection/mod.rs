@@ -0,0 +1,146 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#[cfg(test)]
+mod test;
+
+use crate::{
+    ledger_store::LedgerStore,
+    pruner,
+    schema::{jellyfish_merkle_node::JellyfishMerkleNodeSchema, ledger_info::LedgerInfoSchema},
+};
+use accumulator::HashReader;
+use anyhow::{ensure, Result};
+use jellyfish_merkle::node_type::{Node, NodeKey};
+use libra_crypto::hash::{CryptoHash, HashValue, TransactionAccumulatorHasher};
+use libra_types::{
+    ledger_info::LedgerInfoWithSignatures,
+    proof::{accumulator::InMemoryAccumulator, position::Position},
+    transaction::Version,
+};
+use schemadb::{ColumnFamilyName, ReadOptions, DB};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+/// `InspectionHandler` provides low level access to LibraDB data and integrity checks, for
+/// debugging and forensics purposes.
+#[derive(Clone)]
+pub struct InspectionHandler {
+    db: Arc<DB>,
+    ledger_store: Arc<LedgerStore>,
+}
+
+impl InspectionHandler {
+    pub(crate) fn new(db: Arc<DB>, ledger_store: Arc<LedgerStore>) -> Self {
+        Self { db, ledger_store }
+    }
+
+    /// Gets all ledger infos persisted, i.e. the last ledger info of each epoch, in epoch order.
+    pub fn get_ledger_infos(&self) -> Result<Vec<LedgerInfoWithSignatures>> {
+        let mut iter = self.db.iter::<LedgerInfoSchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+        iter.map(|res| res.map(|(_epoch, li)| li)).collect()
+    }
+
+    /// Gets the last ledger info of `epoch`.
+    pub fn get_ledger_info_in_epoch(&self, epoch: u64) -> Result<LedgerInfoWithSignatures> {
+        self.ledger_store.get_latest_ledger_info_in_epoch(epoch)
+    }
+
+    /// Gets a node of the Jellyfish Merkle tree by its key.
+    pub fn get_jmt_node(&self, node_key: &NodeKey) -> Result<Option<Node>> {
+        self.db.get::<JellyfishMerkleNodeSchema>(node_key)
+    }
+
+    /// Gets the approximate size of each non-empty column family in bytes.
+    pub fn get_cf_sizes(&self) -> Result<BTreeMap<ColumnFamilyName, u64>> {
+        self.db.get_approximate_sizes_cf()
+    }
+
+    /// Recomputes the transaction accumulator from all the `TransactionInfo`s readable, i.e. those
+    /// not pruned, and checks them against the accumulator leaves persisted and against the root
+    /// hashes carried by the persisted ledger infos. Fails on the first mismatch found.
+    pub fn verify_transaction_accumulator(&self) -> Result<AccumulatorVerificationSummary> {
+        let (latest_version, _) = self.ledger_store.get_latest_transaction_info()?;
+        let first_version = pruner::get_ledger_pruner_progress(&self.db)?;
+        ensure!(
+            first_version <= latest_version,
+            "All transaction infos are pruned. Latest version: {}, least readable version: {}.",
+            latest_version,
+            first_version,
+        );
+
+        let mut ledger_infos_by_version: HashMap<Version, Vec<LedgerInfoWithSignatures>> =
+            HashMap::new();
+        for li in self.get_ledger_infos()? {
+            ledger_infos_by_version
+                .entry(li.ledger_info().version())
+                .or_default()
+                .push(li);
+        }
+
+        // Versions before `first_version` can be pruned, so start from the frozen subtrees.
+        let mut accumulator = InMemoryAccumulator::<TransactionAccumulatorHasher>::new(
+            self.ledger_store.get_frozen_subtree_hashes(first_version)?,
+            first_version,
+        )?;
+        let mut num_ledger_infos_verified = 0;
+        let txn_info_iter = self
+            .ledger_store
+            .get_transaction_info_iter(first_version, latest_version - first_version + 1)?;
+        for (version, txn_info_res) in (first_version..).zip(txn_info_iter) {
+            let txn_info_hash = txn_info_res?.hash();
+            let leaf_hash = self.ledger_store.get(Position::from_leaf_index(version))?;
+            ensure!(
+                txn_info_hash == leaf_hash,
+                "TransactionInfo at version {} has hash {}, while the accumulator leaf is {}.",
+                version,
+                txn_info_hash,
+                leaf_hash,
+            );
+            accumulator = accumulator.append(&[txn_info_hash]);
+
+            for li in ledger_infos_by_version.remove(&version).unwrap_or_default() {
+                ensure!(
+                    li.ledger_info().transaction_accumulator_hash() == accumulator.root_hash(),
+                    "LedgerInfo of epoch {} at version {} has accumulator root hash {}, while \
+                     the recomputed one is {}.",
+                    li.ledger_info().epoch(),
+                    version,
+                    li.ledger_info().transaction_accumulator_hash(),
+                    accumulator.root_hash(),
+                );
+                num_ledger_infos_verified += 1;
+            }
+        }
+        ensure!(
+            accumulator.num_leaves() == latest_version + 1,
+            "TransactionInfos missing after version {}.",
+            accumulator.num_leaves() - 1,
+        );
+
+        Ok(AccumulatorVerificationSummary {
+            first_version,
+            latest_version,
+            root_hash: accumulator.root_hash(),
+            num_ledger_infos_verified,
+        })
+    }
+}
+
+/// What `InspectionHandler::verify_transaction_accumulator` has checked.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AccumulatorVerificationSummary {
+    /// The first version verified, which is the least readable ledger version.
+    pub first_version: Version,
+    /// The last version verified.
+    pub latest_version: Version,
+    /// The recomputed root hash of the transaction accumulator at `latest_version`.
+    pub root_hash: HashValue,
+    /// Number of persisted ledger infos checked against the recomputed accumulator. Ledger infos
+    /// of pruned versions are not checked.
+    pub num_ledger_infos_verified: usize,
+}
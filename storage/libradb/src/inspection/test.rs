@@ -0,0 +1,54 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    schema::transaction_info::TransactionInfoSchema, test_helper::arb_blocks_to_commit, LibraDB,
+};
+use libra_crypto::HashValue;
+use libra_temppath::TempPath;
+use libra_types::{transaction::TransactionInfo, vm_error::StatusCode};
+use proptest::prelude::*;
+use storage_interface::DbWriter;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_verify_transaction_accumulator(input in arb_blocks_to_commit()) {
+        let tmp_dir = TempPath::new();
+        let db = LibraDB::new_for_test(&tmp_dir);
+
+        let mut cur_ver = 0;
+        for (txns_to_commit, ledger_info_with_sigs) in input.iter() {
+            db.save_transactions(&txns_to_commit, cur_ver, Some(ledger_info_with_sigs))
+                .unwrap();
+            cur_ver += txns_to_commit.len() as u64;
+        }
+
+        let inspection_handler = db.get_inspection_handler();
+        let summary = inspection_handler.verify_transaction_accumulator().unwrap();
+        let latest_li = input.last().unwrap().1.ledger_info();
+        prop_assert_eq!(summary.first_version, 0);
+        prop_assert_eq!(summary.latest_version, cur_ver - 1);
+        prop_assert_eq!(summary.root_hash, latest_li.transaction_accumulator_hash());
+        prop_assert_eq!(
+            summary.num_ledger_infos_verified,
+            inspection_handler.get_ledger_infos().unwrap().len()
+        );
+
+        // Tamper with a TransactionInfo.
+        db.db
+            .put::<TransactionInfoSchema>(
+                &(cur_ver - 1),
+                &TransactionInfo::new(
+                    HashValue::random(),
+                    HashValue::random(),
+                    HashValue::random(),
+                    0,
+                    StatusCode::UNKNOWN_STATUS,
+                ),
+            )
+            .unwrap();
+        prop_assert!(inspection_handler.verify_transaction_accumulator().is_err());
+    }
+}
@@ -25,8 +25,10 @@ use libra_types::{
     epoch_state::EpochState,
     ledger_info::LedgerInfoWithSignatures,
     proof::{
-        definition::LeafCount, position::Position, AccumulatorConsistencyProof,
-        TransactionAccumulatorProof, TransactionAccumulatorRangeProof, TransactionInfoWithProof,
+        definition::LeafCount,
+        position::{FrozenSubTreeIterator, Position},
+        AccumulatorConsistencyProof, TransactionAccumulatorProof, TransactionAccumulatorRangeProof,
+        TransactionInfoWithProof,
     },
     transaction::{TransactionInfo, Version},
 };
@@ -46,16 +48,8 @@ pub(crate) struct LedgerStore {
 impl LedgerStore {
     pub fn new(db: Arc<DB>) -> Self {
         // Upon restart, read the latest ledger info and signatures and cache them in memory.
-        let ledger_info = {
-            let mut iter = db
-                .iter::<LedgerInfoSchema>(ReadOptions::default())
-                .expect("Constructing iterator should work.");
-            iter.seek_to_last();
-            iter.next()
-                .transpose()
-                .expect("Reading latest ledger info from DB should work.")
-                .map(|kv| kv.1)
-        };
+        let ledger_info =
+            Self::read_latest_ledger_info(&db).expect("Reading latest ledger info should work.");
 
         Self {
             db,
@@ -63,6 +57,20 @@ impl LedgerStore {
         }
     }
 
+    fn read_latest_ledger_info(db: &DB) -> Result<Option<LedgerInfoWithSignatures>> {
+        let mut iter = db.iter::<LedgerInfoSchema>(ReadOptions::default())?;
+        iter.seek_to_last();
+        Ok(iter.next().transpose()?.map(|kv| kv.1))
+    }
+
+    /// Re-reads the latest ledger info from the DB into the cache. Needed when the DB is written
+    /// to by another instance, e.g. after a secondary instance catches up with the primary.
+    pub fn reload_latest_ledger_info(&self) -> Result<()> {
+        let ledger_info = Self::read_latest_ledger_info(&self.db)?;
+        self.latest_ledger_info.store(Arc::new(ledger_info));
+        Ok(())
+    }
+
     pub fn get_epoch(&self, version: Version) -> Result<u64> {
         let mut iter = self
             .db
@@ -133,6 +141,22 @@ impl LedgerStore {
         Ok((results, false))
     }
 
+    /// Gets an iterator that yields epoch ending ledger infos, starting from `start_epoch`, and
+    /// ends at the one before `end_epoch`
+    pub fn get_epoch_ending_ledger_info_iter(
+        &self,
+        start_epoch: u64,
+        end_epoch: u64,
+    ) -> Result<EpochEndingLedgerInfoIter> {
+        let mut iter = self.db.iter::<LedgerInfoSchema>(ReadOptions::default())?;
+        iter.seek(&start_epoch)?;
+        Ok(EpochEndingLedgerInfoIter {
+            inner: iter,
+            next_epoch: start_epoch,
+            end_epoch,
+        })
+    }
+
     pub fn get_latest_ledger_info_option(&self) -> Option<LedgerInfoWithSignatures> {
         let ledger_info_ptr = self.latest_ledger_info.load();
         let ledger_info: &Option<_> = ledger_info_ptr.deref();
@@ -179,11 +203,16 @@ impl LedgerStore {
     ) -> Result<TreeState> {
         Ok(TreeState::new(
             num_transactions,
-            Accumulator::get_frozen_subtree_hashes(self, num_transactions)?,
+            self.get_frozen_subtree_hashes(num_transactions)?,
             transaction_info.state_root_hash(),
         ))
     }
 
+    /// Gets the roots of the frozen subtrees of the accumulator with `num_leaves` leaves.
+    pub fn get_frozen_subtree_hashes(&self, num_leaves: LeafCount) -> Result<Vec<HashValue>> {
+        Accumulator::get_frozen_subtree_hashes(self, num_leaves)
+    }
+
     pub fn get_startup_info(&self) -> Result<Option<StartupInfo>> {
         // Get the latest ledger info. Return None if not bootstrapped.
         let latest_ledger_info = match self.get_latest_ledger_info_option() {
@@ -339,6 +368,48 @@ impl LedgerStore {
         Ok(root_hash)
     }
 
+    /// Write the frozen subtree roots of an accumulator of `num_leaves` leaves to `cs`, or, if they
+    /// already exist in the DB, make sure they match `frozen_subtrees`.
+    ///
+    /// `frozen_subtrees` is expected to be in the same order as yielded by
+    /// `FrozenSubTreeIterator`, i.e. from left to right. This is used to continue an accumulator
+    /// from a version other than 0 upon restoring a transaction backup.
+    pub fn confirm_or_save_frozen_subtrees(
+        &self,
+        num_leaves: LeafCount,
+        frozen_subtrees: &[HashValue],
+        cs: &mut ChangeSet,
+    ) -> Result<()> {
+        let positions: Vec<_> = FrozenSubTreeIterator::new(num_leaves).collect();
+        ensure!(
+            positions.len() == frozen_subtrees.len(),
+            "Number of frozen subtree roots not expected. Expected: {}, actual: {}",
+            positions.len(),
+            frozen_subtrees.len(),
+        );
+
+        positions
+            .iter()
+            .zip(frozen_subtrees.iter())
+            .map(|(position, hash)| {
+                if let Some(existing) = self.db.get::<TransactionAccumulatorSchema>(position)? {
+                    ensure!(
+                        *hash == existing,
+                        "Frozen subtree root at {} does not match that already in DB. \
+                         Provided: {:x}, in DB: {:x}.",
+                        position,
+                        hash,
+                        existing,
+                    );
+                } else {
+                    cs.batch
+                        .put::<TransactionAccumulatorSchema>(position, hash)?;
+                }
+                Ok(())
+            })
+            .collect::<Result<()>>()
+    }
+
     /// Write `ledger_info` to `cs`.
     pub fn put_ledger_info(
         &self,
@@ -405,6 +476,49 @@ impl<'a> Iterator for TransactionInfoIter<'a> {
     }
 }
 
+pub struct EpochEndingLedgerInfoIter<'a> {
+    inner: SchemaIterator<'a, LedgerInfoSchema>,
+    next_epoch: u64,
+    end_epoch: u64,
+}
+
+impl<'a> EpochEndingLedgerInfoIter<'a> {
+    fn next_impl(&mut self) -> Result<Option<LedgerInfoWithSignatures>> {
+        if self.next_epoch >= self.end_epoch {
+            return Ok(None);
+        }
+
+        let ret = match self.inner.next().transpose()? {
+            Some((epoch, li)) => {
+                ensure!(
+                    epoch == self.next_epoch,
+                    "Epochs are not consecutive. expecting: {}, got: {}",
+                    self.next_epoch,
+                    epoch,
+                );
+                ensure!(
+                    li.ledger_info().next_epoch_state().is_some(),
+                    "Latest LedgerInfo of epoch {} is not epoch ending.",
+                    epoch,
+                );
+                self.next_epoch += 1;
+                Some(li)
+            }
+            _ => None,
+        };
+
+        Ok(ret)
+    }
+}
+
+impl<'a> Iterator for EpochEndingLedgerInfoIter<'a> {
+    type Item = Result<LedgerInfoWithSignatures>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_impl().transpose()
+    }
+}
+
 #[cfg(test)]
 mod ledger_info_test;
 #[cfg(test)]
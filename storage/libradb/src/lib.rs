@@ -15,13 +15,16 @@ pub mod test_helper;
 
 pub mod backup;
 pub mod errors;
+pub mod inspection;
 pub mod schema;
 
+mod backfill;
 mod change_set;
 mod event_store;
 mod ledger_counters;
 mod ledger_store;
 mod pruner;
+mod secondary;
 mod state_store;
 mod system_store;
 mod transaction_store;
@@ -34,14 +37,20 @@ mod libradb_test;
 pub use libradb_test::test_save_blocks_impl;
 
 use crate::{
+    backfill::BackfillThread,
     backup::BackupHandler,
     change_set::{ChangeSet, SealedChangeSet},
     errors::LibraDbError,
     event_store::EventStore,
+    inspection::InspectionHandler,
     ledger_counters::LedgerCounters,
     ledger_store::LedgerStore,
     pruner::Pruner,
-    schema::*,
+    schema::{
+        db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
+        *,
+    },
+    secondary::CatchUpThread,
     state_store::StateStore,
     system_store::SystemStore,
     transaction_store::TransactionStore,
@@ -67,13 +76,21 @@ use libra_types::{
         SparseMerkleRangeProof, TransactionListProof,
     },
     transaction::{
-        TransactionInfo, TransactionListWithProof, TransactionToCommit, TransactionWithProof,
-        Version, PRE_GENESIS_VERSION,
+        Transaction, TransactionInfo, TransactionListWithProof, TransactionToCommit,
+        TransactionWithProof, Version, PRE_GENESIS_VERSION,
     },
 };
 use once_cell::sync::Lazy;
-use schemadb::{DB, DEFAULT_CF_NAME};
-use std::{iter::Iterator, path::Path, sync::Arc, time::Instant};
+use schemadb::{ColumnFamilyName, SchemaBatch, DB, DEFAULT_CF_NAME};
+use std::{
+    iter::Iterator,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
 use storage_interface::{DbReader, DbWriter, StartupInfo, TreeState};
 
 static OP_COUNTER: Lazy<OpMetrics> = Lazy::new(|| OpMetrics::new_and_registered("storage"));
@@ -127,19 +144,27 @@ pub struct LibraDB {
     ledger_store: Arc<LedgerStore>,
     transaction_store: Arc<TransactionStore>,
     state_store: Arc<StateStore>,
-    event_store: EventStore,
+    event_store: Arc<EventStore>,
     system_store: SystemStore,
     pruner: Option<Pruner>,
+    /// Only exists when opened as a secondary instance that follows the primary automatically.
+    catch_up_thread: Option<CatchUpThread>,
+    /// Only exists while transactions committed before the transaction by hash index existed are
+    /// being indexed.
+    backfill_thread: Option<BackfillThread>,
+    /// Whether to maintain the account state history index upon commits.
+    account_state_history: bool,
+    /// Set if the account state history index was maintained before but no longer is, in which
+    /// case the index is marked incomplete upon the first commit.
+    account_state_history_to_drop: AtomicBool,
 }
 
 impl LibraDB {
-    pub fn open<P: AsRef<Path> + Clone>(
-        db_root_path: P,
-        readonly: bool,
-        prune_window: Option<u64>,
-    ) -> Result<Self> {
-        let column_families = vec![
+    fn column_families() -> Vec<ColumnFamilyName> {
+        vec![
             /* LedgerInfo CF = */ DEFAULT_CF_NAME,
+            ACCOUNT_STATE_HISTORY_CF_NAME,
+            DB_METADATA_CF_NAME,
             EPOCH_BY_VERSION_CF_NAME,
             EVENT_ACCUMULATOR_CF_NAME,
             EVENT_BY_KEY_CF_NAME,
@@ -150,9 +175,35 @@ impl LibraDB {
             TRANSACTION_CF_NAME,
             TRANSACTION_ACCUMULATOR_CF_NAME,
             TRANSACTION_BY_ACCOUNT_CF_NAME,
+            TRANSACTION_BY_HASH_CF_NAME,
             TRANSACTION_INFO_CF_NAME,
-        ];
+        ]
+    }
 
+    fn new_with_db(db: Arc<DB>, pruner: Option<Pruner>) -> Self {
+        LibraDB {
+            db: Arc::clone(&db),
+            event_store: Arc::new(EventStore::new(Arc::clone(&db))),
+            ledger_store: Arc::new(LedgerStore::new(Arc::clone(&db))),
+            state_store: Arc::new(StateStore::new(Arc::clone(&db))),
+            transaction_store: Arc::new(TransactionStore::new(Arc::clone(&db))),
+            system_store: SystemStore::new(Arc::clone(&db)),
+            pruner,
+            catch_up_thread: None,
+            backfill_thread: None,
+            account_state_history: false,
+            account_state_history_to_drop: AtomicBool::new(false),
+        }
+    }
+
+    pub fn open<P: AsRef<Path> + Clone>(
+        db_root_path: P,
+        readonly: bool,
+        prune_window: Option<u64>,
+        ledger_prune_window: Option<u64>,
+        account_state_history: bool,
+    ) -> Result<Self> {
+        let column_families = Self::column_families();
         let path = db_root_path.as_ref().join("libradb");
         let instant = Instant::now();
 
@@ -168,15 +219,125 @@ impl LibraDB {
             instant.elapsed().as_millis()
         );
 
-        Ok(LibraDB {
-            db: Arc::clone(&db),
-            event_store: EventStore::new(Arc::clone(&db)),
-            ledger_store: Arc::new(LedgerStore::new(Arc::clone(&db))),
-            state_store: Arc::new(StateStore::new(Arc::clone(&db))),
-            transaction_store: Arc::new(TransactionStore::new(Arc::clone(&db))),
-            system_store: SystemStore::new(Arc::clone(&db)),
-            pruner: prune_window.map(|n| Pruner::new(Arc::clone(&db), n)),
-        })
+        let mut libradb = Self::new_with_db(db, None /* pruner */);
+        if !readonly {
+            libradb.set_up_account_state_history(account_state_history)?;
+            let start_version = libradb.set_up_transaction_by_hash_index()?;
+            if start_version > libradb.least_readable_ledger_version()? {
+                info!(
+                    "Backfilling transaction by hash index before version {} in the background.",
+                    start_version
+                );
+                libradb.backfill_thread = Some(BackfillThread::new(
+                    Arc::clone(&libradb.db),
+                    Arc::clone(&libradb.transaction_store),
+                ));
+            }
+        }
+        if prune_window.is_some() || ledger_prune_window.is_some() {
+            libradb.pruner = Some(Pruner::new(
+                Arc::clone(&libradb.db),
+                prune_window,
+                ledger_prune_window,
+            )?);
+        }
+        Ok(libradb)
+    }
+
+    /// Records the version since which the transaction by hash index is complete, the next one to
+    /// be committed, if the index hasn't existed. Returns that version, before which transactions
+    /// are indexed by the backfill thread.
+    fn set_up_transaction_by_hash_index(&self) -> Result<Version> {
+        if let Some(start_version) = backfill::get_transaction_by_hash_start_version(&self.db)? {
+            return Ok(start_version);
+        }
+        let next_version = self
+            .ledger_store
+            .get_latest_transaction_info_option()?
+            .map_or(0, |(version, _)| version + 1);
+        let mut batch = SchemaBatch::new();
+        batch.put::<DbMetadataSchema>(
+            &DbMetadataKey::TransactionByHashStartVersion,
+            &DbMetadataValue::Version(next_version),
+        )?;
+        self.db.write_schemas(batch)?;
+        Ok(next_version)
+    }
+
+    /// Starts or stops maintaining the account state history index. Once started, the index is
+    /// complete since the next version to be committed, which is recorded in the DB so that
+    /// queries of earlier versions can be rejected.
+    fn set_up_account_state_history(&mut self, enable: bool) -> Result<()> {
+        let key = DbMetadataKey::AccountStateHistoryStartVersion;
+        let start_version = self.db.get::<DbMetadataSchema>(&key)?;
+        if enable && start_version.is_none() {
+            let next_version = self
+                .ledger_store
+                .get_latest_transaction_info_option()?
+                .map_or(0, |(version, _)| version + 1);
+            let mut batch = SchemaBatch::new();
+            batch.put::<DbMetadataSchema>(&key, &DbMetadataValue::Version(next_version))?;
+            self.db.write_schemas(batch)?;
+        }
+        self.account_state_history = enable;
+        // Not dropped right away, so that merely opening the DB with tools doesn't lose the index.
+        self.account_state_history_to_drop = AtomicBool::new(!enable && start_version.is_some());
+        Ok(())
+    }
+
+    /// The account state history index is complete since this version.
+    fn account_state_history_start_version(&self) -> Result<Version> {
+        self.db
+            .get::<DbMetadataSchema>(&DbMetadataKey::AccountStateHistoryStartVersion)?
+            .map(DbMetadataValue::expect_version)
+            .ok_or_else(|| format_err!("Account state history index is not enabled."))
+    }
+
+    /// Opens the DB under `db_root_path`, which can be in use by a running node at the same time,
+    /// as a read-only secondary instance. RocksDB info logs of the secondary instance go under
+    /// `secondary_path`.
+    ///
+    /// Updates made by the primary become visible upon `try_catch_up_with_primary()`, which is
+    /// called every `catch_up_interval` in the background if provided. Versions pruned by the
+    /// primary are reported as pruned as usual, while the secondary instance never prunes.
+    pub fn open_as_secondary<P: AsRef<Path> + Clone>(
+        db_root_path: P,
+        secondary_path: P,
+        catch_up_interval: Option<Duration>,
+    ) -> Result<Self> {
+        let path = db_root_path.as_ref().join("libradb");
+        let secondary_path = secondary_path.as_ref().join("libradb_secondary");
+        std::fs::create_dir_all(&secondary_path)?;
+        let instant = Instant::now();
+
+        let db = Arc::new(DB::open_as_secondary(
+            path.clone(),
+            secondary_path,
+            "libradb_secondary",
+            Self::column_families(),
+        )?);
+
+        info!(
+            "Opened LibraDB at {:?} as secondary in {} ms",
+            path,
+            instant.elapsed().as_millis()
+        );
+
+        let mut libradb = Self::new_with_db(db, None /* pruner */);
+        libradb.catch_up_thread = catch_up_interval.map(|interval| {
+            CatchUpThread::new(
+                Arc::clone(&libradb.db),
+                Arc::clone(&libradb.ledger_store),
+                interval,
+            )
+        });
+        Ok(libradb)
+    }
+
+    /// Makes what the primary has written so far visible, for a DB opened via
+    /// `open_as_secondary()`.
+    pub fn try_catch_up_with_primary(&self) -> Result<()> {
+        secondary::catch_up_with_primary(&self.db, &self.ledger_store)
     }
 
     /// This opens db in non-readonly mode, without the pruner.
@@ -186,6 +347,8 @@ impl LibraDB {
             db_root_path,
             false, /* readonly */
             None,  /* pruner */
+            None,  /* ledger_pruner */
+            false, /* account_state_history */
         )
         .expect("Unable to open LibraDB")
     }
@@ -214,6 +377,7 @@ impl LibraDB {
         ledger_version: Version,
         fetch_events: bool,
     ) -> Result<TransactionWithProof> {
+        self.error_if_ledger_pruned(version)?;
         let proof = self
             .ledger_store
             .get_transaction_info_with_proof(version, ledger_version)?;
@@ -234,6 +398,13 @@ impl LibraDB {
         })
     }
 
+    // ================================ Inspection APIs =================================
+
+    /// Gets an instance of `InspectionHandler` for debugging and forensics purposes.
+    pub fn get_inspection_handler(&self) -> InspectionHandler {
+        InspectionHandler::new(Arc::clone(&self.db), Arc::clone(&self.ledger_store))
+    }
+
     // ================================== Backup APIs ===================================
 
     /// Gets an instance of `BackupHandler` for data backup purpose.
@@ -242,6 +413,7 @@ impl LibraDB {
             Arc::clone(&self.ledger_store),
             Arc::clone(&self.transaction_store),
             Arc::clone(&self.state_store),
+            Arc::clone(&self.event_store),
         )
     }
 
@@ -268,6 +440,84 @@ impl LibraDB {
         JellyfishMerkleRestore::new(&*self.state_store, version, expected_root_hash)
     }
 
+    /// Saves epoch ending ledger infos recovered from a backup.
+    pub fn save_ledger_infos(&self, ledger_infos: &[LedgerInfoWithSignatures]) -> Result<()> {
+        ensure!(!ledger_infos.is_empty(), "No LedgerInfos to save.");
+
+        let mut cs = ChangeSet::new();
+        ledger_infos
+            .iter()
+            .map(|li| self.ledger_store.put_ledger_info(li, &mut cs))
+            .collect::<Result<()>>()?;
+        self.db.write_schemas(cs.batch)?;
+
+        // Update the in-memory latest ledger info if the saved ones are newer.
+        let last_li = ledger_infos.last().expect("Known to be non-empty.");
+        let is_newer = match self.ledger_store.get_latest_ledger_info_option() {
+            Some(li) => li.ledger_info().epoch() < last_li.ledger_info().epoch(),
+            None => true,
+        };
+        if is_newer {
+            self.ledger_store.set_latest_ledger_info(last_li.clone());
+        }
+
+        Ok(())
+    }
+
+    /// Makes sure the frozen subtrees of the transaction accumulator of `num_leaves` leaves
+    /// exist in the DB, so that transactions recovered from a backup starting at version
+    /// `num_leaves` can be appended to it.
+    ///
+    /// `frozen_subtrees` are the root hashes of the frozen subtrees from left to right.
+    pub fn confirm_or_save_frozen_subtrees(
+        &self,
+        num_leaves: u64,
+        frozen_subtrees: &[HashValue],
+    ) -> Result<()> {
+        let mut cs = ChangeSet::new();
+        self.ledger_store
+            .confirm_or_save_frozen_subtrees(num_leaves, frozen_subtrees, &mut cs)?;
+        self.db.write_schemas(cs.batch)
+    }
+
+    /// Saves transactions recovered from a backup, together with their `TransactionInfo`s and
+    /// events. Unlike `save_transactions()`, the account states are not touched.
+    pub fn save_transactions_for_restore(
+        &self,
+        first_version: Version,
+        txns: &[Transaction],
+        txn_infos: &[TransactionInfo],
+        events: &[Vec<ContractEvent>],
+    ) -> Result<()> {
+        ensure!(
+            txns.len() == txn_infos.len() && txns.len() == events.len(),
+            "Lengths mismatch. txns: {}, txn_infos: {}, events: {}",
+            txns.len(),
+            txn_infos.len(),
+            events.len(),
+        );
+
+        let mut cs = ChangeSet::new();
+        for (idx, (txn, txn_info, txn_events)) in izip!(txns, txn_infos, events).enumerate() {
+            let version = first_version + idx as Version;
+            self.transaction_store
+                .put_transaction(version, txn, &mut cs)?;
+            let event_root_hash = self.event_store.put_events(version, txn_events, &mut cs)?;
+            ensure!(
+                event_root_hash == txn_info.event_root_hash(),
+                "Event root hash mismatch at version {}. Calculated: {:x}, in TransactionInfo: \
+                 {:x}",
+                version,
+                event_root_hash,
+                txn_info.event_root_hash(),
+            );
+        }
+        self.ledger_store
+            .put_transaction_infos(first_version, txn_infos, &mut cs)?;
+
+        self.db.write_schemas(cs.batch)
+    }
+
     // ================================== Private APIs ==================================
     fn get_events_by_event_key(
         &self,
@@ -318,6 +568,7 @@ impl LibraDB {
         let mut events_with_proof = event_keys
             .into_iter()
             .map(|(seq, ver, idx)| {
+                self.error_if_ledger_pruned(ver)?;
                 let (event, event_proof) = self
                     .event_store
                     .get_event_with_proof_by_version_and_index(ver, idx)?;
@@ -379,6 +630,19 @@ impl LibraDB {
             .iter()
             .map(|txn_to_commit| txn_to_commit.account_states().clone())
             .collect::<Vec<_>>();
+        if self.account_state_history {
+            self.state_store.put_account_state_history(
+                &account_state_sets,
+                first_version,
+                &mut cs,
+            )?;
+        } else if self
+            .account_state_history_to_drop
+            .swap(false, Ordering::Relaxed)
+        {
+            cs.batch
+                .delete::<DbMetadataSchema>(&DbMetadataKey::AccountStateHistoryStartVersion)?;
+        }
         let state_root_hashes =
             self.state_store
                 .put_account_state_sets(account_state_sets, first_version, &mut cs)?;
@@ -444,6 +708,32 @@ impl LibraDB {
         Ok(())
     }
 
+    /// The state of versions before this is pruned.
+    fn least_readable_state_version(&self) -> Result<Version> {
+        match self.pruner.as_ref() {
+            Some(pruner) => Ok(pruner.least_readable_state_version()),
+            None => pruner::get_state_pruner_progress(&self.db),
+        }
+    }
+
+    /// Versions of transactions, transaction infos and events before this are pruned.
+    fn least_readable_ledger_version(&self) -> Result<Version> {
+        match self.pruner.as_ref() {
+            Some(pruner) => Ok(pruner.least_readable_ledger_version()),
+            // The DB could have been pruned when opened with a pruner earlier.
+            None => pruner::get_ledger_pruner_progress(&self.db),
+        }
+    }
+
+    fn error_if_ledger_pruned(&self, version: Version) -> Result<()> {
+        let least_readable_version = self.least_readable_ledger_version()?;
+        if version < least_readable_version {
+            Err(LibraDbError::Pruned(version, least_readable_version).into())
+        } else {
+            Ok(())
+        }
+    }
+
     fn wake_pruner(&self, latest_version: Version) {
         if let Some(pruner) = self.pruner.as_ref() {
             pruner.wake(latest_version)
@@ -509,6 +799,7 @@ impl DbReader for LibraDB {
         if start_version > ledger_version || limit == 0 {
             return Ok(TransactionListWithProof::new_empty());
         }
+        self.error_if_ledger_pruned(start_version)?;
 
         let limit = std::cmp::min(limit, ledger_version - start_version + 1);
 
@@ -634,6 +925,7 @@ impl DbReader for LibraDB {
             ledger_version,
             latest_version
         );
+        self.error_if_ledger_pruned(version)?;
 
         let txn_info_with_proof = self
             .ledger_store
@@ -666,6 +958,78 @@ impl DbReader for LibraDB {
         Ok((version, txn_info.state_root_hash()))
     }
 
+    fn get_events_with_proofs(
+        &self,
+        event_key: &EventKey,
+        start: u64,
+        ascending: bool,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<EventWithProof>> {
+        self.get_events_by_event_key(event_key, start, ascending, limit, ledger_version)
+    }
+
+    fn get_txn_by_hash(
+        &self,
+        hash: HashValue,
+        ledger_version: Version,
+        fetch_events: bool,
+    ) -> Result<Option<TransactionWithProof>> {
+        match self
+            .transaction_store
+            .lookup_transaction_by_hash(&hash, ledger_version)?
+        {
+            Some(version) => self
+                .get_transaction_with_proof(version, ledger_version, fetch_events)
+                .map(Some),
+            None => {
+                // Transactions missing from an incomplete index can't be told apart from those
+                // which don't exist, e.g. while the index is being backfilled.
+                let start_version = backfill::get_transaction_by_hash_start_version(&self.db)?
+                    .ok_or_else(|| format_err!("Transaction by hash index is not built."))?;
+                ensure!(
+                    start_version <= self.least_readable_ledger_version()?,
+                    "Transaction by hash index is incomplete before version {}.",
+                    start_version,
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    fn get_account_state_history(
+        &self,
+        address: AccountAddress,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<Version>> {
+        error_if_too_many_requested(limit, MAX_LIMIT)?;
+        let start_version_indexed = self.account_state_history_start_version()?;
+        ensure!(
+            start_version >= start_version_indexed,
+            "Account state history is only indexed since version {}, requested: {}.",
+            start_version_indexed,
+            start_version,
+        );
+        // The index is pruned together with the state.
+        let least_readable_version = self.least_readable_state_version()?;
+        if start_version < least_readable_version {
+            return Err(LibraDbError::Pruned(start_version, least_readable_version).into());
+        }
+
+        self.state_store
+            .get_account_state_history(address, start_version, limit, ledger_version)
+    }
+
+    fn get_least_readable_ledger_version(&self) -> Result<Version> {
+        self.least_readable_ledger_version()
+    }
+
+    fn get_least_readable_state_version(&self) -> Result<Version> {
+        self.least_readable_state_version()
+    }
+
     fn get_latest_tree_state(&self) -> Result<TreeState> {
         let tree_state = match self.ledger_store.get_latest_transaction_info_option()? {
             Some((version, txn_info)) => self.ledger_store.get_tree_state(version + 1, txn_info)?,
@@ -4,7 +4,10 @@
 use super::*;
 #[allow(unused_imports)]
 use crate::{
-    schema::jellyfish_merkle_node::JellyfishMerkleNodeSchema,
+    schema::{
+        jellyfish_merkle_node::JellyfishMerkleNodeSchema,
+        transaction_by_hash::TransactionByHashSchema,
+    },
     test_helper::{arb_blocks_to_commit, arb_mock_genesis},
 };
 #[allow(unused_imports)]
@@ -342,6 +345,156 @@ fn verify_committed_transactions(
     );
 }
 
+fn test_open_as_secondary_impl(input: Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>) {
+    let tmp_dir = TempPath::new();
+    let secondary_tmp_dir = TempPath::new();
+    let db = LibraDB::new_for_test(&tmp_dir);
+    let secondary = LibraDB::open_as_secondary(
+        &tmp_dir,
+        &secondary_tmp_dir,
+        None, /* catch_up_interval */
+    )
+    .unwrap();
+    assert!(secondary.get_startup_info().unwrap().is_none());
+
+    let mut cur_ver = 0;
+    for (txns_to_commit, ledger_info_with_sigs) in input.iter() {
+        db.save_transactions(
+            &txns_to_commit,
+            cur_ver, /* first_version */
+            Some(ledger_info_with_sigs),
+        )
+        .unwrap();
+
+        secondary.try_catch_up_with_primary().unwrap();
+        assert_eq!(
+            secondary.get_latest_ledger_info().unwrap(),
+            *ledger_info_with_sigs
+        );
+        verify_committed_transactions(
+            &secondary,
+            &txns_to_commit,
+            cur_ver,
+            ledger_info_with_sigs,
+            true, /* is_latest */
+        );
+
+        cur_ver += txns_to_commit.len() as u64;
+    }
+    assert!(secondary
+        .save_transactions(&input[0].0, cur_ver, None)
+        .is_err());
+}
+
+fn test_account_state_history_impl(
+    input: Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>,
+) {
+    let tmp_dir = TempPath::new();
+    let db = LibraDB::open(
+        &tmp_dir, false, /* readonly */
+        None,  /* pruner */
+        None,  /* ledger_pruner */
+        true,  /* account_state_history */
+    )
+    .unwrap();
+
+    let mut expected: HashMap<AccountAddress, Vec<Version>> = HashMap::new();
+    let mut cur_ver = 0;
+    for (txns_to_commit, ledger_info_with_sigs) in input.iter() {
+        db.save_transactions(
+            &txns_to_commit,
+            cur_ver, /* first_version */
+            Some(ledger_info_with_sigs),
+        )
+        .unwrap();
+        for txn_to_commit in txns_to_commit {
+            for address in txn_to_commit.account_states().keys() {
+                expected.entry(*address).or_default().push(cur_ver);
+            }
+            cur_ver += 1;
+        }
+    }
+    let ledger_version = cur_ver - 1;
+
+    for (address, versions) in expected {
+        assert_eq!(
+            db.get_account_state_history(address, 0, MAX_LIMIT, ledger_version)
+                .unwrap(),
+            versions,
+        );
+        // Paginated.
+        assert_eq!(
+            db.get_account_state_history(address, versions[0] + 1, 1, ledger_version)
+                .unwrap(),
+            versions.iter().skip(1).take(1).cloned().collect::<Vec<_>>(),
+        );
+        // Not beyond the ledger version.
+        assert_eq!(
+            db.get_account_state_history(address, 0, MAX_LIMIT, versions[0])
+                .unwrap(),
+            vec![versions[0]],
+        );
+    }
+}
+
+fn test_transaction_by_hash_backfill_impl(
+    input: Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>,
+) {
+    let tmp_dir = TempPath::new();
+    let mut hashes = vec![];
+    {
+        let db = LibraDB::new_for_test(&tmp_dir);
+        let mut cur_ver = 0;
+        for (txns_to_commit, ledger_info_with_sigs) in input.iter() {
+            db.save_transactions(
+                &txns_to_commit,
+                cur_ver, /* first_version */
+                Some(ledger_info_with_sigs),
+            )
+            .unwrap();
+            cur_ver += txns_to_commit.len() as u64;
+        }
+
+        // Drop the index, as if the transactions were committed before it existed.
+        let mut batch = SchemaBatch::new();
+        for (txns_to_commit, _) in input.iter() {
+            for txn_to_commit in txns_to_commit {
+                let hash = txn_to_commit.transaction().hash();
+                batch.delete::<TransactionByHashSchema>(&hash).unwrap();
+                hashes.push(hash);
+            }
+        }
+        batch
+            .put::<DbMetadataSchema>(
+                &DbMetadataKey::TransactionByHashStartVersion,
+                &DbMetadataValue::Version(cur_ver),
+            )
+            .unwrap();
+        db.db.write_schemas(batch).unwrap();
+        assert!(db.get_txn_by_hash(hashes[0], cur_ver - 1, false).is_err());
+    }
+
+    let db = LibraDB::new_for_test(&tmp_dir);
+    let ledger_version = hashes.len() as u64 - 1;
+    // The index is backfilled in the background.
+    while backfill::get_transaction_by_hash_start_version(&db.db).unwrap() != Some(0) {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    for (version, hash) in hashes.into_iter().enumerate() {
+        assert_eq!(
+            db.get_txn_by_hash(hash, ledger_version, false)
+                .unwrap()
+                .unwrap()
+                .version,
+            version as u64,
+        );
+    }
+    assert!(db
+        .get_txn_by_hash(HashValue::random(), ledger_version, false)
+        .unwrap()
+        .is_none());
+}
+
 proptest! {
     #![proptest_config(ProptestConfig::with_cases(10))]
 
@@ -354,6 +507,21 @@ proptest! {
     fn test_sync_transactions(input in arb_blocks_to_commit()) {
         test_sync_transactions_impl(input);
     }
+
+    #[test]
+    fn test_open_as_secondary(input in arb_blocks_to_commit()) {
+        test_open_as_secondary_impl(input);
+    }
+
+    #[test]
+    fn test_account_state_history(input in arb_blocks_to_commit()) {
+        test_account_state_history_impl(input);
+    }
+
+    #[test]
+    fn test_transaction_by_hash_backfill(input in arb_blocks_to_commit()) {
+        test_transaction_by_hash_backfill_impl(input);
+    }
 }
 
 #[test]
@@ -379,6 +547,41 @@ fn test_too_many_requested() {
     assert!(db.get_transactions(0, 1001 /* limit */, 0, true).is_err());
 }
 
+#[test]
+fn test_account_state_history_start_version() {
+    let tmp_dir = TempPath::new();
+    let address = AccountAddress::random();
+    {
+        let db = LibraDB::new_for_test(&tmp_dir);
+        assert!(db.get_account_state_history(address, 0, 1, 0).is_err());
+        put_transaction_info(
+            &db,
+            0,
+            &TransactionInfo::new(
+                HashValue::random(),
+                HashValue::random(),
+                HashValue::random(),
+                0,
+                StatusCode::UNKNOWN_STATUS,
+            ),
+        );
+    }
+
+    let db = LibraDB::open(
+        &tmp_dir, false, /* readonly */
+        None,  /* pruner */
+        None,  /* ledger_pruner */
+        true,  /* account_state_history */
+    )
+    .unwrap();
+    // Version 0 was committed before the index was enabled.
+    assert!(db.get_account_state_history(address, 0, 1, 1).is_err());
+    assert!(db
+        .get_account_state_history(address, 1, 1, 1)
+        .unwrap()
+        .is_empty());
+}
+
 #[test]
 fn test_get_latest_tree_state() {
     let tmp_dir = TempPath::new();
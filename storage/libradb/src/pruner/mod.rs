@@ -6,14 +6,22 @@
 
 use crate::{
     schema::{
-        jellyfish_merkle_node::JellyfishMerkleNodeSchema, stale_node_index::StaleNodeIndexSchema,
+        account_state_history::AccountStateHistorySchema,
+        db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
+        event::EventSchema,
+        event_accumulator::EventAccumulatorSchema,
+        jellyfish_merkle_node::JellyfishMerkleNodeSchema,
+        stale_node_index::StaleNodeIndexSchema,
+        transaction::TransactionSchema,
+        transaction_by_hash::TransactionByHashSchema,
+        transaction_info::TransactionInfoSchema,
     },
     OP_COUNTER,
 };
 use anyhow::Result;
-use jellyfish_merkle::StaleNodeIndex;
+use jellyfish_merkle::{node_type::Node, StaleNodeIndex};
 use libra_logger::prelude::*;
-use libra_types::transaction::Version;
+use libra_types::{proof::position::Position, transaction::Version};
 use schemadb::{ReadOptions, SchemaBatch, SchemaIterator, DB};
 #[cfg(test)]
 use std::thread::sleep;
@@ -34,78 +42,159 @@ use std::{
 /// It creates a worker thread on construction and joins it on destruction. When destructed, it
 /// quits the worker thread eagerly without waiting for all pending work to be done.
 pub(crate) struct Pruner {
-    /// Other than the latest version, how many historical versions to keep being readable. For
-    /// example, this being 0 means keep only the latest version.
-    historical_versions_to_keep: u64,
+    /// Other than the latest version, how many historical versions of the state to keep being
+    /// readable. For example, this being 0 means keep only the latest version. `None` means the
+    /// state is not pruned.
+    historical_versions_to_keep: Option<u64>,
+    /// Same as `historical_versions_to_keep`, but for the ledger history, i.e. transactions,
+    /// transaction infos and events.
+    ledger_versions_to_keep: Option<u64>,
     /// The worker thread handle, created upon Pruner instance construction and joined upon its
     /// destruction. It only becomes `None` after joined in `drop()`.
     worker_thread: Option<JoinHandle<()>>,
     /// The sender side of the channel talking to the worker thread.
     command_sender: Mutex<Sender<Command>>,
-    /// (For tests) A way for the worker thread to inform the `Pruner` the pruning progress. If it
-    /// sets this atomic value to `V`, all versions before `V` can no longer be accessed.
-    #[allow(dead_code)]
+    /// A way for the worker thread to inform the `Pruner` the pruning progress. If it sets this
+    /// atomic value to `V`, all versions before `V` can no longer be accessed.
     worker_progress: Arc<AtomicU64>,
+    /// The worker thread informs the `Pruner` the ledger pruning progress the same way, so that
+    /// reads of pruned ledger history can be rejected.
+    ledger_worker_progress: Arc<AtomicU64>,
 }
 
 impl Pruner {
-    /// Creates a worker thread that waits on a channel for pruning commands.
-    pub fn new(db: Arc<DB>, historical_versions_to_keep: u64) -> Self {
+    /// Creates a worker thread that waits on a channel for pruning commands. Pruning resumes from
+    /// where it was left off, as recorded in the DB.
+    pub fn new(
+        db: Arc<DB>,
+        historical_versions_to_keep: Option<u64>,
+        ledger_versions_to_keep: Option<u64>,
+    ) -> Result<Self> {
         let (command_sender, command_receiver) = channel();
-        let worker_progress = Arc::new(AtomicU64::new(0));
+        let worker_progress = Arc::new(AtomicU64::new(get_state_pruner_progress(&db)?));
         let worker_progress_clone = Arc::clone(&worker_progress);
+        let ledger_worker_progress = Arc::new(AtomicU64::new(get_ledger_pruner_progress(&db)?));
+        let ledger_worker_progress_clone = Arc::clone(&ledger_worker_progress);
 
         let worker_thread = std::thread::Builder::new()
             .name("libradb_pruner".into())
-            .spawn(move || Worker::new(db, command_receiver, worker_progress_clone).work_loop())
+            .spawn(move || {
+                Worker::new(
+                    db,
+                    command_receiver,
+                    worker_progress_clone,
+                    ledger_worker_progress_clone,
+                )
+                .work_loop()
+            })
             .expect("Creating pruner thread should succeed.");
 
-        Self {
+        Ok(Self {
             historical_versions_to_keep,
+            ledger_versions_to_keep,
             worker_thread: Some(worker_thread),
             command_sender: Mutex::new(command_sender),
             worker_progress,
-        }
+            ledger_worker_progress,
+        })
     }
 
-    /// Sends pruning command to the worker thread when necessary.
+    /// Sends pruning commands to the worker thread when necessary.
     pub fn wake(&self, latest_version: Version) {
-        if latest_version > self.historical_versions_to_keep {
-            let least_readable_version = latest_version - self.historical_versions_to_keep;
-            self.command_sender
-                .lock()
-                .expect("command_sender to pruner thread should lock.")
-                .send(Command::Prune {
-                    least_readable_version,
-                })
-                .expect("Receiver should not destruct prematurely.");
+        if let Some(least_readable_version) =
+            least_readable_version(latest_version, self.historical_versions_to_keep)
+        {
+            self.send(Command::Prune {
+                least_readable_version,
+            });
         }
+        if let Some(least_readable_version) =
+            least_readable_version(latest_version, self.ledger_versions_to_keep)
+        {
+            self.send(Command::PruneLedger {
+                least_readable_version,
+            });
+        }
+    }
+
+    /// The state of versions before this is pruned.
+    pub fn least_readable_state_version(&self) -> Version {
+        self.worker_progress.load(Ordering::Relaxed)
+    }
+
+    /// Versions of transactions, transaction infos and events before this are pruned.
+    pub fn least_readable_ledger_version(&self) -> Version {
+        self.ledger_worker_progress.load(Ordering::Relaxed)
+    }
+
+    fn send(&self, command: Command) {
+        self.command_sender
+            .lock()
+            .expect("command_sender to pruner thread should lock.")
+            .send(command)
+            .expect("Receiver should not destruct prematurely.");
     }
 
     /// (For tests only.) Notifies the worker thread and waits for it to finish its job by polling
-    /// an internal counter.
+    /// internal counters.
     #[cfg(test)]
     pub fn wake_and_wait(&self, latest_version: Version) -> Result<()> {
         self.wake(latest_version);
 
-        if latest_version > self.historical_versions_to_keep {
-            let least_readable_version = latest_version - self.historical_versions_to_keep;
-            // Assuming no big pruning chunks will be issued by a test.
-            const TIMEOUT: Duration = Duration::from_secs(10);
-            let end = Instant::now() + TIMEOUT;
-
-            while Instant::now() < end {
-                if self.worker_progress.load(Ordering::Relaxed) >= least_readable_version {
-                    return Ok(());
-                }
-                sleep(Duration::from_millis(1));
+        let targets = [
+            (
+                least_readable_version(latest_version, self.historical_versions_to_keep),
+                &self.worker_progress,
+            ),
+            (
+                least_readable_version(latest_version, self.ledger_versions_to_keep),
+                &self.ledger_worker_progress,
+            ),
+        ];
+        // Assuming no big pruning chunks will be issued by a test.
+        const TIMEOUT: Duration = Duration::from_secs(10);
+        let end = Instant::now() + TIMEOUT;
+
+        while Instant::now() < end {
+            if targets.iter().all(|(target, progress)| match target {
+                Some(target) => progress.load(Ordering::Relaxed) >= *target,
+                None => true,
+            }) {
+                return Ok(());
             }
-            anyhow::bail!("Timeout waiting for pruner worker.");
+            sleep(Duration::from_millis(1));
         }
-        Ok(())
+        anyhow::bail!("Timeout waiting for pruner worker.");
     }
 }
 
+/// Returns the least version to keep readable if anything needs to be pruned.
+fn least_readable_version(
+    latest_version: Version,
+    versions_to_keep: Option<u64>,
+) -> Option<Version> {
+    match versions_to_keep {
+        Some(n) if latest_version > n => Some(latest_version - n),
+        _ => None,
+    }
+}
+
+/// Reads the state pruning progress persisted in the DB. Versions before it are pruned.
+pub(crate) fn get_state_pruner_progress(db: &DB) -> Result<Version> {
+    get_progress(db, DbMetadataKey::StatePrunerProgress)
+}
+
+/// Reads the ledger pruning progress persisted in the DB. Versions before it are pruned.
+pub(crate) fn get_ledger_pruner_progress(db: &DB) -> Result<Version> {
+    get_progress(db, DbMetadataKey::LedgerPrunerProgress)
+}
+
+fn get_progress(db: &DB, key: DbMetadataKey) -> Result<Version> {
+    Ok(db
+        .get::<DbMetadataSchema>(&key)?
+        .map_or(0, DbMetadataValue::expect_version))
+}
+
 impl Drop for Pruner {
     fn drop(&mut self) {
         self.command_sender
@@ -124,6 +213,7 @@ impl Drop for Pruner {
 enum Command {
     Quit,
     Prune { least_readable_version: Version },
+    PruneLedger { least_readable_version: Version },
 }
 
 struct Worker {
@@ -131,9 +221,13 @@ struct Worker {
     command_receiver: Receiver<Command>,
     target_least_readable_version: Version,
     /// Keeps a record of the pruning progress. If this equals to version `V`, we know versions
-    /// smaller than `V` are no longer readable.
-    /// This being an atomic value is to communicate the info with the Pruner thread (for tests).
+    /// smaller than `V` are no longer readable. Initialized with the progress persisted in the DB.
+    /// This being an atomic value is to communicate the info with the Pruner thread.
     least_readable_version: Arc<AtomicU64>,
+    target_least_readable_ledger_version: Version,
+    /// Same as `least_readable_version` but for the ledger history, initialized with the progress
+    /// persisted in the DB.
+    least_readable_ledger_version: Arc<AtomicU64>,
     /// Indicates if there's NOT any pending work to do currently, to hint
     /// `Self::receive_commands()` to `recv()` blocking-ly.
     blocking_recv: bool,
@@ -148,14 +242,20 @@ impl Worker {
         db: Arc<DB>,
         command_receiver: Receiver<Command>,
         least_readable_version: Arc<AtomicU64>,
+        least_readable_ledger_version: Arc<AtomicU64>,
     ) -> Self {
+        let progress = least_readable_version.load(Ordering::Relaxed);
         Self {
             db,
             command_receiver,
             least_readable_version,
-            target_least_readable_version: 0,
+            target_least_readable_version: progress,
+            target_least_readable_ledger_version: least_readable_ledger_version
+                .load(Ordering::Relaxed),
+            least_readable_ledger_version,
             blocking_recv: true,
-            index_min_nonpurged_version: 0,
+            // Index entries before the progress were purged or will never be looked at again.
+            index_min_nonpurged_version: progress,
             index_purged_at: Instant::now(),
         }
     }
@@ -164,35 +264,67 @@ impl Worker {
         while self.receive_commands() {
             // Process a reasonably small batch of work before trying to receive commands again,
             // in case `Command::Quit` is received (that's when we should quit.)
-            match prune_state(
-                Arc::clone(&self.db),
-                self.least_readable_version.load(Ordering::Relaxed),
-                self.target_least_readable_version,
-                Self::MAX_VERSIONS_TO_PRUNE_PER_BATCH,
-            ) {
-                Ok(least_readable_version) => {
-                    // Make next recv() blocking if all done.
-                    self.blocking_recv =
-                        least_readable_version == self.target_least_readable_version;
-
-                    // Log the progress.
-                    self.least_readable_version
-                        .store(least_readable_version, Ordering::Relaxed);
-                    OP_COUNTER.set(
-                        "pruner.least_readable_state_version",
-                        least_readable_version as usize,
-                    );
-
-                    // Try to purge the log.
-                    if let Err(e) = self.maybe_purge_index() {
-                        crit!("Failed purging state state node index, ignored. Err: {}", e);
-                    }
-                }
-                Err(e) => {
-                    crit!("Error pruning stale state nodes. {:?}", e);
-                    // On error, stop retrying vigorously by making next recv() blocking.
-                    self.blocking_recv = true;
+            let state_done = self.prune_state_batch();
+            let ledger_done = self.prune_ledger_batch();
+            // Make next recv() blocking if all done.
+            self.blocking_recv = state_done && ledger_done;
+        }
+    }
+
+    /// Returns `true` if there's no more state pruning to do for now.
+    fn prune_state_batch(&mut self) -> bool {
+        match prune_state(
+            Arc::clone(&self.db),
+            self.least_readable_version.load(Ordering::Relaxed),
+            self.target_least_readable_version,
+            Self::MAX_VERSIONS_TO_PRUNE_PER_BATCH,
+        ) {
+            Ok(least_readable_version) => {
+                // Log the progress.
+                self.least_readable_version
+                    .store(least_readable_version, Ordering::Relaxed);
+                OP_COUNTER.set(
+                    "pruner.least_readable_state_version",
+                    least_readable_version as usize,
+                );
+
+                // Try to purge the log.
+                if let Err(e) = self.maybe_purge_index() {
+                    crit!("Failed purging state state node index, ignored. Err: {}", e);
                 }
+
+                least_readable_version == self.target_least_readable_version
+            }
+            Err(e) => {
+                crit!("Error pruning stale state nodes. {:?}", e);
+                // On error, stop retrying vigorously.
+                true
+            }
+        }
+    }
+
+    /// Returns `true` if there's no more ledger pruning to do for now.
+    fn prune_ledger_batch(&mut self) -> bool {
+        match prune_ledger(
+            &self.db,
+            self.least_readable_ledger_version.load(Ordering::Relaxed),
+            self.target_least_readable_ledger_version,
+            Self::MAX_VERSIONS_TO_PRUNE_PER_BATCH,
+        ) {
+            Ok(least_readable_version) => {
+                self.least_readable_ledger_version
+                    .store(least_readable_version, Ordering::Relaxed);
+                OP_COUNTER.set(
+                    "pruner.least_readable_ledger_version",
+                    least_readable_version as usize,
+                );
+
+                least_readable_version == self.target_least_readable_ledger_version
+            }
+            Err(e) => {
+                crit!("Error pruning ledger history. {:?}", e);
+                // On error, stop retrying vigorously.
+                true
             }
         }
     }
@@ -232,6 +364,14 @@ impl Worker {
                         self.blocking_recv = false;
                     }
                 }
+                Command::PruneLedger {
+                    least_readable_version,
+                } => {
+                    if least_readable_version > self.target_least_readable_ledger_version {
+                        self.target_least_readable_ledger_version = least_readable_version;
+                        self.blocking_recv = false;
+                    }
+                }
             }
         }
     }
@@ -322,35 +462,123 @@ impl<'a> Iterator for StaleNodeIndicesByVersionIterator<'a> {
     }
 }
 
+/// Deletes the state nodes which became stale since `least_readable_version` up to
+/// `target_least_readable_version`, of `max_versions` versions at most, and returns the new least
+/// readable version.
+///
+/// If the account state history index is maintained, the entries of the accounts whose stale leaf
+/// nodes are deleted go together with them, since the states they point to are no longer readable.
+///
+/// The progress is saved even if nothing is deleted, so that it reaches the target when there are
+/// no stale nodes left before it, e.g. for versions whose transactions didn't change the state.
 pub fn prune_state(
     db: Arc<DB>,
     least_readable_version: Version,
     target_least_readable_version: Version,
     max_versions: usize,
 ) -> Result<Version> {
-    let indices = StaleNodeIndicesByVersionIterator::new(
+    let indices_by_version = StaleNodeIndicesByVersionIterator::new(
         &db,
         least_readable_version,
         target_least_readable_version,
     )?
-    .take(max_versions) // Iterator<Item = Result<Vec<StaleNodeIndex>>>
-    .collect::<Result<Vec<_>>>()? // now Vec<Vec<StaleNodeIndex>>
-    .into_iter()
-    .flatten()
-    .collect::<Vec<_>>();
-
-    if indices.is_empty() {
-        Ok(least_readable_version)
+    .take(max_versions)
+    .collect::<Result<Vec<_>>>()?;
+
+    // Fewer versions than asked for means no more stale nodes up to the target.
+    let new_least_readable_version = if indices_by_version.len() < max_versions {
+        std::cmp::max(least_readable_version, target_least_readable_version)
     } else {
-        let new_least_readable_version = indices.last().expect("Should exist.").stale_since_version;
-        let mut batch = SchemaBatch::new();
-        indices
-            .into_iter()
-            .map(|index| batch.delete::<JellyfishMerkleNodeSchema>(&index.node_key))
-            .collect::<Result<_>>()?;
-        db.write_schemas(batch)?;
-        Ok(new_least_readable_version)
+        indices_by_version
+            .last()
+            .and_then(|indices| indices.last())
+            .map_or(least_readable_version, |index| index.stale_since_version)
+    };
+    let prune_account_state_history = db
+        .get::<DbMetadataSchema>(&DbMetadataKey::AccountStateHistoryStartVersion)?
+        .is_some();
+    let mut batch = SchemaBatch::new();
+    for index in indices_by_version.into_iter().flatten() {
+        if prune_account_state_history {
+            // A leaf node is created at the version its account changed, or moved in the tree.
+            if let Some(Node::Leaf(leaf)) = db.get::<JellyfishMerkleNodeSchema>(&index.node_key)? {
+                batch.delete::<AccountStateHistorySchema>(&(
+                    leaf.account_key(),
+                    index.node_key.version(),
+                ))?;
+            }
+        }
+        batch.delete::<JellyfishMerkleNodeSchema>(&index.node_key)?;
     }
+    // Saved with the deletions so that the stale node index is not scanned from the beginning
+    // after restarts.
+    batch.put::<DbMetadataSchema>(
+        &DbMetadataKey::StatePrunerProgress,
+        &DbMetadataValue::Version(new_least_readable_version),
+    )?;
+    db.write_schemas(batch)?;
+    Ok(new_least_readable_version)
+}
+
+/// Deletes transactions (together with the by-hash index on them), transaction infos, events and
+/// the event accumulators of versions in `[least_readable_version, target_least_readable_version)`,
+/// `max_versions` versions at most, and returns the new least readable version.
+///
+/// The progress is saved in the same batch as the deletions, so pruning resumes correctly after
+/// restarts. The transaction accumulator is kept, since it is needed to prove newer transactions.
+/// So is the events by key index, which lookups by sequence number rely on being continuous, and
+/// the transactions by account index, so that looking up a pruned transaction by sequence number
+/// fails as pruned instead of finding nothing.
+pub fn prune_ledger(
+    db: &DB,
+    least_readable_version: Version,
+    target_least_readable_version: Version,
+    max_versions: usize,
+) -> Result<Version> {
+    let end_version = std::cmp::min(
+        target_least_readable_version,
+        least_readable_version.saturating_add(max_versions as u64),
+    );
+    if end_version <= least_readable_version {
+        return Ok(least_readable_version);
+    }
+
+    let mut batch = SchemaBatch::new();
+    for version in least_readable_version..end_version {
+        if let Some(txn) = db.get::<TransactionSchema>(&version)? {
+            batch.delete::<TransactionByHashSchema>(&txn.hash())?;
+        }
+        batch.delete::<TransactionSchema>(&version)?;
+        batch.delete::<TransactionInfoSchema>(&version)?;
+    }
+
+    let mut event_iter = db.iter::<EventSchema>(ReadOptions::default())?;
+    event_iter.seek(&least_readable_version)?;
+    for res in event_iter {
+        let ((version, index), _event) = res?;
+        if version >= end_version {
+            break;
+        }
+        batch.delete::<EventSchema>(&(version, index))?;
+    }
+
+    let mut event_accumulator_iter = db.iter::<EventAccumulatorSchema>(ReadOptions::default())?;
+    event_accumulator_iter.seek(&(least_readable_version, Position::from_inorder_index(0)))?;
+    for res in event_accumulator_iter {
+        let (key, _hash) = res?;
+        if key.0 >= end_version {
+            break;
+        }
+        batch.delete::<EventAccumulatorSchema>(&key)?;
+    }
+
+    batch.put::<DbMetadataSchema>(
+        &DbMetadataKey::LedgerPrunerProgress,
+        &DbMetadataValue::Version(end_version),
+    )?;
+    db.write_schemas(batch)?;
+
+    Ok(end_version)
 }
 
 #[cfg(test)]
@@ -2,11 +2,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::*;
-use crate::{change_set::ChangeSet, state_store::StateStore, LibraDB};
+use crate::{
+    change_set::ChangeSet, errors::LibraDbError, state_store::StateStore,
+    test_helper::arb_blocks_to_commit, LibraDB,
+};
 use libra_crypto::HashValue;
+use libra_proptest_helpers::ValueGenerator;
 use libra_temppath::TempPath;
 use libra_types::{account_address::AccountAddress, account_state_blob::AccountStateBlob};
 use std::collections::HashMap;
+use storage_interface::{DbReader, DbWriter};
 
 fn put_account_state_set(
     db: &DB,
@@ -49,7 +54,12 @@ fn test_pruner() {
     let tmp_dir = TempPath::new();
     let db = LibraDB::new_for_test(&tmp_dir).db;
     let state_store = &StateStore::new(Arc::clone(&db));
-    let pruner = Pruner::new(Arc::clone(&db), 0 /* historical_versions_to_keep */);
+    let pruner = Pruner::new(
+        Arc::clone(&db),
+        Some(0), /* historical_versions_to_keep */
+        None,    /* ledger_versions_to_keep */
+    )
+    .unwrap();
 
     let _root0 = put_account_state_set(
         &db,
@@ -136,6 +146,7 @@ fn test_worker_quit_eagerly() {
             Arc::clone(&db),
             command_receiver,
             Arc::new(AtomicU64::new(0)), /* progress */
+            Arc::new(AtomicU64::new(0)), /* ledger_progress */
         );
         command_sender
             .send(Command::Prune {
@@ -155,3 +166,181 @@ fn test_worker_quit_eagerly() {
         verify_state_in_store(state_store, address, Some(&value2), 2);
     }
 }
+
+#[test]
+fn test_state_pruner_progress_without_stale_nodes() {
+    let address = AccountAddress::new([1u8; AccountAddress::LENGTH]);
+    let tmp_dir = TempPath::new();
+    let db = LibraDB::new_for_test(&tmp_dir).db;
+    let state_store = &StateStore::new(Arc::clone(&db));
+    let value = AccountStateBlob::from(vec![0x01]);
+    put_account_state_set(&db, state_store, vec![(address, value.clone())], 0);
+
+    // Nothing becomes stale, but the progress still moves to the target.
+    assert_eq!(prune_state(Arc::clone(&db), 0, 5, 10).unwrap(), 5);
+    assert_eq!(get_state_pruner_progress(&db).unwrap(), 5);
+    verify_state_in_store(state_store, address, Some(&value), 0);
+
+    // Unless pruning zero versions is asked for.
+    assert_eq!(prune_state(Arc::clone(&db), 5, 8, 0).unwrap(), 5);
+    assert_eq!(get_state_pruner_progress(&db).unwrap(), 5);
+}
+
+#[test]
+fn test_state_pruner_prunes_account_state_history() {
+    let address = AccountAddress::new([1u8; AccountAddress::LENGTH]);
+    let tmp_dir = TempPath::new();
+    let db = LibraDB::new_for_test(&tmp_dir).db;
+    let state_store = &StateStore::new(Arc::clone(&db));
+    let mut batch = SchemaBatch::new();
+    batch
+        .put::<DbMetadataSchema>(
+            &DbMetadataKey::AccountStateHistoryStartVersion,
+            &DbMetadataValue::Version(0),
+        )
+        .unwrap();
+    db.write_schemas(batch).unwrap();
+    for version in 0..3 {
+        let account_state_set = vec![(address, AccountStateBlob::from(vec![version as u8]))];
+        let mut cs = ChangeSet::new();
+        state_store
+            .put_account_state_history(
+                &[account_state_set.iter().cloned().collect()],
+                version,
+                &mut cs,
+            )
+            .unwrap();
+        db.write_schemas(cs.batch).unwrap();
+        put_account_state_set(&db, state_store, account_state_set, version);
+    }
+    assert_eq!(
+        state_store
+            .get_account_state_history(address, 0, 10, 2)
+            .unwrap(),
+        vec![0, 1, 2],
+    );
+
+    // The states at versions 0 and 1 are gone, and so are the index entries pointing to them.
+    assert_eq!(prune_state(Arc::clone(&db), 0, 2, 10).unwrap(), 2);
+    assert_eq!(
+        state_store
+            .get_account_state_history(address, 0, 10, 2)
+            .unwrap(),
+        vec![2],
+    );
+}
+
+#[test]
+fn test_ledger_pruner() {
+    let tmp_dir = TempPath::new();
+    let blocks = ValueGenerator::new().generate(arb_blocks_to_commit());
+    let latest_version = {
+        let db = LibraDB::open(
+            &tmp_dir,
+            false,   /* readonly */
+            None,    /* pruner */
+            Some(1), /* ledger_pruner */
+            false,   /* account_state_history */
+        )
+        .unwrap();
+        let mut cur_ver = 0;
+        for (txns_to_commit, ledger_info_with_sigs) in &blocks {
+            db.save_transactions(txns_to_commit, cur_ver, Some(ledger_info_with_sigs))
+                .unwrap();
+            cur_ver += txns_to_commit.len() as u64;
+        }
+        let latest_version = cur_ver - 1;
+        db.pruner
+            .as_ref()
+            .unwrap()
+            .wake_and_wait(latest_version)
+            .unwrap();
+
+        // The latest 2 versions are readable.
+        if latest_version > 1 {
+            assert!(matches!(
+                db.get_transactions(0, 1, latest_version, false)
+                    .unwrap_err()
+                    .downcast::<LibraDbError>()
+                    .unwrap(),
+                LibraDbError::Pruned(0, v) if v == latest_version - 1
+            ));
+            assert!(db.event_store.get_events_by_version(0).unwrap().is_empty());
+            // Looking up a pruned transaction by account fails the same way.
+            let pruned_user_txn = blocks
+                .iter()
+                .flat_map(|(txns_to_commit, _)| txns_to_commit)
+                .enumerate()
+                .take((latest_version - 1) as usize)
+                .find_map(|(version, txn_to_commit)| {
+                    txn_to_commit
+                        .transaction()
+                        .as_signed_user_txn()
+                        .ok()
+                        .map(|txn| (version as Version, txn.sender(), txn.sequence_number()))
+                });
+            if let Some((version, sender, sequence_number)) = pruned_user_txn {
+                assert!(matches!(
+                    db.get_txn_by_account(sender, sequence_number, latest_version, false)
+                        .unwrap_err()
+                        .downcast::<LibraDbError>()
+                        .unwrap(),
+                    LibraDbError::Pruned(v, least_readable_version)
+                        if v == version && least_readable_version == latest_version - 1
+                ));
+            }
+        }
+        db.get_transactions(latest_version.saturating_sub(1), 2, latest_version, true)
+            .unwrap();
+        latest_version
+    };
+
+    // Progress survives restarts, even without the pruner.
+    let db = LibraDB::new_for_test(&tmp_dir);
+    if latest_version > 1 {
+        assert!(db
+            .get_transaction_with_proof(0, latest_version, false)
+            .is_err());
+    }
+    assert_eq!(
+        get_ledger_pruner_progress(&db.db).unwrap(),
+        latest_version.saturating_sub(1),
+    );
+}
+
+#[test]
+fn test_pruner_resumes() {
+    let address = AccountAddress::new([1u8; AccountAddress::LENGTH]);
+    let tmp_dir = TempPath::new();
+    let db = LibraDB::new_for_test(&tmp_dir).db;
+    let state_store = &StateStore::new(Arc::clone(&db));
+    for version in 0..3 {
+        put_account_state_set(
+            &db,
+            state_store,
+            vec![(address, AccountStateBlob::from(vec![version as u8]))],
+            version,
+        );
+    }
+
+    let new_pruner = || {
+        Pruner::new(
+            Arc::clone(&db),
+            Some(0), /* historical_versions_to_keep */
+            None,    /* ledger_versions_to_keep */
+        )
+        .unwrap()
+    };
+    {
+        let pruner = new_pruner();
+        assert_eq!(pruner.least_readable_state_version(), 0);
+        pruner.wake_and_wait(2 /* latest_version */).unwrap();
+    }
+    assert_eq!(get_state_pruner_progress(&db).unwrap(), 2);
+
+    // A new pruner picks up the progress.
+    let pruner = new_pruner();
+    assert_eq!(pruner.least_readable_state_version(), 2);
+    pruner.wake_and_wait(2 /* latest_version */).unwrap();
+    verify_state_in_store(state_store, address, Some(&vec![2u8].into()), 2);
+}
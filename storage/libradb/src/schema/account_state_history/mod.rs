@@ -0,0 +1,69 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for an index via which the versions at which the
+//! state of an account changed can be found, in order, without scanning every transaction. With
+//! the versions one can resort to the state Merkle tree for the account states.
+//!
+//! ```text
+//! |<--------key-------->|
+//! | account_key | version |
+//! ```
+//!
+//! `account_key` is the hash of the account address, the same as used in the state Merkle tree.
+//! `version` is serialized in big endian so that records in RocksDB will be in order of its numeric
+//! value.
+
+use crate::schema::{ensure_slice_len_eq, ACCOUNT_STATE_HISTORY_CF_NAME};
+use anyhow::Result;
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use libra_crypto::HashValue;
+use libra_types::transaction::Version;
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use std::mem::size_of;
+
+define_schema!(
+    AccountStateHistorySchema,
+    Key,
+    (),
+    ACCOUNT_STATE_HISTORY_CF_NAME
+);
+
+type Key = (HashValue, Version);
+
+impl KeyCodec<AccountStateHistorySchema> for Key {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let (ref account_key, version) = *self;
+
+        let mut encoded = account_key.to_vec();
+        encoded.write_u64::<BigEndian>(version)?;
+
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, HashValue::LENGTH + size_of::<Version>())?;
+
+        let account_key = HashValue::from_slice(&data[..HashValue::LENGTH])?;
+        let version = (&data[HashValue::LENGTH..]).read_u64::<BigEndian>()?;
+
+        Ok((account_key, version))
+    }
+}
+
+impl ValueCodec<AccountStateHistorySchema> for () {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(Vec::new())
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, 0)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,80 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for metadata about the DB itself, for example the
+//! progress of the pruner, which needs to survive restarts.
+//!
+//! ```text
+//! |<-----key----->|<-----value----->|
+//! | metadata type | metadata value  |
+//! ```
+
+use crate::schema::DB_METADATA_CF_NAME;
+use anyhow::Result;
+use libra_types::transaction::Version;
+#[cfg(test)]
+use proptest_derive::Arbitrary;
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use serde::{Deserialize, Serialize};
+
+define_schema!(
+    DbMetadataSchema,
+    DbMetadataKey,
+    DbMetadataValue,
+    DB_METADATA_CF_NAME
+);
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(test, derive(Arbitrary))]
+pub enum DbMetadataKey {
+    /// Transactions, transaction infos and events before this version are pruned.
+    LedgerPrunerProgress,
+    /// The state of versions before this is pruned.
+    StatePrunerProgress,
+    /// The account state history index is complete since this version. Absent if the index is not
+    /// maintained.
+    AccountStateHistoryStartVersion,
+    /// The transaction by hash index is complete since this version. It's lowered as transactions
+    /// committed before the index existed are backfilled.
+    TransactionByHashStartVersion,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(test, derive(Arbitrary))]
+pub enum DbMetadataValue {
+    Version(Version),
+}
+
+impl DbMetadataValue {
+    pub fn expect_version(self) -> Version {
+        match self {
+            Self::Version(version) => version,
+        }
+    }
+}
+
+impl KeyCodec<DbMetadataSchema> for DbMetadataKey {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        lcs::to_bytes(self).map_err(Into::into)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        lcs::from_bytes(data).map_err(Into::into)
+    }
+}
+
+impl ValueCodec<DbMetadataSchema> for DbMetadataValue {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        lcs::to_bytes(self).map_err(Into::into)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        lcs::from_bytes(data).map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod test;
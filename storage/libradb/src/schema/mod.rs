@@ -6,6 +6,8 @@
 //!
 //! All schemas are `pub(crate)` so not shown in rustdoc, refer to the source code to see details.
 
+pub(crate) mod account_state_history;
+pub(crate) mod db_metadata;
 pub(crate) mod epoch_by_version;
 pub(crate) mod event;
 pub(crate) mod event_accumulator;
@@ -17,11 +19,14 @@ pub(crate) mod stale_node_index;
 pub(crate) mod transaction;
 pub(crate) mod transaction_accumulator;
 pub(crate) mod transaction_by_account;
+pub(crate) mod transaction_by_hash;
 pub(crate) mod transaction_info;
 
 use anyhow::{ensure, Result};
 use schemadb::ColumnFamilyName;
 
+pub(super) const ACCOUNT_STATE_HISTORY_CF_NAME: ColumnFamilyName = "account_state_history";
+pub(super) const DB_METADATA_CF_NAME: ColumnFamilyName = "db_metadata";
 pub(super) const EPOCH_BY_VERSION_CF_NAME: ColumnFamilyName = "epoch_by_version";
 pub(super) const EVENT_ACCUMULATOR_CF_NAME: ColumnFamilyName = "event_accumulator";
 pub(super) const EVENT_BY_KEY_CF_NAME: ColumnFamilyName = "event_by_key";
@@ -32,6 +37,7 @@ pub(super) const STALE_NODE_INDEX_CF_NAME: ColumnFamilyName = "stale_node_index"
 pub(super) const TRANSACTION_CF_NAME: ColumnFamilyName = "transaction";
 pub(super) const TRANSACTION_ACCUMULATOR_CF_NAME: ColumnFamilyName = "transaction_accumulator";
 pub(super) const TRANSACTION_BY_ACCOUNT_CF_NAME: ColumnFamilyName = "transaction_by_account";
+pub(super) const TRANSACTION_BY_HASH_CF_NAME: ColumnFamilyName = "transaction_by_hash";
 pub(super) const TRANSACTION_INFO_CF_NAME: ColumnFamilyName = "transaction_info";
 
 fn ensure_slice_len_eq(data: &[u8], len: usize) -> Result<()> {
@@ -68,6 +74,11 @@ pub mod fuzzing {
     pub fn fuzz_decode(data: &[u8]) {
         #[allow(unused_must_use)]
         {
+            decode_key_value!(
+                super::account_state_history::AccountStateHistorySchema,
+                data
+            );
+            decode_key_value!(super::db_metadata::DbMetadataSchema, data);
             decode_key_value!(super::epoch_by_version::EpochByVersionSchema, data);
             decode_key_value!(super::event::EventSchema, data);
             decode_key_value!(super::event_accumulator::EventAccumulatorSchema, data);
@@ -88,6 +99,7 @@ pub mod fuzzing {
                 super::transaction_by_account::TransactionByAccountSchema,
                 data
             );
+            decode_key_value!(super::transaction_by_hash::TransactionByHashSchema, data);
             decode_key_value!(super::transaction_info::TransactionInfoSchema, data);
         }
     }
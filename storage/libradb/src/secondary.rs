@@ -0,0 +1,67 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides `CatchUpThread` which keeps a `LibraDB` instance opened as a RocksDB
+//! secondary instance following the primary instance, e.g. a running node, in the background.
+
+use crate::ledger_store::LedgerStore;
+use anyhow::Result;
+use libra_logger::prelude::*;
+use schemadb::DB;
+use std::{
+    sync::{
+        mpsc::{channel, RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+/// Catches up with the primary periodically in a thread created on construction and joined on
+/// destruction.
+pub(crate) struct CatchUpThread {
+    /// The thread handle, only becomes `None` after joined in `drop()`.
+    thread: Option<JoinHandle<()>>,
+    /// Dropping the sender side wakes up and quits the thread.
+    quit_sender: Option<Mutex<Sender<()>>>,
+}
+
+impl CatchUpThread {
+    pub fn new(db: Arc<DB>, ledger_store: Arc<LedgerStore>, interval: Duration) -> Self {
+        let (quit_sender, quit_receiver) = channel::<()>();
+
+        let thread = std::thread::Builder::new()
+            .name("libradb_catch_up".into())
+            .spawn(move || {
+                while let Err(RecvTimeoutError::Timeout) = quit_receiver.recv_timeout(interval) {
+                    if let Err(e) = catch_up_with_primary(&db, &ledger_store) {
+                        error!("Failed catching up with the primary DB: {:?}", e);
+                    }
+                }
+            })
+            .expect("Creating catch up thread should succeed.");
+
+        Self {
+            thread: Some(thread),
+            quit_sender: Some(Mutex::new(quit_sender)),
+        }
+    }
+}
+
+impl Drop for CatchUpThread {
+    fn drop(&mut self) {
+        self.quit_sender.take();
+        self.thread
+            .take()
+            .expect("Catch up thread must exist.")
+            .join()
+            .expect("Catch up thread should join peacefully.");
+    }
+}
+
+/// Applies what the primary has written since last time, and refreshes what is cached in memory
+/// accordingly.
+pub(crate) fn catch_up_with_primary(db: &DB, ledger_store: &LedgerStore) -> Result<()> {
+    db.try_catch_up_with_primary()?;
+    ledger_store.reload_latest_ledger_info()
+}
@@ -10,6 +10,7 @@ use crate::{
     change_set::ChangeSet,
     ledger_counters::LedgerCounter,
     schema::{
+        account_state_history::AccountStateHistorySchema,
         jellyfish_merkle_node::JellyfishMerkleNodeSchema, stale_node_index::StaleNodeIndexSchema,
     },
 };
@@ -25,7 +26,7 @@ use libra_types::{
     proof::{SparseMerkleProof, SparseMerkleRangeProof},
     transaction::Version,
 };
-use schemadb::{SchemaBatch, DB};
+use schemadb::{ReadOptions, SchemaBatch, DB};
 use std::{collections::HashMap, sync::Arc};
 
 pub(crate) struct StateStore {
@@ -105,6 +106,52 @@ impl StateStore {
         Ok(new_root_hash_vec)
     }
 
+    /// Records in the account state history index that the accounts in each of
+    /// `account_state_sets` changed at the respective version, starting from `first_version`.
+    pub fn put_account_state_history(
+        &self,
+        account_state_sets: &[HashMap<AccountAddress, AccountStateBlob>],
+        first_version: Version,
+        cs: &mut ChangeSet,
+    ) -> Result<()> {
+        (first_version..)
+            .zip(account_state_sets)
+            .flat_map(|(version, account_states)| {
+                account_states
+                    .keys()
+                    .map(move |address| (address.hash(), version))
+            })
+            .map(|key| cs.batch.put::<AccountStateHistorySchema>(&key, &()))
+            .collect()
+    }
+
+    /// Returns the versions, in ascending order, at which the state of the account changed, starting
+    /// from `start_version` and not exceeding `ledger_version`, up to `limit` of them.
+    pub fn get_account_state_history(
+        &self,
+        address: AccountAddress,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<Version>> {
+        let account_key = address.hash();
+        let mut iter = self
+            .db
+            .iter::<AccountStateHistorySchema>(ReadOptions::default())?;
+        iter.seek(&(account_key, start_version))?;
+
+        let mut versions = Vec::new();
+        while versions.len() < limit as usize {
+            match iter.next().transpose()? {
+                Some(((key, version), ())) if key == account_key && version <= ledger_version => {
+                    versions.push(version)
+                }
+                _ => break,
+            }
+        }
+        Ok(versions)
+    }
+
     #[cfg(test)]
     pub fn get_root_hash(&self, version: Version) -> Result<HashValue> {
         JellyfishMerkleTree::new(self).get_root_hash(version)
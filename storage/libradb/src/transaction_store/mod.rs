@@ -6,9 +6,13 @@
 use crate::{
     change_set::ChangeSet,
     errors::LibraDbError,
-    schema::{transaction::TransactionSchema, transaction_by_account::TransactionByAccountSchema},
+    schema::{
+        transaction::TransactionSchema, transaction_by_account::TransactionByAccountSchema,
+        transaction_by_hash::TransactionByHashSchema,
+    },
 };
 use anyhow::{ensure, format_err, Result};
+use libra_crypto::hash::{CryptoHash, HashValue};
 use libra_types::{
     account_address::AccountAddress,
     transaction::{Transaction, Version},
@@ -44,6 +48,18 @@ impl TransactionStore {
         Ok(None)
     }
 
+    /// Gets the version of a transaction by the hash of it.
+    pub fn lookup_transaction_by_hash(
+        &self,
+        hash: &HashValue,
+        ledger_version: Version,
+    ) -> Result<Option<Version>> {
+        Ok(self
+            .db
+            .get::<TransactionByHashSchema>(hash)?
+            .filter(|version| *version <= ledger_version))
+    }
+
     /// Get signed transaction given `version`
     pub fn get_transaction(&self, version: Version) -> Result<Transaction> {
         self.db
@@ -81,6 +97,8 @@ impl TransactionStore {
                 &version,
             )?;
         }
+        cs.batch
+            .put::<TransactionByHashSchema>(&transaction.hash(), &version)?;
         cs.batch.put::<TransactionSchema>(&version, &transaction)?;
 
         Ok(())
@@ -40,6 +40,12 @@ proptest! {
                     .unwrap(),
                 Some(ver as Version)
             );
+            prop_assert_eq!(
+                store
+                    .lookup_transaction_by_hash(&txn.hash(), ledger_version)
+                    .unwrap(),
+                Some(ver as Version)
+            );
         }
 
         prop_assert!(store.get_transaction(ledger_version + 1).is_err());
@@ -211,6 +211,42 @@ impl DB {
         DB::open_cf_readonly(&db_opts, path, name, column_families)
     }
 
+    /// Open db as a secondary instance of the primary db at `primary_path`, which can be opened
+    /// and written to by another process at the same time. The secondary instance keeps its own
+    /// info logs at `secondary_path` and only sees what the primary has written by the time it
+    /// was opened or last caught up via [`try_catch_up_with_primary`](DB::try_catch_up_with_primary).
+    pub fn open_as_secondary(
+        primary_path: impl AsRef<Path>,
+        secondary_path: impl AsRef<Path>,
+        name: &'static str,
+        column_families: Vec<ColumnFamilyName>,
+    ) -> Result<Self> {
+        let mut db_opts = rocksdb::Options::default();
+        // Required by RocksDB so that the secondary instance keeps all files open and isn't
+        // surprised by the primary deleting the ones it's not reading yet.
+        db_opts.set_max_open_files(-1);
+
+        let inner = rocksdb::DB::open_cf_as_secondary(
+            &db_opts,
+            primary_path.as_ref(),
+            secondary_path.as_ref(),
+            &column_families,
+        )?;
+
+        Ok(DB {
+            name,
+            inner,
+            column_families,
+        })
+    }
+
+    /// Applies updates the primary has made since this secondary instance was opened or last
+    /// caught up. Only meaningful for a db opened via [`open_as_secondary`](DB::open_as_secondary).
+    pub fn try_catch_up_with_primary(&self) -> Result<()> {
+        self.inner.try_catch_up_with_primary()?;
+        Ok(())
+    }
+
     fn open_cf(
         opts: &rocksdb::Options,
         path: impl AsRef<Path>,
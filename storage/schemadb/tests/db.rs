@@ -87,6 +87,19 @@ fn open_db_read_only(dir: &libra_temppath::TempPath) -> DB {
     DB::open_readonly(&dir.path(), "test", get_column_families()).expect("Failed to open DB.")
 }
 
+fn open_db_as_secondary(
+    dir: &libra_temppath::TempPath,
+    secondary_dir: &libra_temppath::TempPath,
+) -> DB {
+    DB::open_as_secondary(
+        &dir.path(),
+        &secondary_dir.path(),
+        "test",
+        get_column_families(),
+    )
+    .expect("Failed to open DB.")
+}
+
 struct TestDB {
     _tmpdir: libra_temppath::TempPath,
     db: DB,
@@ -316,6 +329,29 @@ fn test_open_read_only() {
     }
 }
 
+#[test]
+fn test_open_as_secondary() {
+    let tmpdir = libra_temppath::TempPath::new();
+    let secondary_tmpdir = libra_temppath::TempPath::new();
+    let db = open_db(&tmpdir);
+    db.put::<TestSchema1>(&TestField(0), &TestField(0)).unwrap();
+
+    let secondary = open_db_as_secondary(&tmpdir, &secondary_tmpdir);
+    assert_eq!(
+        secondary.get::<TestSchema1>(&TestField(0)).unwrap(),
+        Some(TestField(0)),
+    );
+
+    // Writes to the primary are seen only after catching up.
+    db.put::<TestSchema1>(&TestField(1), &TestField(1)).unwrap();
+    assert_eq!(secondary.get::<TestSchema1>(&TestField(1)).unwrap(), None);
+    secondary.try_catch_up_with_primary().unwrap();
+    assert_eq!(
+        secondary.get::<TestSchema1>(&TestField(1)).unwrap(),
+        Some(TestField(1)),
+    );
+}
+
 #[test]
 fn test_report_size() {
     let db = TestDB::new();
@@ -9,7 +9,7 @@ use libra_types::{
     account_address::AccountAddress,
     account_state::AccountState,
     account_state_blob::{AccountStateBlob, AccountStateWithProof},
-    contract_event::ContractEvent,
+    contract_event::{ContractEvent, EventWithProof},
     epoch_change::EpochChangeProof,
     epoch_state::EpochState,
     event::EventKey,
@@ -242,6 +242,55 @@ pub trait DbReader: Send + Sync {
     /// ../libradb/struct.LibraDB.html#method.get_latest_state_root
     fn get_latest_state_root(&self) -> Result<(Version, HashValue)>;
 
+    /// Returns the versions, in ascending order, at which the state of the account changed,
+    /// starting from `start_version` and not exceeding `ledger_version`, up to `limit` of them.
+    /// Only available if the DB maintains the account state history index.
+    fn get_account_state_history(
+        &self,
+        _address: AccountAddress,
+        _start_version: Version,
+        _limit: u64,
+        _ledger_version: Version,
+    ) -> Result<Vec<Version>> {
+        Err(format_err!("Account state history is not supported."))
+    }
+
+    /// Returns events by given event key, each with the proof against the ledger at
+    /// `ledger_version`.
+    fn get_events_with_proofs(
+        &self,
+        _event_key: &EventKey,
+        _start: u64,
+        _ascending: bool,
+        _limit: u64,
+        _ledger_version: Version,
+    ) -> Result<Vec<EventWithProof>> {
+        Err(format_err!("Events with proofs are not supported."))
+    }
+
+    /// Returns the transaction with hash `hash`, if it is committed at or before `ledger_version`,
+    /// with the proof against the ledger at `ledger_version`.
+    fn get_txn_by_hash(
+        &self,
+        _hash: HashValue,
+        _ledger_version: Version,
+        _fetch_events: bool,
+    ) -> Result<Option<TransactionWithProof>> {
+        Err(format_err!("Transaction lookup by hash is not supported."))
+    }
+
+    /// Returns the least version of which the transactions, transaction infos and events are
+    /// readable, anything older might have been pruned.
+    fn get_least_readable_ledger_version(&self) -> Result<Version> {
+        Ok(0)
+    }
+
+    /// Returns the least version of which the state is readable, anything older might have been
+    /// pruned.
+    fn get_least_readable_state_version(&self) -> Result<Version> {
+        Ok(0)
+    }
+
     /// Gets the latest TreeState no matter if db has been bootstrapped.
     /// Used by the Db-bootstrapper.
     fn get_latest_tree_state(&self) -> Result<TreeState>;
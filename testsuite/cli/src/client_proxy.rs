@@ -126,9 +126,13 @@ impl ClientProxy {
         mnemonic_file: Option<String>,
         waypoint: Waypoint,
     ) -> Result<Self> {
-        // fail fast if url is not valid
-        let url = Url::parse(url)?;
-        let mut client = LibraClient::new(url.clone(), waypoint)?;
+        // fail fast if any url is not valid
+        let urls = url
+            .split(',')
+            .map(Url::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        let url = urls[0].clone();
+        let mut client = LibraClient::new(urls, waypoint)?;
 
         let accounts = vec![];
 
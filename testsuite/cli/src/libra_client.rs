@@ -10,7 +10,7 @@ use libra_json_rpc_client::{
         AccountStateWithProofView, AccountView, BlockMetadata, BytesView, CurrencyInfoView,
         EventView, StateProofView, TransactionView,
     },
-    JsonRpcBatch, JsonRpcClient, JsonRpcResponse, ResponseAsView,
+    JsonRpcBatch, JsonRpcPoolClient, JsonRpcPoolConfig, JsonRpcResponse, ResponseAsView,
 };
 use libra_logger::prelude::*;
 use libra_types::{
@@ -30,7 +30,8 @@ use reqwest::Url;
 /// A client connection to an AdmissionControl (AC) service. `LibraClient` also
 /// handles verifying the server's responses, retrying on non-fatal failures, and
 /// ratcheting our latest verified state, which includes the latest verified
-/// version and latest verified epoch change ledger info.
+/// version and latest verified epoch change ledger info. Requests fail over
+/// between the full nodes given, see `JsonRpcPoolClient`.
 ///
 /// ### Note
 ///
@@ -42,7 +43,7 @@ use reqwest::Url;
 /// 3. We make another request to the remote AC service. In this case, the remote
 ///    AC will be behind us and we will reject their response as stale.
 pub struct LibraClient {
-    client: JsonRpcClient,
+    client: JsonRpcPoolClient,
     /// The latest verified chain state.
     trusted_state: TrustedState,
     /// The most recent epoch change ledger info. This is `None` if we only know
@@ -52,10 +53,10 @@ pub struct LibraClient {
 }
 
 impl LibraClient {
-    /// Construct a new Client instance.
-    pub fn new(url: Url, waypoint: Waypoint) -> Result<Self> {
+    /// Construct a new Client instance over the full nodes at `urls`.
+    pub fn new(urls: Vec<Url>, waypoint: Waypoint) -> Result<Self> {
         let initial_trusted_state = TrustedState::from(waypoint);
-        let client = JsonRpcClient::new(url)?;
+        let client = JsonRpcPoolClient::new(urls, JsonRpcPoolConfig::default())?;
         Ok(LibraClient {
             client,
             trusted_state: initial_trusted_state,
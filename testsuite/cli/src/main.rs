@@ -26,7 +26,8 @@ use structopt::StructOpt;
     about = "Libra client to connect to a specific validator"
 )]
 struct Args {
-    /// Full URL address to connect to - should include port number, if applicable
+    /// Full URL address to connect to - should include port number, if applicable. Several full
+    /// nodes can be given, separated by commas, for requests to fail over between them
     #[structopt(short = "u", long)]
     pub url: String,
     /// Path to the generated keypair for the faucet account. The faucet account can be used to
@@ -385,6 +385,16 @@ impl SignedTransaction {
         }
     }
 
+    pub fn new_with_authenticator(
+        raw_txn: RawTransaction,
+        authenticator: TransactionAuthenticator,
+    ) -> SignedTransaction {
+        SignedTransaction {
+            raw_txn,
+            authenticator,
+        }
+    }
+
     pub fn authenticator(&self) -> TransactionAuthenticator {
         self.authenticator.clone()
     }
@@ -427,6 +437,19 @@ impl SignedTransaction {
             .len()
     }
 
+    /// Returns the hash of the transaction once committed, i.e. that of the
+    /// `Transaction::UserTransaction` wrapping it, without cloning it into one.
+    pub fn committed_hash(&self) -> HashValue {
+        use libra_crypto::hash::CryptoHasher;
+
+        let mut state = TransactionHasher::default();
+        // `Transaction::UserTransaction` is serialized as its variant index followed by the
+        // `SignedTransaction`.
+        state.update(&[0]);
+        lcs::serialize_into(&mut state, self).expect("Unable to serialize SignedTransaction");
+        state.finish()
+    }
+
     /// Checks that the signature of given transaction. Returns `Ok(SignatureCheckedTransaction)` if
     /// the signature is valid.
     pub fn check_signature(self) -> Result<SignatureCheckedTransaction> {
@@ -12,6 +12,7 @@ use crate::{
 use lcs::test_helpers::assert_canonical_encode_decode;
 use libra_crypto::{
     ed25519::{self, Ed25519PrivateKey, Ed25519Signature},
+    hash::CryptoHash,
     PrivateKey, Uniform,
 };
 use proptest::prelude::*;
@@ -44,6 +45,14 @@ proptest! {
         assert!(signed_txn.check_signature().is_ok());
     }
 
+    #[test]
+    fn test_committed_hash(signed_txn in any::<SignedTransaction>()) {
+        assert_eq!(
+            signed_txn.committed_hash(),
+            Transaction::UserTransaction(signed_txn.clone()).hash()
+        );
+    }
+
     #[test]
     fn transaction_payload_lcs_roundtrip(txn_payload in any::<TransactionPayload>()) {
         assert_canonical_encode_decode(txn_payload);